@@ -0,0 +1,8 @@
+#![no_main]
+use adventofcode2018::day05::Day05;
+use adventofcode2018::solution::Solution;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &str| {
+    let _ = Day05::parse(input);
+});
@@ -0,0 +1,8 @@
+#![no_main]
+use adventofcode2018::day10::Day10;
+use adventofcode2018::solution::Solution;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &str| {
+    let _ = Day10::parse(input);
+});
@@ -0,0 +1,8 @@
+#![no_main]
+use adventofcode2018::day24::Day24;
+use adventofcode2018::solution::Solution;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &str| {
+    let _ = Day24::parse(input);
+});
@@ -0,0 +1,333 @@
+//! Benchmarking the solvers, and comparing the results against a saved baseline.
+
+use crate::input::Inputs;
+use crate::report::result_text;
+use crate::runner::{self, nanos, Status};
+use crate::solution::Solver;
+use prettytable::{format, row, Cell, Table};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::Duration;
+
+/// The number of untimed runs before the measurements start, unless told otherwise.
+pub const DEFAULT_WARMUP: usize = 3;
+/// How much slower than the baseline (in percent) a part may get before it's a regression.
+pub const DEFAULT_THRESHOLD: f64 = 10.0;
+
+/// Summary statistics of a set of timings.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Stats {
+    #[serde(rename = "min_ns", with = "nanos")]
+    pub min: Duration,
+    #[serde(rename = "median_ns", with = "nanos")]
+    pub median: Duration,
+    #[serde(rename = "mean_ns", with = "nanos")]
+    pub mean: Duration,
+    #[serde(rename = "p95_ns", with = "nanos")]
+    pub p95: Duration,
+    #[serde(rename = "stddev_ns", with = "nanos")]
+    pub stddev: Duration,
+}
+
+impl Stats {
+    /// Calculates the statistics, there must be at least one sample.
+    pub fn from_samples(samples: &[Duration]) -> Stats {
+        assert!(!samples.is_empty(), "No samples");
+        let mut samples = samples.to_vec();
+        samples.sort();
+        let len = samples.len();
+
+        let median = if len.is_multiple_of(2) {
+            (samples[len / 2 - 1] + samples[len / 2]) / 2
+        } else {
+            samples[len / 2]
+        };
+        // The nearest-rank percentile.
+        let p95 = samples[(len * 95).div_ceil(100) - 1];
+        let mean = samples.iter().sum::<Duration>() / len as u32;
+        let variance = samples
+            .iter()
+            .map(|sample| (sample.as_secs_f64() - mean.as_secs_f64()).powi(2))
+            .sum::<f64>()
+            / len as f64;
+
+        Stats {
+            min: samples[0],
+            median,
+            mean,
+            p95,
+            stddev: Duration::from_secs_f64(variance.sqrt()),
+        }
+    }
+}
+
+/// The benchmark results of a single day/part.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Benchmark {
+    pub day: u8,
+    pub part: u8,
+    /// The number of timed runs.
+    pub runs: usize,
+    #[serde(flatten)]
+    pub stats: Stats,
+}
+
+impl Benchmark {
+    /// The relative change of the median compared to the baseline, ie. `0.1` when 10% slower.
+    pub fn change(&self, baseline: &[Benchmark]) -> Option<f64> {
+        baseline
+            .iter()
+            .find(|b| b.day == self.day && b.part == self.part)
+            .map(|b| self.stats.median.as_secs_f64() / b.stats.median.as_secs_f64() - 1.0)
+    }
+}
+
+/// A day/part that couldn't be benchmarked.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Failure {
+    pub day: u8,
+    pub part: u8,
+    pub error: String,
+}
+
+/// Whether the change (as returned by [`Benchmark::change`]) exceeds the threshold in percent.
+pub fn is_regression(change: f64, threshold: f64) -> bool {
+    change * 100.0 > threshold
+}
+
+/// Runs the solver once through the runner, giving up on it after `timeout` like any other run.
+fn sample(solver: &Solver, input: &str, timeout: Option<Duration>) -> Result<Duration, String> {
+    let record = runner::run(solver, Ok(input), timeout);
+    match record.status {
+        Status::Ok => Ok(record.total_time()),
+        _ => Err(result_text(&record)),
+    }
+}
+
+/// Runs the solver `warmup` times, then times `runs` runs of parsing and solving.
+///
+/// Every run is isolated, the first one that fails to parse, panics or times out fails the
+/// benchmark.
+pub fn bench(
+    solver: &Solver,
+    input: &str,
+    warmup: usize,
+    runs: usize,
+    timeout: Option<Duration>,
+) -> Result<Benchmark, String> {
+    for _ in 0..warmup {
+        sample(solver, input, timeout)?;
+    }
+    let samples = (0..runs)
+        .map(|_| sample(solver, input, timeout))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(Benchmark {
+        day: solver.day,
+        part: solver.part,
+        runs,
+        stats: Stats::from_samples(&samples),
+    })
+}
+
+/// Benchmarks all the solvers in order, loading the inputs as needed. A part that fails doesn't
+/// stop the others from being benchmarked.
+pub fn bench_all(
+    solvers: &[Solver],
+    inputs: &mut Inputs,
+    warmup: usize,
+    runs: usize,
+    timeout: Option<Duration>,
+) -> Vec<Result<Benchmark, Failure>> {
+    solvers
+        .iter()
+        .map(|solver| {
+            let failure = |error| Failure {
+                day: solver.day,
+                part: solver.part,
+                error,
+            };
+            let input = inputs.get(solver.day).map_err(failure)?;
+            bench(solver, input, warmup, runs, timeout).map_err(failure)
+        })
+        .collect()
+}
+
+/// Reads a baseline saved by [`save_baseline`].
+pub fn load_baseline(path: &Path) -> Result<Vec<Benchmark>, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|err| format!("Unable to read {}: {}", path.display(), err))?;
+    serde_json::from_str(&content)
+        .map_err(|err| format!("Invalid baseline {}: {}", path.display(), err))
+}
+
+/// Saves the benchmarks as a JSON baseline.
+pub fn save_baseline(path: &Path, benchmarks: &[Benchmark]) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(benchmarks).map_err(|err| err.to_string())?;
+    fs::write(path, json + "\n")
+        .map_err(|err| format!("Unable to write {}: {}", path.display(), err))
+}
+
+/// Writes a table of the results, returning the number of regressions compared to the baseline.
+pub fn write_table(
+    results: &[Result<Benchmark, Failure>],
+    baseline: Option<&[Benchmark]>,
+    threshold: f64,
+    out: &mut dyn Write,
+) -> io::Result<usize> {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
+    let mut titles = row!["Day", "Part", "Min", "Median", "Mean", "P95", "Std dev"];
+    if baseline.is_some() {
+        titles.add_cell(Cell::new("Change"));
+    }
+    table.set_titles(titles);
+
+    let mut regressions = 0;
+    for result in results {
+        let benchmark = match result {
+            Ok(benchmark) => benchmark,
+            Err(failure) => {
+                table.add_row(row![failure.day, failure.part, H5->failure.error]);
+                continue;
+            }
+        };
+        let stats = &benchmark.stats;
+        let mut row = row![
+            benchmark.day,
+            benchmark.part,
+            format!("{:?}", stats.min),
+            format!("{:?}", stats.median),
+            format!("{:?}", stats.mean),
+            format!("{:?}", stats.p95),
+            format!("{:?}", stats.stddev)
+        ];
+        if let Some(baseline) = baseline {
+            let change = match benchmark.change(baseline) {
+                Some(change) if is_regression(change, threshold) => {
+                    regressions += 1;
+                    format!("{:+.1}% REGRESSION", change * 100.0)
+                }
+                Some(change) => format!("{:+.1}%", change * 100.0),
+                None => "new".to_owned(),
+            };
+            row.add_cell(Cell::new(&change));
+        }
+        table.add_row(row);
+    }
+    table.print(out)?;
+    Ok(regressions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::solvers;
+
+    fn micros(samples: &[u64]) -> Vec<Duration> {
+        samples.iter().map(|&s| Duration::from_micros(s)).collect()
+    }
+
+    fn benchmark(day: u8, median: u64) -> Benchmark {
+        Benchmark {
+            day,
+            part: 1,
+            runs: 1,
+            stats: Stats::from_samples(&micros(&[median])),
+        }
+    }
+
+    #[test]
+    fn stats() {
+        let stats = Stats::from_samples(&micros(&[4, 2, 9, 5, 5, 7, 4, 4]));
+        assert_eq!(stats.min, Duration::from_micros(2));
+        assert_eq!(stats.median, Duration::from_nanos(4500));
+        assert_eq!(stats.mean, Duration::from_micros(5));
+        assert_eq!(stats.p95, Duration::from_micros(9));
+        assert!((stats.stddev.as_secs_f64() - 2e-6).abs() < 1e-9);
+
+        let stats = Stats::from_samples(&micros(&[3]));
+        assert_eq!(stats.median, Duration::from_micros(3));
+        assert_eq!(stats.p95, Duration::from_micros(3));
+        assert_eq!(stats.stddev, Duration::default());
+    }
+
+    #[test]
+    fn regressions() {
+        let baseline = vec![benchmark(1, 100), benchmark(2, 100)];
+        let change = benchmark(1, 120).change(&baseline).unwrap();
+        assert!((change - 0.2).abs() < 1e-9);
+        assert!(is_regression(change, 10.0));
+        assert!(!is_regression(change, 25.0));
+        assert!(benchmark(3, 1).change(&baseline).is_none());
+
+        let results = vec![Ok(benchmark(1, 120)), Ok(benchmark(2, 90))];
+        let mut out = vec![];
+        let regressions = write_table(&results, Some(&baseline), 10.0, &mut out).unwrap();
+        assert_eq!(regressions, 1);
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("+20.0% REGRESSION"));
+        assert!(out.contains("-10.0%"));
+    }
+
+    #[test]
+    fn baseline_round_trip() {
+        let path = std::env::temp_dir().join(format!("aoc2018-baseline-{}", std::process::id()));
+        let benchmarks = vec![benchmark(1, 100), benchmark(25, 3)];
+        save_baseline(&path, &benchmarks).unwrap();
+        assert_eq!(load_baseline(&path).unwrap(), benchmarks);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn bench_solver() {
+        let benchmark = bench(&solvers()[0], "+1, +1, -2", 1, 5, None).unwrap();
+        assert_eq!((benchmark.day, benchmark.part, benchmark.runs), (1, 1, 5));
+        assert!(benchmark.stats.min <= benchmark.stats.median);
+    }
+
+    #[test]
+    fn bench_failures() {
+        // Repeats a frequency in the end, but long after the timeout.
+        let solvers = solvers();
+        let error = bench(
+            &solvers[1],
+            "+200000, -199999",
+            0,
+            1,
+            Some(Duration::from_millis(1)),
+        );
+        assert_eq!(error.unwrap_err(), "TIMEOUT: no answer after 1ms");
+
+        let solvers = solvers
+            .into_iter()
+            .filter(|s| s.day == 1 || s.day == 20)
+            .collect::<Vec<_>>();
+        let dir = std::env::temp_dir().join(format!("aoc2018-bench-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        // Part 1 of day 20 panics without any rooms.
+        fs::write(crate::input::day_path(&dir, 1), "+1, -1\n").unwrap();
+        fs::write(crate::input::day_path(&dir, 20), "^$\n").unwrap();
+        let mut inputs = Inputs::new(crate::input::Source::Dir(dir.clone()));
+        let results = bench_all(&solvers, &mut inputs, 1, 2, None);
+        fs::remove_dir_all(dir).unwrap();
+
+        let parts = results
+            .iter()
+            .map(|result| match result {
+                Ok(benchmark) => (benchmark.day, benchmark.part, "ok".to_owned()),
+                Err(failure) => (failure.day, failure.part, failure.error.clone()),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            parts[..2],
+            [(1, 1, "ok".to_owned()), (1, 2, "ok".to_owned())]
+        );
+        assert_eq!((parts[2].0, parts[2].1), (20, 1));
+        assert!(parts[2].2.starts_with("PANIC: "));
+        // The days after a failure are still benchmarked.
+        assert_eq!(parts[3], (20, 2, "ok".to_owned()));
+        assert_eq!(parts.len(), 4);
+    }
+}
@@ -0,0 +1,955 @@
+use adventofcode2018::bench;
+use adventofcode2018::fetch;
+use adventofcode2018::input::{self, Source};
+use adventofcode2018::parse::parse_number;
+use adventofcode2018::report::Format;
+use adventofcode2018::runner;
+#[cfg(feature = "serve")]
+use adventofcode2018::serve;
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use std::time::Duration;
+
+pub const USAGE: &str = "Usage: adventofcode2018 [OPTIONS]
+       adventofcode2018 <COMMAND> [OPTIONS]
+
+Commands:
+    debug            Step through an ElfCode program, see `debug --help`.
+    decompile        Turn an ElfCode program into pseudo-code, see `decompile --help`.
+    extract          Extract the examples from saved puzzle pages, see `extract --help`.
+    fetch            Download the puzzle inputs, see `fetch --help`.
+    generate         Generate random puzzle inputs, see `generate --help`.
+    serve            Expose the solvers over HTTP, see `serve --help`. Needs the `serve`
+                     feature.
+    trace            Trace and profile an ElfCode program, see `trace --help`.
+
+Options:
+    --day <DAY>      Only run the given day, can be repeated.
+    --days <DAYS>    Only run the given days, ie. `10-15` or `1,3,20-25`.
+    --part <PART>    Only run the given part (1 or 2).
+    --format <FORMAT>
+                     The output format: table (default), json, csv or markdown.
+    --bench <RUNS>   Benchmark the selected days/parts instead, timing each of them RUNS times.
+    --warmup <RUNS>  The number of untimed runs before benchmarking, defaults to 3.
+    --baseline <FILE>
+                     Compare the benchmarks against a baseline, failing on regressions.
+    --save-baseline <FILE>
+                     Save the benchmarks as a baseline.
+    --threshold <PERCENT>
+                     How much slower than the baseline is a regression, defaults to 10.
+    --verify <FILE>  Check the answers against a TOML or JSON file of expected answers.
+    -j, --jobs <N>   Run the parts on N threads, defaults to 1.
+    --timeout <SECS> Give up on a part after SECS seconds, defaults to 60. 0 disables it.
+    --list           List the selected days/parts instead of running them.
+    --tui            Follow the run on a dashboard, needs the `tui` feature.
+    --history <FILE> Show the timings of a saved baseline on the dashboard, can be repeated.
+    --input-dir <DIR>
+                     Read the inputs from `<DIR>/dayNN.txt`, defaults to `inputs`.
+    --input <FILE>   Read the input for the selected day from a file, or stdin with `-`.
+    -h, --help       Print this help.";
+
+pub const DEBUG_USAGE: &str = "Usage: adventofcode2018 debug [OPTIONS] [FILE]
+
+Steps through an ElfCode program, with breakpoints and watchpoints. Reads the input of day 19
+unless given a file, type `help` at the prompt for the commands.
+
+Options:
+    --day <DAY>      Read the input of the given day instead, 19 or 21.
+    -h, --help       Print this help.";
+
+pub const DECOMPILE_USAGE: &str = "Usage: adventofcode2018 decompile [OPTIONS] [FILE]
+
+Turns an ElfCode program into pseudo-code, recovering its loops and conditions where the jumps
+nest properly. Reads the input of day 19 unless given a file.
+
+Options:
+    --day <DAY>      Read the input of the given day instead, 19 or 21.
+    --disassemble    List the instructions instead, each commented with what it does.
+    -h, --help       Print this help.";
+
+pub const EXTRACT_USAGE: &str = "Usage: adventofcode2018 extract [OPTIONS] [PAGE]...
+
+Extracts the examples from saved puzzle pages into `fixtures/dayNN.toml`, and regenerates
+`tests/examples.rs` to run all the fixtures through the solvers. Look over the fixtures before
+committing them, the examples and answers are paired up by guessing.
+
+Options:
+    --force          Overwrite existing fixtures.
+    -h, --help       Print this help.";
+
+pub const FETCH_USAGE: &str = "Usage: adventofcode2018 fetch [OPTIONS]
+
+Downloads the puzzle inputs for a session token, skipping the ones that are already downloaded.
+
+Options:
+    --day <DAY>      Download the input for the given day, can be repeated.
+    --days <DAYS>    Download the inputs for the given days, ie. `10-15` or `1,3,20-25`. Defaults
+                     to all of them.
+    --session <TOKEN>
+                     The `session` cookie of a logged in user, defaults to $AOC_SESSION.
+    --output-dir <DIR>
+                     Save the inputs as `<DIR>/dayNN.txt`, defaults to `inputs`.
+    --base-url <URL> Download from another server, defaults to https://adventofcode.com.
+    -h, --help       Print this help.";
+
+#[cfg(feature = "serve")]
+pub const SERVE_USAGE: &str = "Usage: adventofcode2018 serve [OPTIONS]
+
+Serves the solvers over HTTP, `POST /day/{n}/part/{p}` with the puzzle input as the body
+responds with the answer and timings as JSON.
+
+Options:
+    --addr <ADDR>    The address to listen on, defaults to 127.0.0.1:8018.
+    --timeout <SECS> Give up on a part after SECS seconds, defaults to 60. 0 disables it.
+    --max-input-size <BYTES>
+                     Reject larger inputs, defaults to 1048576.
+    -j, --jobs <N>   Handle N requests at the same time, defaults to 4.
+    --max-solves <N> Answer 503 while N parts are being solved, defaults to 8. Parts that timed
+                     out count until they finish, as they can't be stopped.
+    -h, --help       Print this help.";
+
+pub const GENERATE_USAGE: &str = "Usage: adventofcode2018 generate [OPTIONS]
+
+Generates random puzzle inputs, printing them or writing them to a directory.
+
+Options:
+    --day <DAY>      Generate an input for the given day, can be repeated.
+    --days <DAYS>    Generate inputs for the given days, ie. `10-15` or `1,3,20-25`.
+    --seed <SEED>    The seed for the random numbers, defaults to 0.
+    --size <SIZE>    The size of the inputs, usually the number of lines. Defaults to about the
+                     size of the real inputs.
+    --output-dir <DIR>
+                     Write the inputs to `<DIR>/dayNN.txt`, for use with `--input-dir`.
+    -h, --help       Print this help.";
+
+pub const TRACE_USAGE: &str = "Usage: adventofcode2018 trace [OPTIONS] [FILE]
+
+Runs an ElfCode program, printing how often every instruction ran and the loops it spent its time
+in. Reads the input of day 19 unless given a file.
+
+Options:
+    --day <DAY>      Read the input of the given day instead, 19 or 21.
+    --output <FILE>  Write the instructions run to FILE, with the registers before and after.
+    --every <N>      Only write every Nth instruction run.
+    --last <N>       Only write the last N instructions, once the program stopped.
+    --limit <N>      Stop after running N instructions, defaults to 10000000. 0 runs the program
+                     until it halts.
+    --r0 <VALUE>     Start with register 0 set to VALUE, ie. 1 for part 2 of day 19.
+    -h, --help       Print this help.";
+
+/// Determines which day/part combinations to run.
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct Selection {
+    /// The selected days, `None` means all of them.
+    days: Option<BTreeSet<u8>>,
+    /// The selected part, `None` means both of them.
+    part: Option<u8>,
+}
+
+impl Selection {
+    /// Whether the selection only includes a single day.
+    pub fn is_single_day(&self) -> bool {
+        self.days.as_ref().is_some_and(|days| days.len() == 1)
+    }
+
+    pub fn matches(&self, day: u8, part: u8) -> bool {
+        self.days.as_ref().is_none_or(|days| days.contains(&day))
+            && self.part.is_none_or(|p| p == part)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Bench {
+    pub runs: usize,
+    pub warmup: usize,
+    pub baseline: Option<PathBuf>,
+    pub save_baseline: Option<PathBuf>,
+    pub threshold: f64,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Options {
+    pub selection: Selection,
+    pub source: Source,
+    pub format: Format,
+    pub bench: Option<Bench>,
+    pub verify: Option<PathBuf>,
+    /// How long a part may run, `None` means forever.
+    pub timeout: Option<Duration>,
+    /// The number of parts to run at once.
+    pub jobs: usize,
+    pub list: bool,
+    pub tui: bool,
+    /// The baselines to show on the dashboard.
+    pub history: Vec<PathBuf>,
+    pub help: bool,
+}
+
+/// The options for the `generate` command.
+#[derive(Debug, Default, PartialEq)]
+pub struct Generate {
+    pub days: BTreeSet<u8>,
+    pub seed: u64,
+    /// The size of the inputs, `None` means the default size of every day.
+    pub size: Option<usize>,
+    pub output_dir: Option<PathBuf>,
+    pub help: bool,
+}
+
+/// Where to read an ElfCode program from, the input of `day` unless given a file.
+#[derive(Debug, PartialEq)]
+pub struct Program {
+    pub day: u8,
+    pub file: Option<PathBuf>,
+}
+
+impl Default for Program {
+    fn default() -> Program {
+        Program {
+            day: 19,
+            file: None,
+        }
+    }
+}
+
+/// The options for the `debug` command.
+#[derive(Debug, Default, PartialEq)]
+pub struct DebugProgram {
+    pub program: Program,
+    pub help: bool,
+}
+
+/// The options for the `decompile` command.
+#[derive(Debug, Default, PartialEq)]
+pub struct Decompile {
+    pub program: Program,
+    pub disassemble: bool,
+    pub help: bool,
+}
+
+/// The options for the `extract` command.
+#[derive(Debug, Default, PartialEq)]
+pub struct Extract {
+    pub pages: Vec<PathBuf>,
+    pub force: bool,
+    pub help: bool,
+}
+
+/// The options for the `fetch` command.
+#[derive(Debug, PartialEq)]
+pub struct Fetch {
+    pub days: BTreeSet<u8>,
+    pub session: Option<String>,
+    pub output_dir: PathBuf,
+    pub base_url: String,
+    pub help: bool,
+}
+
+impl Default for Fetch {
+    fn default() -> Fetch {
+        Fetch {
+            days: BTreeSet::new(),
+            session: None,
+            output_dir: PathBuf::from(input::DEFAULT_DIR),
+            base_url: fetch::DEFAULT_BASE_URL.to_owned(),
+            help: false,
+        }
+    }
+}
+
+/// The options for the `serve` command.
+#[cfg(feature = "serve")]
+#[derive(Debug, PartialEq)]
+pub struct Serve {
+    pub addr: String,
+    pub config: serve::Config,
+    pub help: bool,
+}
+
+#[cfg(feature = "serve")]
+impl Default for Serve {
+    fn default() -> Serve {
+        Serve {
+            addr: serve::DEFAULT_ADDR.to_owned(),
+            config: serve::Config::default(),
+            help: false,
+        }
+    }
+}
+
+/// The options for the `trace` command.
+#[derive(Debug, PartialEq)]
+pub struct Trace {
+    pub program: Program,
+    pub output: Option<PathBuf>,
+    pub every: u64,
+    pub last: Option<usize>,
+    /// The number of instructions to run, `None` runs until the program halts.
+    pub limit: Option<u64>,
+    pub r0: usize,
+    pub help: bool,
+}
+
+impl Default for Trace {
+    fn default() -> Trace {
+        Trace {
+            program: Program::default(),
+            output: None,
+            every: 1,
+            last: None,
+            limit: Some(10_000_000),
+            r0: 0,
+            help: false,
+        }
+    }
+}
+
+fn parse_day(input: &str) -> Result<u8, String> {
+    match input.trim().parse() {
+        Ok(day) if (1..=25).contains(&day) => Ok(day),
+        _ => Err(format!("Invalid day: {}, expected 1-25", input)),
+    }
+}
+
+/// Parses the day of an ElfCode program.
+fn parse_program_day(input: &str) -> Result<u8, String> {
+    match parse_day(input)? {
+        day @ (19 | 21) => Ok(day),
+        day => Err(format!(
+            "Day {} doesn't run an ElfCode program, expected 19 or 21",
+            day
+        )),
+    }
+}
+
+/// Parses a comma separated list of days and day ranges, ie. `1,3,10-15`.
+fn parse_days(input: &str) -> Result<BTreeSet<u8>, String> {
+    let mut result = BTreeSet::new();
+    for elem in input.split(',') {
+        let mut bounds = elem.splitn(2, '-');
+        let start = parse_day(bounds.next().unwrap())?;
+        let end = match bounds.next() {
+            Some(end) => parse_day(end)?,
+            None => start,
+        };
+        if start > end {
+            return Err(format!("Invalid day range: {}", elem));
+        }
+        result.extend(start..=end);
+    }
+    Ok(result)
+}
+
+fn parse_threshold(input: &str) -> Result<f64, String> {
+    match input.trim().parse::<f64>() {
+        Ok(threshold) if threshold >= 0.0 => Ok(threshold),
+        _ => Err(format!(
+            "Invalid threshold: {}, expected a percentage",
+            input
+        )),
+    }
+}
+
+fn parse_jobs(input: &str) -> Result<usize, String> {
+    match input.trim().parse() {
+        Ok(jobs) if jobs > 0 => Ok(jobs),
+        _ => Err(format!("Invalid number of jobs: {}", input)),
+    }
+}
+
+fn parse_timeout(input: &str) -> Result<Option<Duration>, String> {
+    match input.trim().parse::<f64>() {
+        Ok(0.0) => Ok(None),
+        Ok(secs) if secs > 0.0 && secs.is_finite() => Ok(Some(Duration::from_secs_f64(secs))),
+        _ => Err(format!("Invalid timeout: {}, expected seconds", input)),
+    }
+}
+
+fn parse_part(input: &str) -> Result<u8, String> {
+    match input.trim() {
+        "1" => Ok(1),
+        "2" => Ok(2),
+        _ => Err(format!("Invalid part: {}, expected 1 or 2", input)),
+    }
+}
+
+pub fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options {
+        timeout: Some(runner::DEFAULT_TIMEOUT),
+        jobs: 1,
+        ..Options::default()
+    };
+    let mut runs = None;
+    let mut warmup = None;
+    let mut baseline = None;
+    let mut save_baseline = None;
+    let mut threshold = None;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        // Fetches the value for the current flag.
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("Missing value for {}", arg))
+        };
+        match arg.as_ref() {
+            "--day" => {
+                let day = parse_day(&value()?)?;
+                options
+                    .selection
+                    .days
+                    .get_or_insert_with(BTreeSet::new)
+                    .insert(day);
+            }
+            "--days" => {
+                let days = parse_days(&value()?)?;
+                options
+                    .selection
+                    .days
+                    .get_or_insert_with(BTreeSet::new)
+                    .extend(days);
+            }
+            "--part" => options.selection.part = Some(parse_part(&value()?)?),
+            "--input" | "--input-dir" => {
+                if options.source != Source::Default {
+                    return Err("Only a single --input or --input-dir can be given".to_owned());
+                }
+                let path = value()?;
+                options.source = match (arg.as_ref(), path.as_ref()) {
+                    ("--input", "-") => Source::Stdin,
+                    ("--input", _) => Source::File(PathBuf::from(path)),
+                    _ => Source::Dir(PathBuf::from(path)),
+                };
+            }
+            "--format" => options.format = value()?.parse()?,
+            "--bench" => match parse_number(&arg, &value()?)? {
+                0 => return Err("--bench needs at least a single run".to_owned()),
+                n => runs = Some(n),
+            },
+            "--warmup" => warmup = Some(parse_number(&arg, &value()?)?),
+            "--baseline" => baseline = Some(PathBuf::from(value()?)),
+            "--save-baseline" => save_baseline = Some(PathBuf::from(value()?)),
+            "--threshold" => threshold = Some(parse_threshold(&value()?)?),
+            "--verify" => options.verify = Some(PathBuf::from(value()?)),
+            "-j" | "--jobs" => options.jobs = parse_jobs(&value()?)?,
+            "--timeout" => options.timeout = parse_timeout(&value()?)?,
+            "--list" => options.list = true,
+            "--tui" => options.tui = true,
+            "--history" => options.history.push(PathBuf::from(value()?)),
+            "-h" | "--help" => options.help = true,
+            _ => return Err(format!("Unknown argument: {}", arg)),
+        }
+    }
+    if options.source.is_single() && !options.selection.is_single_day() {
+        return Err("--input requires a single --day".to_owned());
+    }
+    if runs.is_some() && options.verify.is_some() {
+        return Err("--verify can't be combined with --bench".to_owned());
+    }
+    if runs.is_some() && options.tui {
+        return Err("--tui can't be combined with --bench".to_owned());
+    }
+    if !options.history.is_empty() && !options.tui {
+        return Err("--history requires --tui".to_owned());
+    }
+    if runs.is_some() && options.jobs > 1 {
+        return Err("--jobs can't be combined with --bench".to_owned());
+    }
+    match runs {
+        Some(runs) => {
+            options.bench = Some(Bench {
+                runs,
+                warmup: warmup.unwrap_or(bench::DEFAULT_WARMUP),
+                baseline,
+                save_baseline,
+                threshold: threshold.unwrap_or(bench::DEFAULT_THRESHOLD),
+            })
+        }
+        None if warmup.is_some()
+            || baseline.is_some()
+            || save_baseline.is_some()
+            || threshold.is_some() =>
+        {
+            return Err("The benchmark options require --bench".to_owned());
+        }
+        None => {}
+    }
+    Ok(options)
+}
+
+pub fn parse_debug_args(args: impl IntoIterator<Item = String>) -> Result<DebugProgram, String> {
+    let mut options = DebugProgram::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("Missing value for {}", arg))
+        };
+        match arg.as_ref() {
+            "--day" => options.program.day = parse_program_day(&value()?)?,
+            "-h" | "--help" => options.help = true,
+            _ if arg.starts_with('-') => return Err(format!("Unknown argument: {}", arg)),
+            _ if options.program.file.is_some() => {
+                return Err(format!("Unexpected argument: {}", arg))
+            }
+            _ => options.program.file = Some(PathBuf::from(arg)),
+        }
+    }
+    Ok(options)
+}
+
+pub fn parse_decompile_args(args: impl IntoIterator<Item = String>) -> Result<Decompile, String> {
+    let mut options = Decompile::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("Missing value for {}", arg))
+        };
+        match arg.as_ref() {
+            "--day" => options.program.day = parse_program_day(&value()?)?,
+            "--disassemble" => options.disassemble = true,
+            "-h" | "--help" => options.help = true,
+            _ if arg.starts_with('-') => return Err(format!("Unknown argument: {}", arg)),
+            _ if options.program.file.is_some() => {
+                return Err(format!("Unexpected argument: {}", arg))
+            }
+            _ => options.program.file = Some(PathBuf::from(arg)),
+        }
+    }
+    Ok(options)
+}
+
+pub fn parse_extract_args(args: impl IntoIterator<Item = String>) -> Result<Extract, String> {
+    let mut options = Extract::default();
+    for arg in args {
+        match arg.as_ref() {
+            "--force" => options.force = true,
+            "-h" | "--help" => options.help = true,
+            _ if arg.starts_with('-') => return Err(format!("Unknown argument: {}", arg)),
+            _ => options.pages.push(PathBuf::from(arg)),
+        }
+    }
+    Ok(options)
+}
+
+pub fn parse_fetch_args(args: impl IntoIterator<Item = String>) -> Result<Fetch, String> {
+    let mut options = Fetch::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("Missing value for {}", arg))
+        };
+        match arg.as_ref() {
+            "--day" => {
+                options.days.insert(parse_day(&value()?)?);
+            }
+            "--days" => options.days.extend(parse_days(&value()?)?),
+            "--session" => options.session = Some(value()?),
+            "--output-dir" => options.output_dir = PathBuf::from(value()?),
+            "--base-url" => options.base_url = value()?,
+            "-h" | "--help" => options.help = true,
+            _ => return Err(format!("Unknown argument: {}", arg)),
+        }
+    }
+    if options.days.is_empty() {
+        options.days.extend(1..=25);
+    }
+    Ok(options)
+}
+
+#[cfg(feature = "serve")]
+pub fn parse_serve_args(args: impl IntoIterator<Item = String>) -> Result<Serve, String> {
+    let mut options = Serve::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("Missing value for {}", arg))
+        };
+        match arg.as_ref() {
+            "--addr" => options.addr = value()?,
+            "--timeout" => options.config.timeout = parse_timeout(&value()?)?,
+            "--max-input-size" => options.config.max_input_size = parse_number(&arg, &value()?)?,
+            "-j" | "--jobs" => options.config.jobs = parse_jobs(&value()?)?,
+            "--max-solves" => match parse_number(&arg, &value()?)? {
+                0 => {
+                    return Err("Invalid value for --max-solves: 0, expected at least 1".to_owned())
+                }
+                max => options.config.max_solves = max,
+            },
+            "-h" | "--help" => options.help = true,
+            _ => return Err(format!("Unknown argument: {}", arg)),
+        }
+    }
+    Ok(options)
+}
+
+pub fn parse_generate_args(args: impl IntoIterator<Item = String>) -> Result<Generate, String> {
+    let mut options = Generate::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("Missing value for {}", arg))
+        };
+        match arg.as_ref() {
+            "--day" => {
+                options.days.insert(parse_day(&value()?)?);
+            }
+            "--days" => options.days.extend(parse_days(&value()?)?),
+            "--seed" => options.seed = parse_number(&arg, &value()?)?,
+            "--size" => options.size = Some(parse_number(&arg, &value()?)?),
+            "--output-dir" => options.output_dir = Some(PathBuf::from(value()?)),
+            "-h" | "--help" => options.help = true,
+            _ => return Err(format!("Unknown argument: {}", arg)),
+        }
+    }
+    if options.days.is_empty() && !options.help {
+        return Err("generate requires a --day or --days".to_owned());
+    }
+    if options.days.len() > 1 && options.output_dir.is_none() {
+        return Err("Generating several days requires --output-dir".to_owned());
+    }
+    Ok(options)
+}
+
+pub fn parse_trace_args(args: impl IntoIterator<Item = String>) -> Result<Trace, String> {
+    let mut options = Trace::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("Missing value for {}", arg))
+        };
+        match arg.as_ref() {
+            "--day" => options.program.day = parse_program_day(&value()?)?,
+            "--output" => options.output = Some(PathBuf::from(value()?)),
+            "--every" => match parse_number(&arg, &value()?)? {
+                0 => return Err("Invalid value for --every: 0, expected at least 1".to_owned()),
+                every => options.every = every,
+            },
+            "--last" => options.last = Some(parse_number(&arg, &value()?)?),
+            "--limit" => match parse_number(&arg, &value()?)? {
+                0 => options.limit = None,
+                limit => options.limit = Some(limit),
+            },
+            "--r0" => options.r0 = parse_number(&arg, &value()?)?,
+            "-h" | "--help" => options.help = true,
+            _ if arg.starts_with('-') => return Err(format!("Unknown argument: {}", arg)),
+            _ if options.program.file.is_some() => {
+                return Err(format!("Unexpected argument: {}", arg))
+            }
+            _ => options.program.file = Some(PathBuf::from(arg)),
+        }
+    }
+    Ok(options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Options, String> {
+        parse_args(args.iter().map(|e| e.to_string()))
+    }
+
+    #[test]
+    fn no_arguments_selects_everything() {
+        let options = parse(&[]).unwrap();
+        assert_eq!(
+            options,
+            Options {
+                timeout: Some(runner::DEFAULT_TIMEOUT),
+                jobs: 1,
+                ..Options::default()
+            }
+        );
+        assert!(options.selection.matches(1, 1));
+        assert!(options.selection.matches(25, 2));
+    }
+
+    #[test]
+    fn day_and_part() {
+        let options = parse(&["--day", "15", "--part", "2"]).unwrap();
+        assert!(options.selection.matches(15, 2));
+        assert!(!options.selection.matches(15, 1));
+        assert!(!options.selection.matches(14, 2));
+    }
+
+    #[test]
+    fn day_ranges() {
+        let options = parse(&["--days", "1,10-12", "--day", "25"]).unwrap();
+        let days = (1..=25)
+            .filter(|&day| options.selection.matches(day, 1))
+            .collect::<Vec<_>>();
+        assert_eq!(days, vec![1, 10, 11, 12, 25]);
+    }
+
+    #[test]
+    fn input_sources() {
+        let options = parse(&["--input-dir", "foo"]).unwrap();
+        assert_eq!(options.source, Source::Dir(PathBuf::from("foo")));
+        let options = parse(&["--day", "3", "--input", "foo.txt"]).unwrap();
+        assert_eq!(options.source, Source::File(PathBuf::from("foo.txt")));
+        let options = parse(&["--day", "3", "--input", "-"]).unwrap();
+        assert_eq!(options.source, Source::Stdin);
+    }
+
+    #[test]
+    fn output_format() {
+        assert_eq!(parse(&[]).unwrap().format, Format::Table);
+        assert_eq!(parse(&["--format", "csv"]).unwrap().format, Format::Csv);
+    }
+
+    #[test]
+    fn benchmarks() {
+        let options = parse(&["--bench", "10", "--baseline", "base.json"]).unwrap();
+        assert_eq!(
+            options.bench,
+            Some(Bench {
+                runs: 10,
+                warmup: bench::DEFAULT_WARMUP,
+                baseline: Some(PathBuf::from("base.json")),
+                save_baseline: None,
+                threshold: bench::DEFAULT_THRESHOLD,
+            })
+        );
+        let options = parse(&["--bench", "5", "--warmup", "0", "--threshold", "2.5"]).unwrap();
+        let bench = options.bench.unwrap();
+        assert_eq!((bench.runs, bench.warmup, bench.threshold), (5, 0, 2.5));
+    }
+
+    #[test]
+    fn verify() {
+        let options = parse(&["--verify", "answers.toml"]).unwrap();
+        assert_eq!(options.verify, Some(PathBuf::from("answers.toml")));
+    }
+
+    #[test]
+    fn timeout() {
+        let options = parse(&["--timeout", "2.5"]).unwrap();
+        assert_eq!(options.timeout, Some(Duration::from_millis(2500)));
+        assert_eq!(parse(&["--timeout", "0"]).unwrap().timeout, None);
+    }
+
+    #[test]
+    fn jobs() {
+        assert_eq!(parse(&["--jobs", "4"]).unwrap().jobs, 4);
+        assert_eq!(parse(&["-j", "2"]).unwrap().jobs, 2);
+    }
+
+    #[test]
+    fn generate() {
+        let args = ["--day", "15", "--seed", "42", "--size", "20"];
+        let options = parse_generate_args(args.iter().map(|e| e.to_string())).unwrap();
+        assert_eq!(
+            options,
+            Generate {
+                days: [15].iter().copied().collect(),
+                seed: 42,
+                size: Some(20),
+                ..Generate::default()
+            }
+        );
+        let args = ["--days", "1-3", "--output-dir", "inputs"];
+        let options = parse_generate_args(args.iter().map(|e| e.to_string())).unwrap();
+        assert_eq!(options.days.len(), 3);
+        assert_eq!(options.output_dir, Some(PathBuf::from("inputs")));
+
+        let invalid: [&[&str]; 4] = [
+            &[],
+            &["--days", "1-3"],
+            &["--day", "1", "--seed", "-1"],
+            &["--day", "1", "--size", "big"],
+        ];
+        for args in invalid.iter() {
+            assert!(parse_generate_args(args.iter().map(|e| e.to_string())).is_err());
+        }
+    }
+
+    #[test]
+    fn debug() {
+        let options = parse_debug_args(vec!["--day".to_owned(), "21".to_owned()]).unwrap();
+        assert_eq!(options.program.day, 21);
+        assert_eq!(options.program.file, None);
+        assert!(parse_debug_args(vec!["--disassemble".to_owned()]).is_err());
+    }
+
+    #[test]
+    fn decompile() {
+        let options = parse_decompile_args(Vec::new()).unwrap();
+        assert_eq!(options, Decompile::default());
+        assert_eq!(options.program.day, 19);
+
+        let args = ["--disassemble", "--day", "21", "program.txt"];
+        let options = parse_decompile_args(args.iter().map(|e| e.to_string())).unwrap();
+        assert_eq!(
+            options,
+            Decompile {
+                program: Program {
+                    day: 21,
+                    file: Some(PathBuf::from("program.txt")),
+                },
+                disassemble: true,
+                help: false,
+            }
+        );
+        for args in [vec!["--day", "16"], vec!["a.txt", "b.txt"], vec!["--day"]] {
+            assert!(parse_decompile_args(args.iter().map(|e| e.to_string())).is_err());
+        }
+    }
+
+    #[test]
+    fn extract() {
+        let args = ["day01.html", "--force", "saved/day02.html"];
+        let options = parse_extract_args(args.iter().map(|e| e.to_string())).unwrap();
+        assert_eq!(
+            options,
+            Extract {
+                pages: vec![
+                    PathBuf::from("day01.html"),
+                    PathBuf::from("saved/day02.html")
+                ],
+                force: true,
+                help: false,
+            }
+        );
+        assert!(parse_extract_args(vec!["--day".to_owned()]).is_err());
+    }
+
+    #[test]
+    fn fetch() {
+        let options = parse_fetch_args(Vec::new()).unwrap();
+        assert_eq!(options.days.len(), 25);
+        assert_eq!(options.output_dir, PathBuf::from("inputs"));
+        assert_eq!(options.base_url, "https://adventofcode.com");
+
+        let args = [
+            "--days",
+            "3-4",
+            "--session",
+            "abc",
+            "--base-url",
+            "http://localhost",
+        ];
+        let options = parse_fetch_args(args.iter().map(|e| e.to_string())).unwrap();
+        assert_eq!(
+            options,
+            Fetch {
+                days: [3, 4].iter().copied().collect(),
+                session: Some("abc".to_owned()),
+                base_url: "http://localhost".to_owned(),
+                ..Fetch::default()
+            }
+        );
+        assert!(parse_fetch_args(vec!["--session".to_owned()]).is_err());
+    }
+
+    #[cfg(feature = "serve")]
+    #[test]
+    fn serve() {
+        let options = parse_serve_args(Vec::new()).unwrap();
+        assert_eq!(options, Serve::default());
+
+        let args = [
+            "--addr",
+            "0.0.0.0:80",
+            "--timeout",
+            "0",
+            "--max-input-size",
+            "10",
+            "-j",
+            "2",
+            "--max-solves",
+            "3",
+        ];
+        let options = parse_serve_args(args.iter().map(|e| e.to_string())).unwrap();
+        assert_eq!(options.addr, "0.0.0.0:80");
+        assert_eq!(
+            options.config,
+            serve::Config {
+                timeout: None,
+                max_input_size: 10,
+                jobs: 2,
+                max_solves: 3,
+            }
+        );
+        assert!(parse_serve_args(vec!["-j".to_owned(), "0".to_owned()]).is_err());
+        assert!(parse_serve_args(vec!["--max-solves".to_owned(), "0".to_owned()]).is_err());
+    }
+
+    #[test]
+    fn trace() {
+        assert_eq!(parse_trace_args(Vec::new()).unwrap(), Trace::default());
+        let args = [
+            "--day",
+            "21",
+            "--output",
+            "trace.txt",
+            "--every",
+            "10",
+            "--last",
+            "5",
+            "--limit",
+            "0",
+            "--r0",
+            "1",
+        ];
+        let options = parse_trace_args(args.iter().map(|e| e.to_string())).unwrap();
+        assert_eq!(
+            options,
+            Trace {
+                program: Program {
+                    day: 21,
+                    file: None
+                },
+                output: Some(PathBuf::from("trace.txt")),
+                every: 10,
+                last: Some(5),
+                limit: None,
+                r0: 1,
+                help: false,
+            }
+        );
+        for args in &[&["--every", "0"][..], &["--limit", "-1"], &["--r0"]] {
+            assert!(parse_trace_args(args.iter().map(|e| e.to_string())).is_err());
+        }
+    }
+
+    #[test]
+    fn dashboard() {
+        let options = parse(&["--tui", "--history", "a.json", "--history", "b.json"]).unwrap();
+        assert!(options.tui);
+        assert_eq!(
+            options.history,
+            vec![PathBuf::from("a.json"), PathBuf::from("b.json")]
+        );
+    }
+
+    #[test]
+    fn invalid_arguments() {
+        assert!(parse(&["--day"]).is_err());
+        assert!(parse(&["--day", "26"]).is_err());
+        assert!(parse(&["--days", "15-10"]).is_err());
+        assert!(parse(&["--part", "3"]).is_err());
+        assert!(parse(&["--frobnicate"]).is_err());
+        assert!(parse(&["--format", "xml"]).is_err());
+        assert!(parse(&["--timeout", "-1"]).is_err());
+        assert!(parse(&["--jobs", "0"]).is_err());
+        assert!(parse(&["--bench", "10", "--jobs", "2"]).is_err());
+        assert!(parse(&["--bench", "0"]).is_err());
+        assert_eq!(
+            parse(&["--bench", "10", "--warmup", "x"]).err(),
+            Some("Invalid value for --warmup: x, expected a number".to_owned())
+        );
+        assert!(parse(&["--bench", "10", "--threshold", "-1"]).is_err());
+        assert!(parse(&["--save-baseline", "base.json"]).is_err());
+        assert!(parse(&["--bench", "10", "--verify", "answers.toml"]).is_err());
+        assert!(parse(&["--input", "foo.txt"]).is_err());
+        assert!(parse(&["--days", "1-2", "--input", "-"]).is_err());
+        assert!(parse(&["--day", "1", "--input", "-", "--input-dir", "x"]).is_err());
+        assert!(parse(&["--bench", "10", "--tui"]).is_err());
+        assert!(parse(&["--history", "base.json"]).is_err());
+    }
+}
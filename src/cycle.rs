@@ -0,0 +1,273 @@
+//! Cycle detection, for simulations that have to run for way too many steps.
+//!
+//! [`floyd`] and [`brent`] find the cycle in a sequence using constant memory, while [`find`]
+//! remembers the key of every state of a [`Simulation`] to find it in a single pass, and
+//! [`state_at`] every state as well.
+
+use std::collections::hash_map::{Entry, HashMap};
+use std::hash::Hash;
+
+/// A cycle in a sequence of states: from step `start` on, the states repeat every `period` steps.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Cycle {
+    /// The first step in the cycle.
+    pub start: u64,
+    /// The length of the cycle.
+    pub period: u64,
+}
+
+impl Cycle {
+    /// The earliest step with the same state as step `n`.
+    pub fn reduce(&self, n: u64) -> u64 {
+        if n < self.start {
+            n
+        } else {
+            self.start + (n - self.start) % self.period
+        }
+    }
+}
+
+/// Finds the cycle with Floyd's tortoise and hare, `next` returns the state after the given one.
+///
+/// Never returns when the sequence doesn't cycle.
+pub fn floyd<S: Clone + Eq>(initial: S, mut next: impl FnMut(&S) -> S) -> Cycle {
+    // Find a step where the hare, going twice as fast, is a multiple of the period ahead.
+    let mut tortoise = next(&initial);
+    let mut hare = next(&tortoise);
+    while tortoise != hare {
+        tortoise = next(&tortoise);
+        let ahead = next(&hare);
+        hare = next(&ahead);
+    }
+
+    // Restarting the tortoise, both meet at the start of the cycle.
+    let mut start = 0;
+    tortoise = initial;
+    while tortoise != hare {
+        tortoise = next(&tortoise);
+        hare = next(&hare);
+        start += 1;
+    }
+
+    let mut period = 1;
+    hare = next(&tortoise);
+    while tortoise != hare {
+        hare = next(&hare);
+        period += 1;
+    }
+    Cycle { start, period }
+}
+
+/// Finds the cycle with Brent's algorithm, which usually calls `next` less often than [`floyd`].
+///
+/// Never returns when the sequence doesn't cycle.
+pub fn brent<S: Clone + Eq>(initial: S, mut next: impl FnMut(&S) -> S) -> Cycle {
+    // Find the period by teleporting the tortoise to the hare at every power of two.
+    let mut power = 1;
+    let mut period = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = next(&initial);
+    while tortoise != hare {
+        if power == period {
+            tortoise = hare.clone();
+            power *= 2;
+            period = 0;
+        }
+        hare = next(&hare);
+        period += 1;
+    }
+
+    // With the hare a period ahead, both meet at the start of the cycle.
+    let mut start = 0;
+    tortoise = initial.clone();
+    hare = initial;
+    for _ in 0..period {
+        hare = next(&hare);
+    }
+    while tortoise != hare {
+        tortoise = next(&tortoise);
+        hare = next(&hare);
+        start += 1;
+    }
+    Cycle { start, period }
+}
+
+/// A simulation that can be stepped through, and eventually repeats itself.
+pub trait Simulation: Clone {
+    /// What has to repeat for the simulation to be in a cycle, usually the whole state.
+    type Key: Eq + Hash;
+
+    /// Advances the simulation by a single step.
+    fn step(&mut self);
+
+    /// The key of the current state.
+    fn key(&self) -> Self::Key;
+
+    /// Advances the state by `periods` whole periods of the cycle, `start` and `end` being the
+    /// states at the beginning and the end of a single period.
+    ///
+    /// This is only needed when the key is just a part of the state, ie. when a pattern repeats
+    /// while drifting.
+    fn skip(&mut self, _periods: u64, _start: &Self, _end: &Self) {}
+}
+
+/// Steps through the simulation until it repeats, or until step `limit`, calling `visit` with
+/// every state stepped past.
+///
+/// Returns the steps by key, the last state and the cycle when found.
+fn run<S: Simulation>(
+    initial: &S,
+    limit: Option<u64>,
+    mut visit: impl FnMut(&S),
+) -> (HashMap<S::Key, u64>, S, Option<Cycle>) {
+    let mut seen = HashMap::new();
+    let mut state = initial.clone();
+    for step in 0.. {
+        match seen.entry(state.key()) {
+            Entry::Occupied(entry) => {
+                let start = *entry.get();
+                let cycle = Cycle {
+                    start,
+                    period: step - start,
+                };
+                return (seen, state, Some(cycle));
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(step);
+            }
+        }
+        if limit == Some(step) {
+            break;
+        }
+        visit(&state);
+        state.step();
+    }
+    (seen, state, None)
+}
+
+/// The keys seen, in the order of their steps.
+fn keys<K>(seen: HashMap<K, u64>) -> Vec<K> {
+    let mut keys = seen.into_iter().collect::<Vec<_>>();
+    keys.sort_unstable_by_key(|&(_, step)| step);
+    keys.into_iter().map(|(key, _)| key).collect()
+}
+
+/// Finds the cycle by remembering the key of every state, returning the keys before it repeats
+/// as well.
+///
+/// Never returns when the simulation doesn't cycle.
+pub fn find<S: Simulation>(initial: &S) -> (Cycle, Vec<S::Key>) {
+    let (seen, _, cycle) = run(initial, None, |_| {});
+    (cycle.expect("Unbounded runs end with a cycle"), keys(seen))
+}
+
+/// Like [`find`], but gives up when the simulation doesn't repeat within `limit` steps.
+pub fn find_within<S: Simulation>(initial: &S, limit: u64) -> Option<(Cycle, Vec<S::Key>)> {
+    let (seen, _, cycle) = run(initial, Some(limit), |_| {});
+    cycle.map(|cycle| (cycle, keys(seen)))
+}
+
+/// The state after `n` steps, skipping the repetitions once the simulation is in a cycle.
+pub fn state_at<S: Simulation>(initial: &S, n: u64) -> S {
+    let mut states = vec![];
+    let (_, end, cycle) = run(initial, Some(n), |state| states.push(state.clone()));
+    let cycle = match cycle {
+        Some(cycle) => cycle,
+        None => return end,
+    };
+    // The last state is the first repetition.
+    let start = &states[cycle.start as usize];
+    // Step n has the same key as this one, but the state might have drifted in the meantime.
+    let index = cycle.reduce(n);
+    let mut result = states[index as usize].clone();
+    result.skip((n - index) / cycle.period, start, &end);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A counter running from 0 up to 9, and then looping from 4 to 9.
+    #[derive(Debug, Clone, Eq, PartialEq)]
+    struct Counter {
+        value: u64,
+        steps: u64,
+    }
+
+    impl Simulation for Counter {
+        type Key = u64;
+
+        fn step(&mut self) {
+            self.value = next(&self.value);
+            self.steps += 1;
+        }
+
+        fn key(&self) -> u64 {
+            self.value
+        }
+
+        fn skip(&mut self, periods: u64, start: &Counter, end: &Counter) {
+            self.steps += periods * (end.steps - start.steps);
+        }
+    }
+
+    fn next(value: &u64) -> u64 {
+        if *value == 9 {
+            4
+        } else {
+            value + 1
+        }
+    }
+
+    const EXPECTED: Cycle = Cycle {
+        start: 4,
+        period: 6,
+    };
+
+    #[test]
+    fn constant_memory() {
+        assert_eq!(floyd(0, next), EXPECTED);
+        assert_eq!(brent(0, next), EXPECTED);
+        assert_eq!(
+            brent(5, next),
+            Cycle {
+                start: 0,
+                period: 6
+            }
+        );
+        assert_eq!(
+            floyd(3, |_| 3),
+            Cycle {
+                start: 0,
+                period: 1
+            }
+        );
+    }
+
+    #[test]
+    fn remembering_states() {
+        let initial = Counter { value: 0, steps: 0 };
+        let (cycle, keys) = find(&initial);
+        assert_eq!(cycle, EXPECTED);
+        assert_eq!(keys, (0..10).collect::<Vec<_>>());
+        assert_eq!(cycle.reduce(3), 3);
+        assert_eq!(cycle.reduce(10), 4);
+        assert_eq!(
+            find_within(&initial, 10).map(|(cycle, _)| cycle),
+            Some(EXPECTED)
+        );
+        assert!(find_within(&initial, 9).is_none());
+    }
+
+    #[test]
+    fn state_at_step() {
+        let initial = Counter { value: 0, steps: 0 };
+        for n in [0, 3, 9, 10, 15, 1_000_000_000_000] {
+            let state = state_at(&initial, n);
+            assert_eq!(state.steps, n);
+            let expected = if n < 4 { n } else { 4 + (n - 4) % 6 };
+            assert_eq!(state.value, expected, "step {}", n);
+        }
+    }
+}
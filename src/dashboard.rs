@@ -0,0 +1,536 @@
+//! A terminal dashboard following a run, built with the `tui` feature.
+//!
+//! The days are listed on the left with the progress of their parts, the selected day's answers,
+//! its timings in the saved benchmark baselines and its picture (see
+//! [`Solution::render`](crate::solution::Solution::render)) are shown on the right. The pictures
+//! are drawn in the background, as some days run their whole simulation again for it.
+
+use crate::bench::Benchmark;
+use crate::input::{self, Inputs, Source};
+use crate::report;
+use crate::runner::{self, Progress, Record, Status};
+use crate::solution::Solver;
+use prettytable::{format, row, Table};
+use ratatui::backend::CrosstermBackend;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::crossterm::execute;
+use ratatui::crossterm::terminal::{self, EnterAlternateScreen};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame, Terminal};
+use std::collections::HashMap;
+use std::io;
+use std::panic::{self, AssertUnwindSafe, PanicHookInfo};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How often the dashboard is redrawn while waiting for keys.
+const REFRESH: Duration = Duration::from_millis(100);
+
+/// A saved benchmark baseline, by the name of its file.
+pub type Baseline = (String, Vec<Benchmark>);
+
+/// Where a part is at.
+#[derive(Debug, Clone)]
+enum State {
+    /// Not selected, or day 25 part 2.
+    Skipped,
+    Waiting,
+    Running(Instant),
+    Done(Record),
+}
+
+enum Message {
+    Started(usize),
+    Finished(usize, Record),
+    /// All the parts finished, after the wall-clock time.
+    Done(Duration),
+    /// The picture of a day, or why it couldn't be drawn.
+    Picture(u8, Result<Option<String>, String>),
+}
+
+/// The picture of a day.
+#[derive(Debug, Clone)]
+enum Picture {
+    Drawing,
+    Drawn(Result<Option<String>, String>),
+}
+
+/// The state of the dashboard, updated by the runner and the keys.
+pub struct Dashboard {
+    solvers: Vec<Solver>,
+    /// The parts of every day.
+    states: Vec<[State; 2]>,
+    source: Source,
+    history: Vec<Baseline>,
+    /// The pictures of the days, drawn once they're selected after finishing.
+    pictures: HashMap<u8, Picture>,
+    list: ListState,
+    scroll: u16,
+    elapsed: Option<Duration>,
+    /// The messages of the runner and the pictures drawn in the background.
+    sender: Sender<Message>,
+    receiver: Receiver<Message>,
+}
+
+fn duration_text(duration: Duration) -> String {
+    format!("{:.1?}", duration)
+}
+
+impl Dashboard {
+    /// Creates a dashboard for running the given solvers, the inputs are read from `source`.
+    pub fn new(solvers: &[Solver], source: Source, history: Vec<Baseline>) -> Dashboard {
+        let mut states = vec![[State::Skipped, State::Skipped]; 25];
+        for solver in solvers {
+            states[solver.day as usize - 1][solver.part as usize - 1] = State::Waiting;
+        }
+        let first = solvers.first().map_or(0, |s| s.day as usize - 1);
+        let (sender, receiver) = mpsc::channel();
+        Dashboard {
+            solvers: solvers.to_vec(),
+            states,
+            source,
+            history,
+            pictures: HashMap::new(),
+            list: ListState::default().with_selected(Some(first)),
+            scroll: 0,
+            elapsed: None,
+            sender,
+            receiver,
+        }
+    }
+
+    fn state_mut(&mut self, index: usize) -> &mut State {
+        let solver = &self.solvers[index];
+        &mut self.states[solver.day as usize - 1][solver.part as usize - 1]
+    }
+
+    fn update(&mut self, message: Message) {
+        match message {
+            Message::Started(i) => *self.state_mut(i) = State::Running(Instant::now()),
+            Message::Finished(i, record) => *self.state_mut(i) = State::Done(record),
+            Message::Done(elapsed) => self.elapsed = Some(elapsed),
+            Message::Picture(day, picture) => {
+                self.pictures.insert(day, Picture::Drawn(picture));
+            }
+        }
+    }
+
+    /// Handles the messages received so far.
+    fn receive(&mut self) {
+        let messages = self.receiver.try_iter().collect::<Vec<_>>();
+        for message in messages {
+            self.update(message);
+        }
+    }
+
+    /// The records of all the parts, once they've all finished.
+    pub fn records(&self) -> Option<Vec<Record>> {
+        let record = |s: &Solver| match &self.states[s.day as usize - 1][s.part as usize - 1] {
+            State::Done(record) => Some(record.clone()),
+            _ => None,
+        };
+        self.solvers.iter().map(record).collect()
+    }
+
+    fn selected_day(&self) -> u8 {
+        self.list.selected().unwrap_or(0) as u8 + 1
+    }
+
+    /// Handles a key, returning false when it's time to quit.
+    fn key(&mut self, key: KeyCode) -> bool {
+        match key {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Up | KeyCode::Char('k') => self.select(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.select(1),
+            KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(10),
+            KeyCode::PageDown => self.scroll = self.scroll.saturating_add(10),
+            _ => {}
+        }
+        true
+    }
+
+    fn select(&mut self, delta: isize) {
+        let day = self.list.selected().unwrap_or(0) as isize + delta;
+        self.list.select(Some(day.clamp(0, 24) as usize));
+        self.scroll = 0;
+    }
+
+    /// Starts drawing the picture of the day on a thread of its own.
+    fn draw_picture(&self, solver: Solver) -> Picture {
+        if self.source == Source::Stdin {
+            return Picture::Drawn(Err("Not available for inputs from stdin".to_owned()));
+        }
+        let source = self.source.clone();
+        let sender = self.sender.clone();
+        let spawned = thread::Builder::new()
+            .name(format!("day{:02}-picture", solver.day))
+            .stack_size(runner::STACK_SIZE)
+            .spawn(move || {
+                let picture = panic::catch_unwind(AssertUnwindSafe(|| {
+                    let input = input::load(&source, solver.day)?;
+                    solver.render(&input).map_err(|err| err.to_string())
+                }))
+                .unwrap_or_else(|_| Err("Drawing the picture panicked".to_owned()));
+                // The dashboard might be closed already.
+                let _ = sender.send(Message::Picture(solver.day, picture));
+            });
+        match spawned {
+            Ok(_) => Picture::Drawing,
+            Err(err) => Picture::Drawn(Err(format!("Unable to start a thread: {}", err))),
+        }
+    }
+
+    /// The picture of the selected day, once all its parts have finished. It's drawn in the
+    /// background the first time.
+    fn picture(&mut self) -> Option<String> {
+        let day = self.selected_day();
+        let states = &self.states[day as usize - 1];
+        let started = states.iter().any(|s| !matches!(s, State::Skipped));
+        let finished = states
+            .iter()
+            .all(|s| matches!(s, State::Done(_) | State::Skipped));
+        if !started || !finished {
+            return None;
+        }
+        let solver = *self.solvers.iter().find(|s| s.day == day)?;
+        if !self.pictures.contains_key(&day) {
+            let picture = self.draw_picture(solver);
+            self.pictures.insert(day, picture);
+        }
+        match &self.pictures[&day] {
+            Picture::Drawing => Some("Drawing the picture…".to_owned()),
+            Picture::Drawn(Ok(picture)) => picture.clone(),
+            Picture::Drawn(Err(err)) => Some(err.clone()),
+        }
+    }
+
+    fn part_text(state: &State) -> Span<'static> {
+        match state {
+            State::Skipped => Span::raw(" -       ").dark_gray(),
+            State::Waiting => Span::raw(" ·       ").dark_gray(),
+            State::Running(since) => {
+                Span::raw(format!(" … {:<6}", format!("{:.0?}", since.elapsed()))).yellow()
+            }
+            State::Done(record) if record.status == Status::Ok => {
+                Span::raw(format!(" ✔ {:<6}", duration_text(record.total_time()))).green()
+            }
+            State::Done(record) => {
+                let status = match record.status {
+                    Status::Timeout => "TIMEOUT",
+                    Status::Panic => "PANIC",
+                    _ => "ERROR",
+                };
+                Span::raw(format!(" ✘ {:<6}", status)).red()
+            }
+        }
+    }
+
+    /// The answers and timings of the selected day, and its timings in the baselines.
+    fn details(&mut self) -> String {
+        let day = self.selected_day();
+        let mut table = Table::new();
+        table.set_format(*format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
+        table.set_titles(row!["Part", "Result", "Parse", "Solve"]);
+        for (part, state) in (1..).zip(&self.states[day as usize - 1]) {
+            match state {
+                State::Skipped => continue,
+                State::Waiting => table.add_row(row![part, "Waiting", "", ""]),
+                State::Running(since) => table.add_row(row![
+                    part,
+                    format!("Running for {:.1?}", since.elapsed()),
+                    "",
+                    ""
+                ]),
+                State::Done(record) => table.add_row(row![
+                    part,
+                    report::result_text(record),
+                    duration_text(record.parse_time),
+                    duration_text(record.solve_time)
+                ]),
+            };
+        }
+        let mut text = format!("Day {:02}\n\n{}", day, table);
+
+        if !self.history.is_empty() {
+            let mut table = Table::new();
+            table.set_format(*format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
+            table.set_titles(row!["Baseline", "Part 1 median", "Part 2 median"]);
+            for (name, benchmarks) in &self.history {
+                let median = |part| {
+                    benchmarks
+                        .iter()
+                        .find(|b| b.day == day && b.part == part)
+                        .map_or("-".to_owned(), |b| duration_text(b.stats.median))
+                };
+                table.add_row(row![name, median(1), median(2)]);
+            }
+            text += &format!("\nHistory\n\n{}", table);
+        }
+        if let Some(picture) = self.picture() {
+            text += &format!("\n{}", picture);
+        }
+        text
+    }
+
+    /// Draws the whole dashboard.
+    pub fn draw(&mut self, frame: &mut Frame) {
+        let [main, help] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+        let [days, details] =
+            Layout::horizontal([Constraint::Length(30), Constraint::Min(0)]).areas(main);
+
+        let items = self
+            .states
+            .iter()
+            .enumerate()
+            .map(|(i, [part1, part2])| {
+                ListItem::new(Line::from(vec![
+                    Span::raw(format!("Day {:02}", i + 1)),
+                    Dashboard::part_text(part1),
+                    Dashboard::part_text(part2),
+                ]))
+            })
+            .collect::<Vec<_>>();
+        let title = match self.elapsed {
+            Some(elapsed) => format!(" Days, done in {} ", duration_text(elapsed)),
+            None => " Days ".to_owned(),
+        };
+        let list = List::new(items)
+            .block(Block::bordered().title(title))
+            .highlight_style(Style::new().reversed());
+        frame.render_stateful_widget(list, days, &mut self.list);
+
+        let details_text = self.details();
+        let paragraph = Paragraph::new(details_text)
+            .block(Block::bordered())
+            .scroll((self.scroll, 0));
+        frame.render_widget(paragraph, details);
+
+        let keys = " ↑/↓ select a day   PgUp/PgDn scroll   q quit";
+        frame.render_widget(Line::from(keys).fg(Color::DarkGray), help);
+    }
+}
+
+/// Runs the solvers on `jobs` threads while showing the dashboard, until it's closed.
+///
+/// Returns the records and the wall-clock time of the run, when it finished before closing it.
+pub fn run(
+    solvers: &[Solver],
+    source: Source,
+    timeout: Option<Duration>,
+    jobs: usize,
+    history: Vec<Baseline>,
+) -> io::Result<Option<(Vec<Record>, Duration)>> {
+    let mut dashboard = Dashboard::new(solvers, source.clone(), history);
+    let sender = dashboard.sender.clone();
+    let runner_solvers = solvers.to_vec();
+    let runner_source = source;
+    thread::spawn(move || {
+        let before = Instant::now();
+        let mut inputs = Inputs::new(runner_source);
+        runner::run_all_observed(&runner_solvers, &mut inputs, timeout, jobs, &|progress| {
+            let message = match progress {
+                Progress::Started(i) => Message::Started(i),
+                Progress::Finished(i, record) => Message::Finished(i, record.clone()),
+            };
+            // The dashboard might be closed already.
+            let _ = sender.send(message);
+        });
+        let _ = sender.send(Message::Done(before.elapsed()));
+    });
+
+    let _hook = PanicHook::install(ratatui::restore);
+    let result = init().and_then(|mut terminal| event_loop(&mut dashboard, &mut terminal));
+    ratatui::restore();
+    result?;
+    Ok(dashboard.records().zip(dashboard.elapsed))
+}
+
+/// Sets up the terminal like `ratatui::init`, without its panic hook restoring the terminal for
+/// every panic, including the ones caught on the solver and picture threads.
+fn init() -> io::Result<DefaultTerminal> {
+    terminal::enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen)?;
+    Terminal::new(CrosstermBackend::new(io::stdout()))
+}
+
+type Hook = dyn Fn(&PanicHookInfo) + Send + Sync;
+
+/// Restores the terminal when the main thread panics, keeping quiet about the panics on other
+/// threads as they'd garble the dashboard. The previous hook is put back when dropped.
+struct PanicHook {
+    previous: Arc<Hook>,
+}
+
+impl PanicHook {
+    fn install(restore: impl Fn() + Send + Sync + 'static) -> PanicHook {
+        let previous: Arc<Hook> = Arc::from(panic::take_hook());
+        let hook = previous.clone();
+        let main = thread::current().id();
+        panic::set_hook(Box::new(move |info| {
+            if thread::current().id() == main {
+                restore();
+                hook(info);
+            }
+        }));
+        PanicHook { previous }
+    }
+}
+
+impl Drop for PanicHook {
+    fn drop(&mut self) {
+        let previous = self.previous.clone();
+        panic::set_hook(Box::new(move |info| previous(info)));
+    }
+}
+
+fn event_loop(dashboard: &mut Dashboard, terminal: &mut DefaultTerminal) -> io::Result<()> {
+    loop {
+        dashboard.receive();
+        terminal.draw(|frame| dashboard.draw(frame))?;
+        if event::poll(REFRESH)? {
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press && !dashboard.key(key.code) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bench::Stats;
+    use crate::solution::solvers;
+    use ratatui::backend::TestBackend;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn screen(dashboard: &mut Dashboard) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+        terminal.draw(|frame| dashboard.draw(frame)).unwrap();
+        terminal.backend().to_string()
+    }
+
+    #[test]
+    fn follows_the_run() {
+        let solvers = solvers()
+            .into_iter()
+            .filter(|s| s.day == 1 || s.day == 25)
+            .collect::<Vec<_>>();
+        let mut dashboard = Dashboard::new(&solvers, Source::Default, vec![]);
+        assert!(screen(&mut dashboard).contains("Day 01 ·        ·"));
+        assert!(screen(&mut dashboard).contains("Day 02 -        -"));
+        assert!(screen(&mut dashboard).contains("Day 25 ·        -"));
+
+        dashboard.update(Message::Started(0));
+        dashboard.update(Message::Started(1));
+        let record = runner::run(&solvers[0], Ok("+1, +1"), None);
+        dashboard.update(Message::Finished(0, record));
+        let screen = screen(&mut dashboard);
+        assert!(screen.contains("Day 01 ✔"));
+        assert!(screen.contains("Running for"));
+        assert!(dashboard.records().is_none());
+
+        let record = runner::run(&solvers[1], Ok("+1, -1"), None);
+        dashboard.update(Message::Finished(1, record.clone()));
+        dashboard.update(Message::Started(2));
+        let panic = runner::run(&solvers[2], Ok("1,2,3"), None);
+        dashboard.update(Message::Finished(2, panic));
+        let records = dashboard.records().unwrap();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[1], record);
+        dashboard.update(Message::Done(Duration::from_millis(20)));
+        let screen = self::screen(&mut dashboard);
+        assert!(screen.contains("Day 25 ✘ ERROR"));
+        assert!(screen.contains("Days, done in 20.0ms"));
+    }
+
+    #[test]
+    fn details_of_the_selected_day() {
+        let solvers = solvers()
+            .into_iter()
+            .filter(|s| s.day == 18)
+            .collect::<Vec<_>>();
+        let stats = Stats::from_samples(&[Duration::from_millis(3)]);
+        let history = vec![(
+            "before".to_owned(),
+            vec![Benchmark {
+                day: 18,
+                part: 1,
+                runs: 1,
+                stats,
+            }],
+        )];
+        let mut dashboard = Dashboard::new(&solvers, Source::Default, history);
+        for (i, solver) in solvers.iter().enumerate() {
+            let record = runner::run(solver, Ok(".#\n|.\n"), None);
+            dashboard.update(Message::Finished(i, record));
+        }
+        dashboard.source = Source::Stdin;
+
+        let details = dashboard.details();
+        assert!(details.starts_with("Day 18"));
+        assert!(details.contains("| before   | 3.0ms         | -             |"));
+        assert!(details.contains("Not available for inputs from stdin"));
+
+        assert!(dashboard.key(KeyCode::Up));
+        assert!(dashboard.details().starts_with("Day 17"));
+        assert!(dashboard.key(KeyCode::Down));
+        assert!(dashboard.key(KeyCode::Down));
+        assert_eq!(dashboard.selected_day(), 19);
+        assert!(!dashboard.key(KeyCode::Char('q')));
+    }
+
+    #[test]
+    fn pictures() {
+        let solvers = solvers()
+            .into_iter()
+            .filter(|s| s.day == 18)
+            .collect::<Vec<_>>();
+        let mut dashboard = Dashboard::new(&solvers, Source::Default, vec![]);
+        assert_eq!(dashboard.picture(), None);
+        for (i, solver) in solvers.iter().enumerate() {
+            let record = runner::run(solver, Ok(".#\n|.\n"), None);
+            dashboard.update(Message::Finished(i, record));
+        }
+        // Drawn in the background, without holding up the dashboard.
+        assert_eq!(dashboard.picture().unwrap(), "Drawing the picture…");
+        let start = Instant::now();
+        while let Some(Picture::Drawing) = dashboard.pictures.get(&18) {
+            assert!(start.elapsed() < Duration::from_secs(60));
+            thread::sleep(Duration::from_millis(10));
+            dashboard.receive();
+        }
+        let picture = dashboard.picture().unwrap();
+        assert_eq!(picture.lines().count(), 50);
+    }
+
+    #[test]
+    fn panic_hook() {
+        let solver = solvers()
+            .into_iter()
+            .find(|s| s.day == 20 && s.part == 1)
+            .unwrap();
+        let restored = Arc::new(AtomicUsize::new(0));
+        let counter = restored.clone();
+        let hook = PanicHook::install(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+        // The part panics on its own thread, which leaves the terminal alone.
+        let record = runner::run(&solver, Ok("^$"), None);
+        assert_eq!(record.status, Status::Panic);
+        assert_eq!(restored.load(Ordering::SeqCst), 0);
+
+        assert!(panic::catch_unwind(|| panic!("on the main thread")).is_err());
+        assert_eq!(restored.load(Ordering::SeqCst), 1);
+        drop(hook);
+        assert!(panic::catch_unwind(|| panic!("after closing")).is_err());
+        assert_eq!(restored.load(Ordering::SeqCst), 1);
+    }
+}
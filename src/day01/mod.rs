@@ -1,14 +1,23 @@
+//! Day 1: Chronal Calibration.
+
+use crate::parse;
+use crate::solution::{ParseError, Solution};
 use std::collections::HashSet;
 
-pub fn raw_input() -> String {
+#[cfg(any(test, feature = "embedded-inputs"))]
+pub fn get_input() -> String {
     include_str!("input.txt").to_string()
 }
 
-pub fn parse_input(input: &str) -> Vec<i32> {
-    input
-        .split_whitespace()
-        .map(|e| e.replace(",", "").replace("+", "").parse::<_>().unwrap())
-        .collect()
+/// The changes are separated by newlines or commas.
+pub fn parse_input(input: &str) -> Result<Vec<i32>, ParseError> {
+    let mut result = vec![];
+    for line in parse::lines(Day01::DAY, input) {
+        for change in line.text.split(',').filter(|e| !e.trim().is_empty()) {
+            result.push(line.parse(change, "a frequency change like `+1` or `-2`")?);
+        }
+    }
+    Ok(result)
 }
 
 pub fn part1(input: &[i32]) -> i32 {
@@ -27,41 +36,65 @@ pub fn part2(input: &[i32]) -> i32 {
     unreachable!()
 }
 
+pub struct Day01;
+
+impl Solution for Day01 {
+    const DAY: u8 = 1;
+
+    type Input = Vec<i32>;
+    type Answer1 = i32;
+    type Answer2 = i32;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_input(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Answer1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Answer2 {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_parse_input() {
-        assert_eq!(parse_input("+1, +1, +1"), vec![1, 1, 1]);
-        assert_eq!(parse_input("+1, +1, -2"), vec![1, 1, -2]);
-        assert_eq!(parse_input("-1, -2, -3"), vec![-1, -2, -3]);
+        assert_eq!(parse_input("+1, +1, +1").unwrap(), vec![1, 1, 1]);
+        assert_eq!(parse_input("+1, +1, -2").unwrap(), vec![1, 1, -2]);
+        assert_eq!(parse_input("-1, -2, -3").unwrap(), vec![-1, -2, -3]);
+        assert_eq!(parse_input("+1\n-2\n").unwrap(), vec![1, -2]);
+        assert_eq!(parse_input("+1, 2x").unwrap_err().column, 4);
     }
 
     #[test]
     fn examples_part1() {
-        assert_eq!(part1(&parse_input("+1, -2, +3, +1")), 3);
-        assert_eq!(part1(&parse_input("+1, +1, +1")), 3);
-        assert_eq!(part1(&parse_input("+1, +1, -2")), 0);
-        assert_eq!(part1(&parse_input("-1, -2, -3")), -6);
+        assert_eq!(part1(&parse_input("+1, -2, +3, +1").unwrap()), 3);
+        assert_eq!(part1(&parse_input("+1, +1, +1").unwrap()), 3);
+        assert_eq!(part1(&parse_input("+1, +1, -2").unwrap()), 0);
+        assert_eq!(part1(&parse_input("-1, -2, -3").unwrap()), -6);
     }
 
     #[test]
     fn result_part1() {
-        assert_eq!(part1(&parse_input(&raw_input())), 516);
+        assert_eq!(part1(&parse_input(&get_input()).unwrap()), 516);
     }
 
     #[test]
     fn examples_part2() {
-        assert_eq!(part2(&parse_input("+1, -2, +3, +1")), 2);
-        assert_eq!(part2(&parse_input("+1, -1")), 0);
-        assert_eq!(part2(&parse_input("+3, +3, +4, -2, -4")), 10);
-        assert_eq!(part2(&parse_input("-6, +3, +8, +5, -6")), 5);
-        assert_eq!(part2(&parse_input("+7, +7, -2, -7, -4")), 14);
+        assert_eq!(part2(&parse_input("+1, -2, +3, +1").unwrap()), 2);
+        assert_eq!(part2(&parse_input("+1, -1").unwrap()), 0);
+        assert_eq!(part2(&parse_input("+3, +3, +4, -2, -4").unwrap()), 10);
+        assert_eq!(part2(&parse_input("-6, +3, +8, +5, -6").unwrap()), 5);
+        assert_eq!(part2(&parse_input("+7, +7, -2, -7, -4").unwrap()), 14);
     }
 
     #[test]
     fn result_part2() {
-        assert_eq!(part2(&parse_input(&raw_input())), 71892);
+        assert_eq!(part2(&parse_input(&get_input()).unwrap()), 71892);
     }
 }
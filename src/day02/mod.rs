@@ -1,16 +1,32 @@
+//! Day 2: Inventory Management System.
+
+use crate::parse;
+use crate::solution::{ParseError, Solution};
 use std::collections::HashMap;
 
-pub fn get_input<'a>() -> Vec<&'a str> {
-    include_str!("input.txt").lines().collect::<Vec<_>>()
+#[cfg(any(test, feature = "embedded-inputs"))]
+pub fn get_input() -> String {
+    include_str!("input.txt").to_owned()
+}
+
+pub fn parse_input(input: &str) -> Result<Vec<String>, ParseError> {
+    parse::lines(Day02::DAY, input)
+        .map(
+            |line| match line.text.find(|c: char| !c.is_ascii_lowercase()) {
+                Some(column) => Err(line.error_at(column + 1, "a lowercase box ID")),
+                None => Ok(line.text.to_owned()),
+            },
+        )
+        .collect()
 }
 
-pub fn part1(input: &[&str]) -> u32 {
+pub fn part1<S: AsRef<str>>(input: &[S]) -> u32 {
     let mut twos = 0;
     let mut threes = 0;
 
     for elem in input {
         let mut chars: HashMap<char, u32> = HashMap::new();
-        for c in elem.chars() {
+        for c in elem.as_ref().chars() {
             *chars.entry(c).or_insert(0) += 1;
         }
         if chars.values().any(|&count| count == 2) {
@@ -23,9 +39,9 @@ pub fn part1(input: &[&str]) -> u32 {
     twos * threes
 }
 
-pub fn part2(input: &[&str]) -> String {
-    for (idx, id) in input.iter().enumerate() {
-        for id2 in input.iter().skip(idx + 1) {
+pub fn part2<S: AsRef<str>>(input: &[S]) -> String {
+    for (idx, id) in input.iter().map(|id| id.as_ref()).enumerate() {
+        for id2 in input.iter().skip(idx + 1).map(|id| id.as_ref()) {
             // If there's only a single digit different between the two ID's.
             if id.chars().zip(id2.chars()).filter(|(a, b)| a != b).count() == 1 {
                 // Return the characters that are equal, ignore the characters that differ between the IDs.
@@ -41,13 +57,35 @@ pub fn part2(input: &[&str]) -> String {
     unreachable!()
 }
 
+pub struct Day02;
+
+impl Solution for Day02 {
+    const DAY: u8 = 2;
+
+    type Input = Vec<String>;
+    type Answer1 = u32;
+    type Answer2 = String;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_input(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Answer1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Answer2 {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_part1_examples() {
-        let input = vec![
+        let input = [
             "abcdef", "bababc", "abbcde", "abcccd", "aabcdd", "abcdee", "ababab",
         ];
 
@@ -59,12 +97,12 @@ mod tests {
 
     #[test]
     fn test_part1_input() {
-        assert_eq!(part1(&get_input()[..]), 8296);
+        assert_eq!(part1(&parse_input(&get_input()).unwrap()), 8296);
     }
 
     #[test]
     fn test_part2_examples() {
-        let input = vec![
+        let input = [
             "abcde", "fghij", "klmno", "pqrst", "fguij", "axcye", "wvxyz",
         ];
 
@@ -73,6 +111,9 @@ mod tests {
 
     #[test]
     fn test_part2_input() {
-        assert_eq!(part2(&get_input()[..]), "pazvmqbftrbeosiecxlghkwud");
+        assert_eq!(
+            part2(&parse_input(&get_input()).unwrap()),
+            "pazvmqbftrbeosiecxlghkwud"
+        );
     }
 }
@@ -1,13 +1,12 @@
+//! Day 3: No Matter How You Slice It.
+
 extern crate regex;
 
+use crate::geometry::Vec2;
+use crate::parse;
+use crate::solution::{ParseError, Solution};
 use std::collections::{HashMap, HashSet};
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
-struct Point {
-    x: u32,
-    y: u32,
-}
-
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 pub struct Claim {
     number: u32,
@@ -17,34 +16,34 @@ pub struct Claim {
     height: u32,
 }
 
-pub fn parse_input(input: &str) -> Vec<Claim> {
-    let input_regex = regex::Regex::new(r"#(\d+) @ (\d+),(\d+): (\d+)x(\d+)").unwrap();
-    input
-        .lines()
+pub fn parse_input(input: &str) -> Result<Vec<Claim>, ParseError> {
+    let input_regex = regex::Regex::new(r"^#(\d+) @ (\d+),(\d+): (\d+)x(\d+)$").unwrap();
+    parse::lines(Day03::DAY, input)
         .map(|line| {
-            let caps = input_regex.captures(line).unwrap();
-            Claim {
-                number: caps[1].parse().unwrap(),
-                x: caps[2].parse().unwrap(),
-                y: caps[3].parse().unwrap(),
-                width: caps[4].parse().unwrap(),
-                height: caps[5].parse().unwrap(),
-            }
+            let caps = line.captures(&input_regex, "a claim like `#1 @ 1,3: 4x4`")?;
+            Ok(Claim {
+                number: line.parse(&caps[1], "a claim number")?,
+                x: line.parse(&caps[2], "a left edge")?,
+                y: line.parse(&caps[3], "a top edge")?,
+                width: line.parse(&caps[4], "a width")?,
+                height: line.parse(&caps[5], "a height")?,
+            })
         })
         .collect()
 }
 
+#[cfg(any(test, feature = "embedded-inputs"))]
 pub fn get_input() -> String {
     include_str!("input.txt").to_owned()
 }
 
 pub fn part1(input: &[Claim]) -> u32 {
-    let mut map: HashMap<Point, u32> = HashMap::new();
+    let mut map: HashMap<Vec2<u32>, u32> = HashMap::new();
 
     for claim in input {
         for dy in claim.y..claim.y + claim.height {
             for dx in claim.x..claim.x + claim.width {
-                *map.entry(Point { x: dx, y: dy }).or_insert(0) += 1;
+                *map.entry(Vec2::new(dx, dy)).or_insert(0) += 1;
             }
         }
     }
@@ -53,14 +52,14 @@ pub fn part1(input: &[Claim]) -> u32 {
 }
 
 pub fn part2(input: &[Claim]) -> u32 {
-    let mut map: HashMap<Point, Vec<u32>> = HashMap::new();
+    let mut map: HashMap<Vec2<u32>, Vec<u32>> = HashMap::new();
     let mut seen_numbers = HashSet::new();
 
     for claim in input {
         for dy in claim.y..claim.y + claim.height {
             for dx in claim.x..claim.x + claim.width {
-                let p = Point { x: dx, y: dy };
-                let vec = map.entry(p).or_insert_with(|| vec![]);
+                let p = Vec2::new(dx, dy);
+                let vec = map.entry(p).or_default();
                 vec.push(claim.number);
                 if vec.len() > 1 {
                     for val in vec {
@@ -78,15 +77,39 @@ pub fn part2(input: &[Claim]) -> u32 {
         .unwrap()
 }
 
+pub struct Day03;
+
+impl Solution for Day03 {
+    const DAY: u8 = 3;
+
+    type Input = Vec<Claim>;
+    type Answer1 = u32;
+    type Answer2 = u32;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_input(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Answer1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Answer2 {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::generate;
+    use proptest::prelude::*;
 
     const TEST_INPUT: &str = include_str!("test.txt");
 
     #[test]
     fn test_parse_input() {
-        let claims = parse_input(TEST_INPUT);
+        let claims = parse_input(TEST_INPUT).unwrap();
 
         assert_eq!(
             claims,
@@ -118,29 +141,46 @@ mod tests {
 
     #[test]
     fn test_part1_examples() {
-        let claims = parse_input(TEST_INPUT);
+        let claims = parse_input(TEST_INPUT).unwrap();
 
         assert_eq!(part1(&claims), 4);
     }
 
     #[test]
     fn test_part1() {
-        let claims = parse_input(&get_input());
+        let claims = parse_input(&get_input()).unwrap();
 
         assert_eq!(part1(&claims), 117505);
     }
 
     #[test]
     fn test_part2_examples() {
-        let claims = parse_input(TEST_INPUT);
+        let claims = parse_input(TEST_INPUT).unwrap();
 
         assert_eq!(part2(&claims), 3);
     }
 
     #[test]
     fn test_part2() {
-        let claims = parse_input(&get_input());
+        let claims = parse_input(&get_input()).unwrap();
 
         assert_eq!(part2(&claims), 1254);
     }
+
+    fn overlaps(a: &Claim, b: &Claim) -> bool {
+        a.x < b.x + b.width && b.x < a.x + a.width && a.y < b.y + b.height && b.y < a.y + a.height
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        #[test]
+        fn intact_claim(seed in any::<u64>()) {
+            let claims = parse_input(&generate::generate(3, seed, Some(40)).unwrap()).unwrap();
+            let intact = claims.iter().find(|c| c.number == part2(&claims)).unwrap();
+            prop_assert!(claims.iter().all(|c| c == intact || !overlaps(c, intact)));
+            let area = claims.iter().map(|c| c.width * c.height).sum::<u32>();
+            prop_assert!(part1(&claims) <= area);
+        }
+    }
 }
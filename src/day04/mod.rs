@@ -1,5 +1,9 @@
+//! Day 4: Repose Record.
+
 extern crate regex;
 
+use crate::parse;
+use crate::solution::{ParseError, Solution};
 use std::collections::HashMap;
 
 #[derive(Debug, Eq, PartialEq)]
@@ -10,12 +14,9 @@ pub struct SleepInterval {
 
 pub type GuardData = HashMap<i32, Vec<SleepInterval>>;
 
-pub fn parse_input(input: &str) -> GuardData {
-    let mut sorted_lines = input
-        .lines()
-        .map(|line| line.to_string())
-        .collect::<Vec<_>>();
-    sorted_lines.sort();
+pub fn parse_input(input: &str) -> Result<GuardData, ParseError> {
+    let mut sorted_lines = parse::lines(Day04::DAY, input).collect::<Vec<_>>();
+    sorted_lines.sort_by_key(|line| line.text);
 
     let match_minute_action = regex::Regex::new(r"^\[.+:(\d+)\] (.+)$").unwrap();
     let match_guard_id = regex::Regex::new(r"^.*Guard \#(\d+) begins.*$").unwrap();
@@ -26,30 +27,45 @@ pub fn parse_input(input: &str) -> GuardData {
 
     // Iterate on all the days in order, one guard is on duty each night.
     for line in sorted_lines {
-        let captures = match_minute_action.captures(&line).unwrap();
-        let minute = captures[1].parse::<i32>().unwrap();
-        let action = captures[2].to_owned();
-
-        match action.as_ref() {
+        let captures = line.captures(
+            &match_minute_action,
+            "a record like `[1518-11-01 00:05] falls asleep`",
+        )?;
+        let minute = line.parse::<i32>(&captures[1], "a minute")?;
+        let action = captures.get(2).unwrap().as_str();
+
+        match action {
             // The start of a new sleep interval.
-            "falls asleep" => start_minute = minute,
+            "falls asleep" if person >= 0 => start_minute = minute,
             // A sleep interval is over, register the start/end minute.
-            "wakes up" => guards.entry(person).or_default().push(SleepInterval {
-                start_minute,
-                end_minute: minute,
-            }),
+            "wakes up" if start_minute >= 0 => {
+                guards.entry(person).or_default().push(SleepInterval {
+                    start_minute,
+                    end_minute: minute,
+                });
+                start_minute = -1;
+            }
+            "falls asleep" => return Err(line.error(action, "a guard to begin a shift first")),
+            "wakes up" => return Err(line.error(action, "a guard to fall asleep first")),
             // Determine the guard on duty, by the first line for the given day.
             _ => {
-                let guard_match = match_guard_id.captures(&action).unwrap();
-                person = guard_match[1].parse().unwrap();
+                let guard_match = match_guard_id.captures(action).ok_or_else(|| {
+                    line.error(
+                        action,
+                        "`falls asleep`, `wakes up` or `Guard #N begins shift`",
+                    )
+                })?;
+                person = line.parse(&guard_match[1], "a guard ID")?;
+                start_minute = -1;
             }
         }
     }
 
     // Returns the map of guards,
-    guards
+    Ok(guards)
 }
 
+#[cfg(any(test, feature = "embedded-inputs"))]
 pub fn get_input() -> String {
     include_str!("input.txt").to_owned()
 }
@@ -107,6 +123,28 @@ pub fn part2(input: &GuardData) -> i32 {
     guard_id * minute
 }
 
+pub struct Day04;
+
+impl Solution for Day04 {
+    const DAY: u8 = 4;
+
+    type Input = GuardData;
+    type Answer1 = i32;
+    type Answer2 = i32;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_input(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Answer1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Answer2 {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -115,28 +153,28 @@ mod tests {
 
     #[test]
     fn test_part1_examples() {
-        let guard_data = parse_input(TEST_DATA);
+        let guard_data = parse_input(TEST_DATA).unwrap();
 
         assert_eq!(part1(&guard_data), 10 * 24);
     }
 
     #[test]
     fn test_part1_result() {
-        let guard_data = parse_input(&get_input());
+        let guard_data = parse_input(&get_input()).unwrap();
 
         assert_eq!(part1(&guard_data), 39422);
     }
 
     #[test]
     fn test_part2_examples() {
-        let guard_data = parse_input(TEST_DATA);
+        let guard_data = parse_input(TEST_DATA).unwrap();
 
         assert_eq!(part2(&guard_data), 99 * 45);
     }
 
     #[test]
     fn test_part2_result() {
-        let guard_data = parse_input(&get_input());
+        let guard_data = parse_input(&get_input()).unwrap();
 
         assert_eq!(part2(&guard_data), 65474);
     }
@@ -1,9 +1,25 @@
+//! Day 5: Alchemical Reduction.
+
+use crate::parse;
+use crate::solution::{ParseError, Solution};
 use std::collections::{HashSet, VecDeque};
 
+#[cfg(any(test, feature = "embedded-inputs"))]
 pub fn get_input() -> String {
     include_str!("input.txt").to_owned()
 }
 
+/// The polymer is a single line of letters.
+pub fn parse_input(input: &str) -> Result<String, ParseError> {
+    let line = parse::first_line(Day05::DAY, input, "a polymer")?;
+    let polymer = line.text.trim();
+    match polymer.find(|c: char| !c.is_ascii_alphabetic()) {
+        _ if polymer.is_empty() => Err(line.error_at(1, "a polymer")),
+        Some(column) => Err(line.error(&polymer[column..], "a unit (a letter)")),
+        None => Ok(polymer.to_owned()),
+    }
+}
+
 pub fn part1(input: &str) -> String {
     let mut result = input.chars().collect::<VecDeque<_>>();
     let mut i = 0;
@@ -13,9 +29,7 @@ pub fn part1(input: &str) -> String {
         if c != c2 && c.eq_ignore_ascii_case(&c2) {
             result.remove(i);
             result.remove(i);
-            if i > 0 {
-                i -= 1;
-            }
+            i = i.saturating_sub(1);
         } else {
             i += 1;
         }
@@ -31,20 +45,38 @@ pub fn part2(input: &str) -> usize {
         .collect::<HashSet<_>>()
         .iter()
         .map(|&unit| -> usize {
-            part1(
-                &processed_input
-                    .replace(unit, "")
-                    .replace(unit.to_ascii_uppercase(), ""),
-            )
-            .len()
+            part1(&processed_input.replace([unit, unit.to_ascii_uppercase()], "")).len()
         })
         .min()
-        .unwrap()
+        .unwrap_or(0)
+}
+
+pub struct Day05;
+
+impl Solution for Day05 {
+    const DAY: u8 = 5;
+
+    type Input = String;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_input(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Answer1 {
+        part1(input).len()
+    }
+
+    fn part2(input: &Self::Input) -> Self::Answer2 {
+        part2(input)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
     #[test]
     fn test_part1_examples() {
@@ -69,4 +101,17 @@ mod tests {
     fn test_part2_result() {
         assert_eq!(part2(&get_input()), 4934);
     }
+
+    proptest! {
+        #[test]
+        fn fully_reacted(polymer in "[a-cA-C]{0,100}") {
+            let result = part1(&polymer);
+            let units = result.chars().collect::<Vec<_>>();
+            prop_assert!(units
+                .windows(2)
+                .all(|pair| pair[0] == pair[1] || !pair[0].eq_ignore_ascii_case(&pair[1])));
+            prop_assert_eq!(part1(&result), result.clone());
+            prop_assert!(part2(&polymer) <= result.len());
+        }
+    }
 }
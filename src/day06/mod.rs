@@ -1,39 +1,36 @@
+//! Day 6: Chronal Coordinates.
+
+use crate::geometry::{BoundingBox, Vec2};
+use crate::parse;
+use crate::solution::{ParseError, Solution};
 use std::collections::{HashMap, HashSet};
 
-#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
-pub struct Point {
-    x: i32,
-    y: i32,
-}
+pub type Point = Vec2<i32>;
 
+#[cfg(any(test, feature = "embedded-inputs"))]
 pub fn get_input() -> String {
     include_str!("input.txt").to_owned()
 }
 
-pub fn parse_input(input: &str) -> Vec<Point> {
-    input
-        .lines()
-        .map(|line| -> Point {
-            let mapped = line
-                .to_owned()
-                .split(", ")
-                .take(2)
-                .map(|e| e.parse::<i32>().unwrap())
-                .collect::<Vec<_>>();
-            Point {
-                x: mapped[0],
-                y: mapped[1],
-            }
+pub fn parse_input(input: &str) -> Result<Vec<Point>, ParseError> {
+    let points = parse::lines(Day06::DAY, input)
+        .map(|line| {
+            let [x, y] = line.fields(", ", "a coordinate like `1, 6`")?;
+            Ok(Point::new(
+                line.parse(x, "an x coordinate")?,
+                line.parse(y, "a y coordinate")?,
+            ))
         })
-        .collect()
+        .collect::<Result<Vec<_>, _>>()?;
+    if points.is_empty() {
+        return Err(parse::unexpected_end(Day06::DAY, input, "a coordinate"));
+    }
+    Ok(points)
 }
 
 pub fn part1(input: &[Point]) -> u32 {
     // Determine the boundaries of the map.
-    let min_x = input.iter().map(|e| e.x).min().unwrap();
-    let max_x = input.iter().map(|e| e.x).max().unwrap();
-    let min_y = input.iter().map(|e| e.y).min().unwrap();
-    let max_y = input.iter().map(|e| e.y).max().unwrap();
+    let bounds = BoundingBox::from_points(input.iter().copied()).unwrap();
 
     // Build a map with a distance of 0 to the destinations.
     let mut map = input.iter().map(|key| (key, 0)).collect::<HashMap<_, _>>();
@@ -42,28 +39,29 @@ pub fn part1(input: &[Point]) -> u32 {
     let mut at_edge = HashSet::new();
 
     // Loop through all the points in the grid inside the boundaries, and calculate the closest points based on the existing map data.
-    for x in min_x..=max_x {
-        for y in min_y..=max_y {
+    for x in bounds.min.x()..=bounds.max.x() {
+        for y in bounds.min.y()..=bounds.max.y() {
+            let pos = Point::new(x, y);
             // Determine the closest destination, and the distance to it.
             let mut min_dist = None;
             let mut closest = None;
             for &point in map.keys() {
-                // The manhattan distance.
-                let distance = (point.x - x).abs() + (point.y - y).abs();
-                if min_dist.is_none() || distance < min_dist.unwrap() {
-                    min_dist = Some(distance);
-                    closest = Some(point);
-                } else if min_dist.is_some() && min_dist.unwrap() == distance {
-                    closest = None;
+                let distance = point.manhattan(&pos);
+                match min_dist {
+                    Some(min) if distance > min => {}
+                    Some(min) if distance == min => closest = None,
+                    _ => {
+                        min_dist = Some(distance);
+                        closest = Some(point);
+                    }
                 }
             }
 
-            if closest.is_some() {
-                let closest = closest.unwrap();
-                *map.entry(&closest).or_insert(0) += 1;
+            if let Some(closest) = closest {
+                *map.entry(closest).or_insert(0) += 1;
 
                 // If we're at the edge of the map, mark the destination as `at_edge`.
-                if x <= min_x || x >= max_x || y <= min_y || y >= max_y {
+                if bounds.on_edge(&pos) {
                     at_edge.insert(closest);
                 }
             }
@@ -80,19 +78,14 @@ pub fn part1(input: &[Point]) -> u32 {
 }
 
 pub fn part2(input: &[Point], less_than: i32) -> u32 {
-    let min_x = input.iter().map(|e| e.x).min().unwrap();
-    let max_x = input.iter().map(|e| e.x).max().unwrap();
-    let min_y = input.iter().map(|e| e.y).min().unwrap();
-    let max_y = input.iter().map(|e| e.y).max().unwrap();
+    let bounds = BoundingBox::from_points(input.iter().copied()).unwrap();
 
     let mut count = 0;
 
-    for x in min_x..=max_x {
-        for y in min_y..=max_y {
-            let mut sum: i32 = 0;
-            for &point in input.iter() {
-                sum += (point.x - x).abs() + (point.y - y).abs();
-            }
+    for x in bounds.min.x()..=bounds.max.x() {
+        for y in bounds.min.y()..=bounds.max.y() {
+            let pos = Point::new(x, y);
+            let sum: i32 = input.iter().map(|point| point.manhattan(&pos)).sum();
 
             if sum < less_than {
                 count += 1;
@@ -102,29 +95,64 @@ pub fn part2(input: &[Point], less_than: i32) -> u32 {
     count as u32
 }
 
+pub struct Day06;
+
+impl Solution for Day06 {
+    const DAY: u8 = 6;
+
+    type Input = Vec<Point>;
+    type Answer1 = u32;
+    type Answer2 = u32;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_input(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Answer1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Answer2 {
+        part2(input, 10_000)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::generate;
+    use proptest::prelude::*;
 
     const TEST_INPUT: &str = include_str!("test.txt");
 
     #[test]
     fn part1_examples() {
-        assert_eq!(part1(&parse_input(TEST_INPUT)), 17);
+        assert_eq!(part1(&parse_input(TEST_INPUT).unwrap()), 17);
     }
 
     #[test]
     fn part1_result() {
-        assert_eq!(part1(&parse_input(&get_input())), 4143);
+        assert_eq!(part1(&parse_input(&get_input()).unwrap()), 4143);
     }
 
     #[test]
     fn part2_examples() {
-        assert_eq!(part2(&parse_input(TEST_INPUT), 32), 16);
+        assert_eq!(part2(&parse_input(TEST_INPUT).unwrap(), 32), 16);
     }
 
     #[test]
     fn part2_results() {
-        assert_eq!(part2(&parse_input(&get_input()), 10_000), 35039);
+        assert_eq!(part2(&parse_input(&get_input()).unwrap(), 10_000), 35039);
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(16))]
+
+        #[test]
+        fn safe_region_grows(seed in any::<u64>(), limit in 1..20_000i32) {
+            let points = parse_input(&generate::generate(6, seed, Some(12)).unwrap()).unwrap();
+            prop_assert!(part2(&points, limit) <= part2(&points, limit + 500));
+            prop_assert!(part1(&points) > 0);
+        }
     }
 }
@@ -1,26 +1,41 @@
+//! Day 7: The Sum of Its Parts.
+
+use crate::parse;
+use crate::solution::{ParseError, Solution};
 use std::collections::{BTreeSet, BinaryHeap, HashMap, HashSet};
 
+#[cfg(any(test, feature = "embedded-inputs"))]
 pub fn get_input() -> String {
     include_str!("input.txt").to_owned()
 }
 
-pub fn parse_input(input: &str) -> HashMap<char, Vec<char>> {
+/// Parses the steps into a map from each step to the steps depending on it.
+pub fn parse_input(input: &str) -> Result<HashMap<char, Vec<char>>, ParseError> {
+    parse_input_part2(input).map(|steps| dependencies(&steps))
+}
+
+fn dependencies(steps: &[(char, char)]) -> HashMap<char, Vec<char>> {
     let mut result = HashMap::new();
-    for line in input.lines() {
-        let chars = line.chars().collect::<Vec<_>>();
-        result
-            .entry(chars[5])
-            .or_insert_with(|| vec![])
-            .push(chars[36]);
+    for &(before, after) in steps {
+        result.entry(before).or_insert_with(Vec::new).push(after);
     }
     result
 }
 
-pub fn parse_input_part2(input: &str) -> Vec<(char, char)> {
-    input
-        .lines()
-        .map(|l| l.as_bytes())
-        .map(|l| (l[5] as char, l[36] as char))
+/// Parses the steps into pairs of a step and a step depending on it.
+pub fn parse_input_part2(input: &str) -> Result<Vec<(char, char)>, ParseError> {
+    let m_step =
+        regex::Regex::new(r"^Step ([A-Z]) must be finished before step ([A-Z]) can begin\.$")
+            .unwrap();
+    parse::lines(Day07::DAY, input)
+        .map(|line| {
+            let caps = line.captures(
+                &m_step,
+                "a step like `Step C must be finished before step A can begin.`",
+            )?;
+            let step = |i: usize| caps[i].chars().next().unwrap();
+            Ok((step(1), step(2)))
+        })
         .collect()
 }
 
@@ -107,32 +122,79 @@ pub fn part2(input: &[(char, char)], workercount: usize, time_offset: i32) -> i3
     -time
 }
 
+pub struct Day07;
+
+impl Solution for Day07 {
+    const DAY: u8 = 7;
+
+    type Input = (HashMap<char, Vec<char>>, Vec<(char, char)>);
+    type Answer1 = String;
+    type Answer2 = i32;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        let steps = parse_input_part2(input)?;
+        Ok((dependencies(&steps), steps))
+    }
+
+    fn part1(input: &Self::Input) -> Self::Answer1 {
+        part1(&mut input.0.clone())
+    }
+
+    fn part2(input: &Self::Input) -> Self::Answer2 {
+        part2(&input.1, 5, 60)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::generate;
+    use proptest::prelude::*;
 
     const TEST_INPUT: &str = include_str!("test.txt");
 
     #[test]
     fn part1_examples() {
-        assert_eq!(part1(&mut parse_input(TEST_INPUT)), "CABDFE");
+        assert_eq!(part1(&mut parse_input(TEST_INPUT).unwrap()), "CABDFE");
     }
 
     #[test]
     fn part1_result() {
         assert_eq!(
-            part1(&mut parse_input(&get_input())),
+            part1(&mut parse_input(&get_input()).unwrap()),
             "OCPUEFIXHRGWDZABTQJYMNKVSL"
         )
     }
 
     #[test]
     fn part2_examples() {
-        assert_eq!(part2(&mut parse_input_part2(TEST_INPUT), 2, 0), 15);
+        assert_eq!(part2(&parse_input_part2(TEST_INPUT).unwrap(), 2, 0), 15);
     }
 
     #[test]
     fn part2_result() {
-        assert_eq!(part2(&mut parse_input_part2(&get_input()), 5, 60), 991);
+        assert_eq!(part2(&parse_input_part2(&get_input()).unwrap(), 5, 60), 991);
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        #[test]
+        fn steps_in_order(seed in any::<u64>(), size in 2..=26usize) {
+            let input = generate::generate(7, seed, Some(size)).unwrap();
+            let steps = parse_input_part2(&input).unwrap();
+            let order = part1(&mut parse_input(&input).unwrap());
+            prop_assert_eq!(order.len(), size);
+            for (before, after) in &steps {
+                prop_assert!(order.find(*before) < order.find(*after));
+            }
+
+            // The workers take at least as long as the slowest step, and at most as long as a
+            // single worker.
+            let time = |step: char| 61 + (step as i32 - 'A' as i32);
+            let seconds = part2(&steps, 5, 60);
+            prop_assert!(seconds >= order.chars().map(time).max().unwrap());
+            prop_assert!(seconds <= order.chars().map(time).sum());
+        }
     }
 }
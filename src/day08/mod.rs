@@ -1,3 +1,8 @@
+//! Day 8: Memory Maneuver.
+
+use crate::parse;
+use crate::solution::{ParseError, Solution};
+
 #[derive(Debug, PartialEq, Eq)]
 pub struct Node {
     children: Vec<Node>,
@@ -6,7 +11,7 @@ pub struct Node {
 
 impl Node {
     pub fn sum_metadata(&self) -> usize {
-        self.metadata.iter().sum::<usize>() + self.children.iter().map(|c| part1(&c)).sum::<usize>()
+        self.metadata.iter().sum::<usize>() + self.children.iter().map(part1).sum::<usize>()
     }
 
     pub fn value(&self) -> usize {
@@ -24,22 +29,34 @@ impl Node {
     }
 }
 
+#[cfg(any(test, feature = "embedded-inputs"))]
 pub fn get_input() -> String {
     include_str!("input.txt").to_owned()
 }
 
-pub fn parse_input(input: &str) -> Node {
-    parse_node(&mut input.split_whitespace().map(|e| e.parse().unwrap()))
+pub fn parse_input(input: &str) -> Result<Node, ParseError> {
+    let mut numbers = vec![];
+    for line in parse::lines(Day08::DAY, input) {
+        for number in line.text.split_whitespace() {
+            numbers.push(line.parse(number, "a number")?);
+        }
+    }
+    parse_node(&mut numbers.into_iter())
+        .ok_or_else(|| parse::unexpected_end(Day08::DAY, input, "more numbers"))
 }
 
-fn parse_node(iter: &mut impl Iterator<Item = usize>) -> Node {
-    let child_nodes = iter.next().unwrap();
-    let meta_nodes = iter.next().unwrap();
+fn parse_node(iter: &mut impl Iterator<Item = usize>) -> Option<Node> {
+    let child_nodes = iter.next()?;
+    let meta_nodes = iter.next()?;
 
-    Node {
-        children: (0..child_nodes).map(|_| parse_node(iter)).collect(),
-        metadata: iter.take(meta_nodes).collect(),
-    }
+    Some(Node {
+        children: (0..child_nodes)
+            .map(|_| parse_node(iter))
+            .collect::<Option<_>>()?,
+        metadata: (0..meta_nodes)
+            .map(|_| iter.next())
+            .collect::<Option<_>>()?,
+    })
 }
 
 pub fn part1(root: &Node) -> usize {
@@ -50,6 +67,28 @@ pub fn part2(root: &Node) -> usize {
     root.value()
 }
 
+pub struct Day08;
+
+impl Solution for Day08 {
+    const DAY: u8 = 8;
+
+    type Input = Node;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_input(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Answer1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Answer2 {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -58,21 +97,21 @@ mod tests {
 
     #[test]
     fn part1_examples() {
-        assert_eq!(part1(&parse_input(TEST_INPUT)), 138);
+        assert_eq!(part1(&parse_input(TEST_INPUT).unwrap()), 138);
     }
 
     #[test]
     fn part1_result() {
-        assert_eq!(part1(&parse_input(&get_input())), 43825);
+        assert_eq!(part1(&parse_input(&get_input()).unwrap()), 43825);
     }
 
     #[test]
     fn part2_examples() {
-        assert_eq!(part2(&parse_input(TEST_INPUT)), 66);
+        assert_eq!(part2(&parse_input(TEST_INPUT).unwrap()), 66);
     }
 
     #[test]
     fn part2_result() {
-        assert_eq!(part2(&parse_input(&get_input())), 19276);
+        assert_eq!(part2(&parse_input(&get_input()).unwrap()), 19276);
     }
 }
@@ -1,17 +1,40 @@
+//! Day 9: Marble Mania.
+
+use crate::parse;
+use crate::solution::{ParseError, Solution};
 use std::collections::VecDeque;
 
-pub fn get_input() -> (usize, usize) {
-    let input = include_str!("input.txt")
-        .split_whitespace()
-        .map(|e| e.parse())
-        .filter(|e| e.is_ok())
-        .map(|e| e.unwrap())
-        .collect::<Vec<_>>();
-    (input[0], input[1])
+#[cfg(any(test, feature = "embedded-inputs"))]
+pub fn get_input() -> String {
+    include_str!("input.txt").to_owned()
+}
+
+/// The most players and the highest last marble accepted, part 2 keeps 100 times as many marbles
+/// in the circle.
+pub const MAX_PLAYERS: usize = 100_000;
+pub const MAX_MARBLE: usize = 100_000;
+
+/// Parses the number of players and the value of the last marble.
+pub fn parse_input(input: &str) -> Result<(usize, usize), ParseError> {
+    let m_game = regex::Regex::new(r"^(\d+) players; last marble is worth (\d+) points$").unwrap();
+    let expected = "a game like `10 players; last marble is worth 1618 points`";
+    let line = parse::first_line(Day09::DAY, input, expected)?;
+    let caps = line.captures(&m_game, expected)?;
+    let players = line.parse(&caps[1], "a number of players")?;
+    if !(1..=MAX_PLAYERS).contains(&players) {
+        let expected = format!("between 1 and {} players", MAX_PLAYERS);
+        return Err(line.error(&caps[1], expected));
+    }
+    let marbles = line.parse(&caps[2], "a marble value")?;
+    if marbles > MAX_MARBLE {
+        let expected = format!("a marble value of at most {}", MAX_MARBLE);
+        return Err(line.error(&caps[2], expected));
+    }
+    Ok((players, marbles))
 }
 
 pub fn part1(players: usize, marbles: usize) -> usize {
-    let mut circle = VecDeque::with_capacity(marbles);
+    let mut circle = VecDeque::new();
     circle.push_back(0);
     let mut scores = vec![0; players];
 
@@ -39,6 +62,28 @@ pub fn part2(players: usize, marbles: usize) -> usize {
     part1(players, marbles * 100)
 }
 
+pub struct Day09;
+
+impl Solution for Day09 {
+    const DAY: u8 = 9;
+
+    type Input = (usize, usize);
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_input(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Answer1 {
+        part1(input.0, input.1)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Answer2 {
+        part2(input.0, input.1)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -53,15 +98,31 @@ mod tests {
         assert_eq!(part1(30, 5807), 37305);
     }
 
+    #[test]
+    fn parse_errors() {
+        let error = parse_input("0 players; last marble is worth 25 points").unwrap_err();
+        assert_eq!(
+            (error.column, error.expected.as_str()),
+            (1, "between 1 and 100000 players")
+        );
+        let error = parse_input("1 players; last marble is worth 10000000000 points").unwrap_err();
+        assert_eq!(error.column, 33);
+        assert_eq!(error.expected, "a marble value of at most 100000");
+        assert_eq!(
+            parse_input("9 players; last marble is worth 100000 points"),
+            Ok((9, 100_000))
+        );
+    }
+
     #[test]
     fn part1_result() {
-        let (players, marbles) = get_input();
+        let (players, marbles) = parse_input(&get_input()).unwrap();
         assert_eq!(part1(players, marbles), 439635);
     }
 
     #[test]
     fn part2_result() {
-        let (players, marbles) = get_input();
+        let (players, marbles) = parse_input(&get_input()).unwrap();
         assert_eq!(part2(players, marbles), 3562722971);
     }
 }
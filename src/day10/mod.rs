@@ -1,74 +1,87 @@
+//! Day 10: The Stars Align.
+
 extern crate regex;
 
+use crate::geometry::{BoundingBox, Vec2};
+use crate::parse;
+use crate::solution::{ParseError, Solution};
 use std::collections::HashSet;
 
+#[cfg(any(test, feature = "embedded-inputs"))]
 pub fn get_input() -> String {
     include_str!("input.txt").to_owned()
 }
 
 #[derive(Debug, Copy, Clone)]
 pub struct Point {
-    position: (i32, i32),
-    velocity: (i32, i32),
+    position: Vec2<i32>,
+    velocity: Vec2<i32>,
 }
 
 impl Point {
     /// Moves the point according to the velocity, returning a new point at the new location.
     fn tick(&self) -> Point {
         Point {
-            position: (
-                self.position.0 + self.velocity.0,
-                self.position.1 + self.velocity.1,
-            ),
+            position: self.position + self.velocity,
             velocity: self.velocity,
         }
     }
 }
 
-/// Calculates the total grid size for the given input.
-fn grid_size(points: &[Point]) -> i32 {
-    (points.iter().map(|e| e.position.0).max().unwrap()
-        - points.iter().map(|e| e.position.0).min().unwrap())
-        * (points.iter().map(|e| e.position.1).max().unwrap()
-            + points.iter().map(|e| e.position.1).min().unwrap())
+/// The bounds of the points on the board.
+fn bounds(points: &[Point]) -> BoundingBox<2, i32> {
+    BoundingBox::from_points(points.iter().map(|e| e.position)).unwrap()
+}
+
+/// Calculates the total grid size for the given input, the area of the bounding box.
+fn grid_size(points: &[Point]) -> i64 {
+    let size = bounds(points).size();
+    i64::from(size.x()) * i64::from(size.y())
 }
 
-pub fn parse_input(input: &str) -> Vec<Point> {
+pub fn parse_input(input: &str) -> Result<Vec<Point>, ParseError> {
     let match_input =
         regex::Regex::new(r"^position=<\s*(-?\d+),\s*(-?\d+)> velocity=<\s*(-?\d+),\s*(-?\d+)>$")
             .unwrap();
-    input
-        .lines()
-        .map(|line| -> Point {
-            let capt = match_input.captures(&line).unwrap();
-            Point {
-                position: (capt[1].parse().unwrap(), capt[2].parse().unwrap()),
-                velocity: (capt[3].parse().unwrap(), capt[4].parse().unwrap()),
-            }
+    let points = parse::lines(Day10::DAY, input)
+        .map(|line| {
+            let capt = line.captures(
+                &match_input,
+                "a point like `position=< 9,  1> velocity=< 0,  2>`",
+            )?;
+            Ok(Point {
+                position: Vec2::new(
+                    line.parse(&capt[1], "a position")?,
+                    line.parse(&capt[2], "a position")?,
+                ),
+                velocity: Vec2::new(
+                    line.parse(&capt[3], "a velocity")?,
+                    line.parse(&capt[4], "a velocity")?,
+                ),
+            })
         })
-        .collect()
+        .collect::<Result<Vec<_>, _>>()?;
+    if points.is_empty() {
+        return Err(parse::unexpected_end(Day10::DAY, input, "a point"));
+    }
+    Ok(points)
 }
 
 /// Generates ASCII graphics for your eyes only :)
 fn generate_board_graphics(board: &[Point]) -> String {
     // Determine the board boundaries + 1 free column/row in each dimension.
-    let min_x = board.iter().map(|e| e.position.1).min().unwrap() - 1;
-    let max_x = board.iter().map(|e| e.position.1).max().unwrap() + 1;
-    let min_y = board.iter().map(|e| e.position.0).min().unwrap() - 1;
-    let max_y = board.iter().map(|e| e.position.0).max().unwrap() + 1;
-
-    let mut result = String::with_capacity(1 + ((max_x - min_x) * (max_y - min_y) + 1) as usize);
-    result.push_str("\n");
+    let bounds = bounds(board).grow(1);
+    let mut result = String::from("\n");
     let points = board.iter().map(|e| e.position).collect::<HashSet<_>>();
-    for x in min_x..=max_x {
-        for y in min_y..=max_y {
-            if points.contains(&(y, x)) {
+    for y in bounds.min.y()..=bounds.max.y() {
+        for x in bounds.min.x()..=bounds.max.x() {
+            if points.contains(&Vec2::new(x, y)) {
                 result.push('#');
             } else {
                 result.push('.');
             }
         }
-        result.push_str("\n");
+        result.push('\n');
     }
     result
 }
@@ -104,6 +117,28 @@ pub fn part2(input: &[Point]) -> u32 {
     solve(input).1
 }
 
+pub struct Day10;
+
+impl Solution for Day10 {
+    const DAY: u8 = 10;
+
+    type Input = Vec<Point>;
+    type Answer1 = String;
+    type Answer2 = u32;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_input(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Answer1 {
+        part1(input).trim().to_owned()
+    }
+
+    fn part2(input: &Self::Input) -> Self::Answer2 {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -113,7 +148,7 @@ mod tests {
     #[test]
     fn part1_examples() {
         assert_eq!(
-            part1(&parse_input(TEST_INPUT)),
+            part1(&parse_input(TEST_INPUT).unwrap()),
             "
 ............
 .#...#..###.
@@ -132,7 +167,7 @@ mod tests {
     #[test]
     fn part1_result() {
         assert_eq!(
-            part1(&parse_input(&get_input())),
+            part1(&parse_input(&get_input()).unwrap()),
             "
 ................................................................
 .#....#..######..#....#..#####...#.......#####...#....#..#....#.
@@ -150,13 +185,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn grid_area() {
+        let points =
+            parse_input("position=< 0,  5> velocity=< 0,  0>\nposition=< 3,  9> velocity=< 0,  0>")
+                .unwrap();
+        // 4 columns by 5 rows, the height used to be the sum of the y bounds instead.
+        assert_eq!(grid_size(&points), 20);
+    }
+
     #[test]
     fn part2_examples() {
-        assert_eq!(part2(&parse_input(TEST_INPUT)), 3);
+        assert_eq!(part2(&parse_input(TEST_INPUT).unwrap()), 3);
     }
 
     #[test]
     fn part2_result() {
-        assert_eq!(part2(&parse_input(&get_input())), 10459);
+        assert_eq!(part2(&parse_input(&get_input()).unwrap()), 10459);
     }
 }
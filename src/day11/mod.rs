@@ -1,5 +1,16 @@
-pub fn get_input() -> i32 {
-    include_str!("input.txt").parse().unwrap()
+//! Day 11: Chronal Charge.
+
+use crate::parse;
+use crate::solution::{ParseError, Solution};
+
+#[cfg(any(test, feature = "embedded-inputs"))]
+pub fn get_input() -> String {
+    include_str!("input.txt").to_owned()
+}
+
+pub fn parse_input(input: &str) -> Result<i32, ParseError> {
+    let line = parse::first_line(Day11::DAY, input, "a grid serial number")?;
+    line.parse(line.text, "a grid serial number")
 }
 
 pub fn calc_power_level(coord: (i32, i32), input: i32) -> i32 {
@@ -12,7 +23,7 @@ pub fn part1(input: i32) -> (i32, i32) {
     let mut grid = Vec::with_capacity(300 * 300);
     for y in 0..300 {
         for x in 0..300 {
-            grid.push(calc_power_level((x as i32, y as i32), input));
+            grid.push(calc_power_level((x, y), input));
         }
     }
     let mut max = 0;
@@ -41,7 +52,7 @@ pub fn part2(input: i32) -> (i32, i32, i32) {
     let mut grid = Vec::with_capacity(300 * 300);
     for y in 0..300 {
         for x in 0..300 {
-            grid.push(calc_power_level((x as i32, y as i32), input));
+            grid.push(calc_power_level((x, y), input));
         }
     }
     let mut max = 0;
@@ -72,6 +83,30 @@ pub fn part2(input: i32) -> (i32, i32, i32) {
     (max_pos.0, max_pos.1, max_grid_size)
 }
 
+pub struct Day11;
+
+impl Solution for Day11 {
+    const DAY: u8 = 11;
+
+    type Input = i32;
+    type Answer1 = String;
+    type Answer2 = String;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_input(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Answer1 {
+        let (x, y) = part1(*input);
+        format!("{},{}", x, y)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Answer2 {
+        let (x, y, size) = part2(*input);
+        format!("{},{},{}", x, y, size)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -92,7 +127,7 @@ mod tests {
 
     #[test]
     fn part1_result() {
-        assert_eq!(part1(get_input()), (235, 14));
+        assert_eq!(part1(parse_input(&get_input()).unwrap()), (235, 14));
     }
 
     #[test]
@@ -102,6 +137,6 @@ mod tests {
 
     #[test]
     fn part2_result() {
-        assert_eq!(part2(get_input()), (237, 227, 14));
+        assert_eq!(part2(parse_input(&get_input()).unwrap()), (237, 227, 14));
     }
 }
@@ -1,104 +1,192 @@
+//! Day 12: Subterranean Sustainability.
+
+use crate::cycle::{self, Simulation};
+use crate::parse;
+use crate::solution::{ParseError, Solution};
 use std::collections::HashMap;
 
+#[cfg(any(test, feature = "embedded-inputs"))]
 pub fn get_input() -> String {
     include_str!("input.txt").to_owned()
 }
 
-pub fn parse_input(input: &str) -> (String, HashMap<String, char>) {
-    let lines = input.lines().collect::<Vec<_>>();
-    let state = lines[0].split(": ").nth(1).unwrap().trim().to_owned();
-    let map = lines[2..]
-        .iter()
-        .map(|&line| {
-            (
-                line[..5].chars().collect::<String>(),
-                if line.ends_with('#') { '#' } else { '.' },
-            )
+pub fn parse_input(input: &str) -> Result<(String, HashMap<String, char>), ParseError> {
+    let m_state = regex::Regex::new(r"^initial state: ([#.]+)$").unwrap();
+    let m_rule = regex::Regex::new(r"^([#.]{5}) => ([#.])$").unwrap();
+
+    let mut lines = parse::lines(Day12::DAY, input);
+    let expected = "the initial state like `initial state: #..#.#`";
+    let line = lines
+        .next()
+        .ok_or_else(|| ParseError::new(Day12::DAY, 1, 1, expected))?;
+    let state = line.captures(&m_state, expected)?[1].to_owned();
+    let map = lines
+        .filter(|line| !line.text.is_empty())
+        .map(|line| {
+            let caps = line.captures(&m_rule, "a rule like `...## => #`")?;
+            Ok((caps[1].to_owned(), caps[2].chars().next().unwrap()))
         })
-        .collect();
-    (state, map)
+        .collect::<Result<_, _>>()?;
+    Ok((state, map))
 }
 
-pub fn solve(
-    initial_state: &str,
-    instructions: &HashMap<String, char>,
-    generations: i64,
-) -> i64 {
-    let mut n = String::from("...");
-    n.push_str(&initial_state);
-    n.push_str("...");
-
-    let mut last: i64 = 0;
-    let mut diffs: HashMap<i64, u32> = HashMap::new();
-
-    for gen in 1..=generations {
-        // Start with 3 empty pots at the beginning and the end, each time.
-        let mut s = String::from("...");
-        for i in 2..n.len() - 2 {
-            let slice = &n[i - 2..=i + 2];
-            match instructions.get(slice) {
-                Some('#') => {
-                    s.push('#');
-                }
-                _ => s.push('.'),
-            }
-        }
-        s.push_str("...");
-        n = s;
+/// The pots after some generations, trimmed to the leftmost and the rightmost plant.
+#[derive(Debug, Clone)]
+struct Pots<'a> {
+    rules: &'a HashMap<String, char>,
+    plants: String,
+    /// The number of the leftmost pot.
+    offset: i64,
+}
+
+impl<'a> Pots<'a> {
+    fn new(initial_state: &str, rules: &'a HashMap<String, char>) -> Pots<'a> {
+        let mut pots = Pots {
+            rules,
+            plants: initial_state.to_owned(),
+            offset: 0,
+        };
+        pots.trim();
+        pots
+    }
+
+    fn trim(&mut self) {
+        let trimmed = self.plants.trim_start_matches('.');
+        self.offset += (self.plants.len() - trimmed.len()) as i64;
+        self.plants = trimmed.trim_end_matches('.').to_owned();
+    }
 
-        // Make sure to grow the string at both ends.
-        let score: i64 = n
+    fn score(&self) -> i64 {
+        self.plants
             .chars()
             .enumerate()
             .filter(|(_, c)| c == &'#')
-            .map(|(i, _)| i as i64 - (3 + gen) as i64)
-            .sum();
-        let e = diffs.entry(score as i64 - last as i64).or_insert(0);
-        if *e > 10 {
-            return (generations - gen) as i64 * (score - last) + score;
-        } else {
-            *e += 1;
-        }
-        last = score;
+            .map(|(i, _)| i as i64 + self.offset)
+            .sum()
+    }
+}
+
+impl<'a> Simulation for Pots<'a> {
+    /// The same plants might repeat while drifting to the left or the right.
+    type Key = String;
+
+    fn step(&mut self) {
+        // Pad with enough empty pots for plants to grow 2 pots beyond either end.
+        let padded = format!("....{}....", self.plants);
+        self.plants = (2..padded.len() - 2)
+            .map(|i| match self.rules.get(&padded[i - 2..=i + 2]) {
+                Some('#') => '#',
+                _ => '.',
+            })
+            .collect();
+        self.offset -= 2;
+        self.trim();
     }
-    last as i64
+
+    fn key(&self) -> String {
+        self.plants.clone()
+    }
+
+    fn skip(&mut self, periods: u64, start: &Pots, end: &Pots) {
+        self.offset += periods as i64 * (end.offset - start.offset);
+    }
+}
+
+pub fn solve(initial_state: &str, instructions: &HashMap<String, char>, generations: u64) -> i64 {
+    cycle::state_at(&Pots::new(initial_state, instructions), generations).score()
+}
+
+/// Whether the pots settle into a repeating pattern within the given number of generations.
+pub fn settles_within(
+    initial_state: &str,
+    instructions: &HashMap<String, char>,
+    generations: u64,
+) -> bool {
+    cycle::find_within(&Pots::new(initial_state, instructions), generations).is_some()
 }
 
 pub fn part1(initial_state: &str, instructions: &HashMap<String, char>) -> i64 {
-    solve(&initial_state, instructions, 20)
+    solve(initial_state, instructions, 20)
 }
 
 pub fn part2(initial_state: &str, instructions: &HashMap<String, char>) -> i64 {
-    solve(&initial_state, instructions, 50_000_000_000)
+    solve(initial_state, instructions, 50_000_000_000)
+}
+
+pub struct Day12;
+
+impl Solution for Day12 {
+    const DAY: u8 = 12;
+
+    type Input = (String, HashMap<String, char>);
+    type Answer1 = i64;
+    type Answer2 = i64;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_input(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Answer1 {
+        part1(&input.0, &input.1)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Answer2 {
+        part2(&input.0, &input.1)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::generate;
+    use proptest::prelude::*;
 
     const TEST_INPUT: &str = include_str!("test.txt");
 
     #[test]
     fn part1_examples() {
-        let (state, instructions) = parse_input(TEST_INPUT);
+        let (state, instructions) = parse_input(TEST_INPUT).unwrap();
         assert_eq!(part1(&state, &instructions), 325);
     }
 
     #[test]
     fn part1_result() {
-        let (state, instructions) = parse_input(&get_input());
+        let (state, instructions) = parse_input(&get_input()).unwrap();
         assert_eq!(part1(&state, &instructions), 2349);
     }
 
     #[test]
     fn part2_examples() {
-        let (state, instructions) = parse_input(TEST_INPUT);
+        let (state, instructions) = parse_input(TEST_INPUT).unwrap();
         assert_eq!(part2(&state, &instructions), 999999999374);
     }
 
+    #[test]
+    fn any_generation() {
+        let (state, instructions) = parse_input(TEST_INPUT).unwrap();
+        assert_eq!(solve(&state, &instructions, 0), 145);
+        assert_eq!(solve(&state, &instructions, 1), 91);
+        assert_eq!(solve(&state, &instructions, 1000), 19374);
+    }
+
     #[test]
     fn part2_result() {
-        let (state, instructions) = parse_input(&get_input());
+        let (state, instructions) = parse_input(&get_input()).unwrap();
         assert_eq!(part2(&state, &instructions), 2100000001168);
     }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        #[test]
+        fn skipping_generations(seed in any::<u64>(), generations in 0..300u64) {
+            let (state, rules) =
+                parse_input(&generate::generate(12, seed, Some(20)).unwrap()).unwrap();
+            let mut pots = Pots::new(&state, &rules);
+            for _ in 0..generations {
+                pots.step();
+            }
+            prop_assert_eq!(solve(&state, &rules, generations), pots.score());
+        }
+    }
 }
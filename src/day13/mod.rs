@@ -1,137 +1,90 @@
+//! Day 13: Mine Cart Madness.
+
+use crate::geometry::{Direction, Turn, Vec2};
+use crate::grid::Grid;
+use crate::solution::{ParseError, Solution};
 use std::collections::HashSet;
 
+#[cfg(any(test, feature = "embedded-inputs"))]
 pub fn get_input() -> String {
     include_str!("input.txt").to_owned()
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
-/// A two-dimensional direction.
-pub enum Dir {
-    Up,
-    Right,
-    Down,
-    Left,
-}
-
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
-pub enum Inter {
-    Left = 0,
-    Straight = 1,
-    Right = 2,
-}
-
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
-pub struct Position {
-    x: usize,
-    y: usize,
-}
+/// A position on the tracks, formatted the way the puzzle expects the answer, ie. `7,3`.
+pub type Position = Vec2<usize>;
 
 #[derive(Debug, Eq, PartialEq, Clone, Hash)]
 /// A cart with a position and a direction.
 pub struct Cart {
     position: Position,
-    direction: Dir,
-    intersection: Inter,
+    direction: Direction,
+    /// Where to turn at the next intersection.
+    intersection: Turn,
 }
 
-pub fn parse_input(input: &str) -> (Vec<Vec<char>>, Vec<Cart>) {
-    let board = input
-        .lines()
-        .map(|line| line.chars().collect())
-        .collect::<Vec<Vec<_>>>();
-    let mut carts = vec![];
-    for (y, row) in board.iter().enumerate() {
-        for (x, c) in row.iter().enumerate() {
-            let direction = match c {
-                '<' => Some(Dir::Left),
-                '>' => Some(Dir::Right),
-                '^' => Some(Dir::Up),
-                'v' => Some(Dir::Down),
-                _ => None,
-            };
-            if direction.is_none() {
-                continue;
-            }
-            carts.push(Cart {
-                direction: direction.unwrap(),
-                position: Position { x, y },
-                intersection: Inter::Left,
-            })
+pub fn parse_input(input: &str) -> Result<(Grid<char>, Vec<Cart>), ParseError> {
+    // Trailing spaces may have been trimmed, so the lines can be of different lengths.
+    let board = Grid::parse_ragged(Day13::DAY, input, ' ', "a track or a cart", |c| {
+        if " -|/\\+<>^v".contains(c) {
+            Some(c)
+        } else {
+            None
         }
-    }
-    (board, carts)
+    })?;
+    let carts = board
+        .iter()
+        .filter_map(|((x, y), &c)| {
+            Some(Cart {
+                direction: Direction::from_arrow(c)?,
+                position: Position::new(x, y),
+                intersection: Turn::Left,
+            })
+        })
+        .collect();
+    Ok((board, carts))
 }
 
 impl Cart {
     fn tick(&mut self, c: char) {
-        self.direction = match c {
+        self.direction = match (c, self.direction) {
             // Change direction when we hit a corner.
-            '/' => match self.direction {
-                Dir::Left => Dir::Down,
-                Dir::Down => Dir::Left,
-                Dir::Right => Dir::Up,
-                Dir::Up => Dir::Right,
-            },
-            '\\' => match self.direction {
-                Dir::Left => Dir::Up,
-                Dir::Up => Dir::Left,
-                Dir::Right => Dir::Down,
-                Dir::Down => Dir::Right,
-            },
+            ('/', Direction::Left | Direction::Right) => self.direction.turn(Turn::Left),
+            ('/', _) => self.direction.turn(Turn::Right),
+            ('\\', Direction::Left | Direction::Right) => self.direction.turn(Turn::Right),
+            ('\\', _) => self.direction.turn(Turn::Left),
             // Handle intersections.
-            '+' => match self.intersection {
-                Inter::Left => {
-                    self.intersection = Inter::Straight;
-                    match self.direction {
-                        Dir::Left => Dir::Down,
-                        Dir::Down => Dir::Right,
-                        Dir::Right => Dir::Up,
-                        Dir::Up => Dir::Left,
-                    }
-                }
-                Inter::Straight => {
-                    self.intersection = Inter::Right;
-                    self.direction
-                }
-                Inter::Right => {
-                    self.intersection = Inter::Left;
-                    match self.direction {
-                        Dir::Left => Dir::Up,
-                        Dir::Up => Dir::Right,
-                        Dir::Right => Dir::Down,
-                        Dir::Down => Dir::Left,
-                    }
-                }
-            },
+            ('+', _) => {
+                let turn = self.intersection;
+                self.intersection = match turn {
+                    Turn::Left => Turn::Straight,
+                    Turn::Straight => Turn::Right,
+                    Turn::Right => Turn::Left,
+                };
+                self.direction.turn(turn)
+            }
             // Otherwise, proceed.
             _ => self.direction,
         };
         // Move in that direction.
-        match self.direction {
-            Dir::Left => self.position.x -= 1,
-            Dir::Right => self.position.x += 1,
-            Dir::Up => self.position.y -= 1,
-            Dir::Down => self.position.y += 1,
-        };
+        self.position = self
+            .position
+            .step(self.direction)
+            .expect("The cart ran off the tracks");
     }
 }
 
-pub fn part1(board: &[Vec<char>], initial_carts: &[Cart]) -> Position {
+pub fn part1(board: &Grid<char>, initial_carts: &[Cart]) -> Position {
     let mut carts = initial_carts.to_vec();
     loop {
         // All the positions, for all the carts, before ticking anything.
         let mut positions = carts.iter().map(|c| c.position).collect::<HashSet<_>>();
 
         // Sort the carts to make sure we tick them in the right order.
-        carts.sort_by(|a, b| {
-            let p1 = a.position;
-            let p2 = b.position;
-            (p1.y, p1.x).cmp(&(p2.y, p2.x))
-        });
+        carts.sort_by_key(|c| c.position);
 
         // Tick all the carts, while checking for collisions.
         for cart in &mut carts {
-            let c = board[cart.position.y][cart.position.x];
+            let c = board[cart.position];
             positions.remove(&cart.position);
             cart.tick(c);
             if !positions.insert(cart.position) {
@@ -141,7 +94,7 @@ pub fn part1(board: &[Vec<char>], initial_carts: &[Cart]) -> Position {
     }
 }
 
-pub fn part2(board: &[Vec<char>], initial_carts: &[Cart]) -> Position {
+pub fn part2(board: &Grid<char>, initial_carts: &[Cart]) -> Position {
     let mut carts = initial_carts.to_vec();
     loop {
         // If we're done, return the last carts position.
@@ -153,11 +106,7 @@ pub fn part2(board: &[Vec<char>], initial_carts: &[Cart]) -> Position {
         let mut crashed_locations = HashSet::new();
 
         // Sort the carts to make sure we tick them in the right order.
-        carts.sort_by(|a, b| {
-            let p1 = a.position;
-            let p2 = b.position;
-            (p1.y, p1.x).cmp(&(p2.y, p2.x))
-        });
+        carts.sort_by_key(|c| c.position);
 
         // Tick all the carts, while checking for collisions.
         for cart in &mut carts {
@@ -166,7 +115,7 @@ pub fn part2(board: &[Vec<char>], initial_carts: &[Cart]) -> Position {
                 continue;
             }
             // Move the cart.
-            let c = board[cart.position.y][cart.position.x];
+            let c = board[cart.position];
             positions.remove(&cart.position);
             cart.tick(c);
 
@@ -177,11 +126,29 @@ pub fn part2(board: &[Vec<char>], initial_carts: &[Cart]) -> Position {
         }
 
         // Remove all colliding carts.
-        carts = carts
-            .iter()
-            .filter(|&c| !crashed_locations.contains(&c.position))
-            .cloned()
-            .collect();
+        carts.retain(|c| !crashed_locations.contains(&c.position));
+    }
+}
+
+pub struct Day13;
+
+impl Solution for Day13 {
+    const DAY: u8 = 13;
+
+    type Input = (Grid<char>, Vec<Cart>);
+    type Answer1 = Position;
+    type Answer2 = Position;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_input(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Answer1 {
+        part1(&input.0, &input.1)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Answer2 {
+        part2(&input.0, &input.1)
     }
 }
 
@@ -194,47 +161,47 @@ mod tests {
 
     #[test]
     fn parse_example_carts() {
-        let (_, carts) = parse_input(TEST_INPUT);
+        let (_, carts) = parse_input(TEST_INPUT).unwrap();
         assert_eq!(carts.len(), 2);
         assert_eq!(
             carts[0],
             Cart {
-                direction: Dir::Right,
-                position: Position { x: 2, y: 0 },
-                intersection: Inter::Left
+                direction: Direction::Right,
+                position: Position::new(2, 0),
+                intersection: Turn::Left
             }
         );
         assert_eq!(
             carts[1],
             Cart {
-                direction: Dir::Down,
-                position: Position { x: 9, y: 3 },
-                intersection: Inter::Left
+                direction: Direction::Down,
+                position: Position::new(9, 3),
+                intersection: Turn::Left
             }
         );
     }
 
     #[test]
     fn part1_examples() {
-        let (board, carts) = parse_input(TEST_INPUT);
-        assert_eq!(part1(&board, &carts), Position { x: 7, y: 3 });
+        let (board, carts) = parse_input(TEST_INPUT).unwrap();
+        assert_eq!(part1(&board, &carts), Position::new(7, 3));
     }
 
     #[test]
     fn part1_result() {
-        let (board, carts) = parse_input(&get_input());
-        assert_eq!(part1(&board, &carts), Position { x: 41, y: 22 });
+        let (board, carts) = parse_input(&get_input()).unwrap();
+        assert_eq!(part1(&board, &carts), Position::new(41, 22));
     }
 
     #[test]
     fn part2_examples() {
-        let (board, carts) = parse_input(TEST_INPUT2);
-        assert_eq!(part2(&board, &carts), Position { x: 6, y: 4 });
+        let (board, carts) = parse_input(TEST_INPUT2).unwrap();
+        assert_eq!(part2(&board, &carts), Position::new(6, 4));
     }
 
     #[test]
     fn part2_result() {
-        let (board, carts) = parse_input(&get_input());
-        assert_eq!(part2(&board, &carts), Position { x: 84, y: 90 });
+        let (board, carts) = parse_input(&get_input()).unwrap();
+        assert_eq!(part2(&board, &carts), Position::new(84, 90));
     }
 }
@@ -1,5 +1,41 @@
-pub fn get_input() -> usize {
-    704_321
+//! Day 14: Chocolate Charts.
+
+use crate::parse;
+use crate::solution::{ParseError, Solution};
+
+#[cfg(any(test, feature = "embedded-inputs"))]
+pub fn get_input() -> String {
+    include_str!("input.txt").to_owned()
+}
+
+/// The puzzle input, read as a number for part 1 and as a sequence of digits for part 2.
+pub struct Input {
+    pub recipes: usize,
+    pub digits: Vec<usize>,
+}
+
+pub fn parse_digits(input: &str) -> Vec<usize> {
+    input
+        .chars()
+        .filter_map(|c| c.to_digit(10))
+        .map(|d| d as usize)
+        .collect()
+}
+
+/// The most recipes accepted, part 1 keeps every recipe made until then.
+pub const MAX_RECIPES: usize = 10_000_000;
+
+pub fn parse_input(input: &str) -> Result<Input, ParseError> {
+    let line = parse::first_line(Day14::DAY, input, "a number of recipes")?;
+    let recipes = line.parse(line.text, "a number of recipes")?;
+    if recipes > MAX_RECIPES {
+        let expected = format!("at most {} recipes", MAX_RECIPES);
+        return Err(line.error(line.text, expected));
+    }
+    Ok(Input {
+        recipes,
+        digits: parse_digits(line.text),
+    })
 }
 
 pub fn part1(input: usize) -> String {
@@ -23,11 +59,7 @@ pub fn part1(input: usize) -> String {
         .collect()
 }
 
-pub fn part2(input: &str) -> usize {
-    let parsed_input = &input
-        .chars()
-        .map(|c| c.to_digit(10).unwrap() as usize)
-        .collect::<Vec<_>>()[..];
+pub fn part2(parsed_input: &[usize]) -> usize {
     let mut recipes = vec![3, 7];
     let mut elf1 = 0;
     let mut elf2 = 1;
@@ -57,6 +89,28 @@ pub fn part2(input: &str) -> usize {
     result
 }
 
+pub struct Day14;
+
+impl Solution for Day14 {
+    const DAY: u8 = 14;
+
+    type Input = Input;
+    type Answer1 = String;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_input(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Answer1 {
+        part1(input.recipes)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Answer2 {
+        part2(&input.digits)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -71,19 +125,30 @@ mod tests {
 
     #[test]
     fn part1_result() {
-        assert_eq!(part1(get_input()), "1741551073");
+        assert_eq!(
+            part1(parse_input(&get_input()).unwrap().recipes),
+            "1741551073"
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(parse_input("10000000").unwrap().recipes, MAX_RECIPES);
+        let error = parse_input("10000001").err().unwrap();
+        assert_eq!(error.expected, "at most 10000000 recipes");
+        assert!(parse_input("99999999999999999999").is_err());
     }
 
     #[test]
     fn part2_examples() {
-        assert_eq!(part2("51589"), 9);
-        assert_eq!(part2("01245"), 5);
-        assert_eq!(part2("92510"), 18);
-        assert_eq!(part2("59414"), 2018);
+        assert_eq!(part2(&parse_digits("51589")), 9);
+        assert_eq!(part2(&parse_digits("01245")), 5);
+        assert_eq!(part2(&parse_digits("92510")), 18);
+        assert_eq!(part2(&parse_digits("59414")), 2018);
     }
 
     #[test]
     fn part2_result() {
-        assert_eq!(part2(&format!("{}", get_input())), 20322683);
+        assert_eq!(part2(&parse_input(&get_input()).unwrap().digits), 20322683);
     }
 }
@@ -1,10 +1,17 @@
+//! Day 15: Beverage Bandits.
+
+use crate::geometry::Vec2;
+use crate::grid::Grid;
+use crate::solution::{ParseError, Solution};
 use std::cmp::Reverse;
 use std::collections::BinaryHeap;
 use std::collections::HashMap;
 use std::collections::HashSet;
 
-type Point = (usize, usize);
+/// Positions are ordered in reading order, which decides who goes first.
+type Point = Vec2<usize>;
 
+#[cfg(any(test, feature = "embedded-inputs"))]
 pub fn get_input() -> String {
     include_str!("input.txt").to_owned()
 }
@@ -20,7 +27,7 @@ struct Unit {
 impl Default for Unit {
     fn default() -> Self {
         Unit {
-            pos: (0, 0),
+            pos: Point::default(),
             hp: 200,
             power: 3,
             faction: 'E',
@@ -34,46 +41,36 @@ impl Unit {
     }
 }
 
-#[derive(Default)]
-pub struct Day15 {
-    walls: Vec<Vec<bool>>,
+#[derive(Default, Clone)]
+pub struct Cave {
+    walls: Grid<bool>,
     units: Vec<Unit>,
     alive: [usize; 2],
 }
 
-fn manhattan_distance(a: &Point, b: &Point) -> usize {
-    (if a.0 > b.0 { a.0 - b.0 } else { b.0 - a.0 })
-        + (if a.1 > b.1 { a.1 - b.1 } else { b.1 - a.1 })
-}
-
-impl Day15 {
+impl Cave {
     pub fn new() -> Self {
         Default::default()
     }
 
-    fn read_input(&mut self, input: &str) {
-        for (y, line) in input.lines().enumerate() {
-            let mut current = vec![false; line.len()];
-
-            for (x, c) in line.chars().enumerate() {
-                match c {
-                    '#' => {
-                        current[x] = true;
-                    }
-                    'E' | 'G' => {
-                        self.units.push(Unit {
-                            pos: (y, x),
-                            faction: c,
-                            ..Default::default()
-                        });
-                        self.alive[if c == 'E' { 0 } else { 1 }] += 1;
-                    }
-                    '.' => {}
-                    c => panic!("Invalid tile {}!", c),
-                }
+    fn read_input(&mut self, input: &str) -> Result<(), ParseError> {
+        let expected = "a wall, open cavern, elf or goblin";
+        let map = Grid::parse(Day15::DAY, input, expected, |c| match c {
+            '#' | '.' | 'E' | 'G' => Some(c),
+            _ => None,
+        })?;
+        for ((x, y), &c) in map.iter() {
+            if c == 'E' || c == 'G' {
+                self.units.push(Unit {
+                    pos: Point::new(x, y),
+                    faction: c,
+                    ..Default::default()
+                });
+                self.alive[if c == 'E' { 0 } else { 1 }] += 1;
             }
-            self.walls.push(current);
         }
+        self.walls = map.map(|&c| c == '#');
+        Ok(())
     }
 
     fn get_movement(&self, unit: usize) -> Option<Point> {
@@ -99,17 +96,16 @@ impl Day15 {
 
         todo.push(Reverse((0, initial)));
 
-        while let Some(Reverse((d, (y, x)))) = todo.pop() {
-            let next = [(y - 1, x), (y, x - 1), (y, x + 1), (y + 1, x)];
-
-            for pos in &next {
-                if !prev.contains_key(pos) && !self.walls[pos.0][pos.1] {
-                    if enemy_positions.contains(pos) {
-                        return prev.remove(&(y, x));
-                    } else if !all_positions.contains(pos) {
-                        let prev_step = *prev.get(&(y, x)).unwrap_or(pos);
-                        prev.insert(*pos, prev_step);
-                        todo.push(Reverse((d + 1, *pos)));
+        while let Some(Reverse((d, current))) = todo.pop() {
+            for pos in self.walls.neighbours4(current.x(), current.y()) {
+                let pos = Point::from(pos);
+                if !prev.contains_key(&pos) && !self.walls[pos] {
+                    if enemy_positions.contains(&pos) {
+                        return prev.remove(&current);
+                    } else if !all_positions.contains(&pos) {
+                        let prev_step = *prev.get(&current).unwrap_or(&pos);
+                        prev.insert(pos, prev_step);
+                        todo.push(Reverse((d + 1, pos)));
                     }
                 }
             }
@@ -126,7 +122,7 @@ impl Day15 {
             .iter()
             .enumerate()
             .filter(|(_, x)| x.faction != faction && x.is_alive())
-            .filter(|(_, x)| manhattan_distance(&x.pos, &initial) == 1)
+            .filter(|(_, x)| x.pos.manhattan(&initial) == 1)
             .min_by(|&(_, a), &(_, b)| a.hp.cmp(&b.hp).then(a.pos.cmp(&b.pos)));
 
         if let Some((index, _)) = to_attack {
@@ -183,9 +179,14 @@ impl Day15 {
     }
 }
 
-pub fn part1(input: &str) -> String {
-    let mut inst = Day15::new();
-    inst.read_input(input);
+pub fn parse_input(input: &str) -> Result<Cave, ParseError> {
+    let mut cave = Cave::new();
+    cave.read_input(input)?;
+    Ok(cave)
+}
+
+pub fn part1(cave: &Cave) -> String {
+    let mut inst = cave.clone();
     let mut rounds = 0;
     while inst.simulate() {
         rounds += 1;
@@ -193,9 +194,8 @@ pub fn part1(input: &str) -> String {
     inst.return_score(rounds)
 }
 
-pub fn part2(input: &str) -> String {
-    let mut inst = Day15::new();
-    inst.read_input(input);
+pub fn part2(cave: &Cave) -> String {
+    let mut inst = cave.clone();
     let backup = inst.units.clone();
     let starting_elves = inst.alive[0];
     let starting_goblins = inst.alive[1];
@@ -224,6 +224,28 @@ pub fn part2(input: &str) -> String {
     }
 }
 
+pub struct Day15;
+
+impl Solution for Day15 {
+    const DAY: u8 = 15;
+
+    type Input = Cave;
+    type Answer1 = String;
+    type Answer2 = String;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_input(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Answer1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Answer2 {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -232,23 +254,30 @@ mod tests {
 
     #[test]
     fn part1_examples() {
-        assert_eq!(part1(&TEST_INPUT), "27730");
+        assert_eq!(part1(&parse_input(TEST_INPUT).unwrap()), "27730");
+    }
+
+    #[test]
+    fn invalid_tile() {
+        let err = parse_input("#######\n#.G.E.#\n#E.x..#\n").err().unwrap();
+        assert_eq!((err.day, err.line, err.column), (15, 3, 4));
+        assert_eq!(err.expected, "a wall, open cavern, elf or goblin");
     }
 
     #[test]
     fn part1_result() {
-        let input = get_input();
+        let input = parse_input(&get_input()).unwrap();
         assert_eq!(part1(&input), "319410");
     }
 
     #[test]
     fn part2_examples() {
-        assert_eq!(part2(&TEST_INPUT), "4988");
+        assert_eq!(part2(&parse_input(TEST_INPUT).unwrap()), "4988");
     }
 
     #[test]
     fn part2_result() {
-        let input = get_input();
+        let input = parse_input(&get_input()).unwrap();
         assert_eq!(part2(&input), "63168");
     }
 }
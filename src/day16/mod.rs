@@ -1,79 +1,31 @@
+//! Day 16: Chronal Classification.
+
 extern crate regex;
 
+use crate::elfcode::{Cpu, Instruction};
+use crate::parse::{self, Line};
+use crate::solution::{ParseError, Solution};
 use std::collections::{HashMap, HashSet};
+use std::iter::Peekable;
 
-pub fn get_input_part1() -> String {
-    include_str!("input1.txt").to_owned()
-}
+pub use crate::elfcode::{exec, Inst};
 
-pub fn get_input_part2() -> Vec<Vec<usize>> {
-    include_str!("input2.txt")
-        .lines()
-        .map(|line| line_to_vec(line))
-        .collect()
+/// The samples and the program, separated by blank lines like the puzzle input.
+#[cfg(any(test, feature = "embedded-inputs"))]
+pub fn get_input() -> String {
+    format!(
+        "{}\n\n\n\n{}",
+        get_input_part1(),
+        include_str!("input2.txt")
+    )
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
-pub enum Inst {
-    /// add register
-    Addr,
-    /// add immediate
-    Addi,
-    /// multiply register
-    Mulr,
-    /// multiply immediate
-    Muli,
-    /// bitwise and register
-    Banr,
-    /// bitwise and immediate
-    Bani,
-    /// bitwise or register
-    Borr,
-    /// bitwise or immediate
-    Bori,
-    /// set register
-    Setr,
-    /// set immediate
-    Seti,
-    /// greater than immediate/register
-    Gtir,
-    /// greater than register/immediate
-    Gtri,
-    /// greater than register/register
-    Gtrr,
-    /// equal immediate/register
-    Eqir,
-    /// equal register/immediate
-    Eqri,
-    /// equal register/register
-    Eqrr,
-}
-
-impl Inst {
-    /// Converts opcode to instruction.
-    pub fn from_usize(inst: usize) -> Inst {
-        match inst {
-            0x0 => Inst::Addr,
-            0x1 => Inst::Addi,
-            0x2 => Inst::Mulr,
-            0x3 => Inst::Muli,
-            0x4 => Inst::Banr,
-            0x5 => Inst::Bani,
-            0x6 => Inst::Borr,
-            0x7 => Inst::Bori,
-            0x8 => Inst::Setr,
-            0x9 => Inst::Seti,
-            0xa => Inst::Gtir,
-            0xb => Inst::Gtri,
-            0xc => Inst::Gtrr,
-            0xd => Inst::Eqir,
-            0xe => Inst::Eqri,
-            0xf => Inst::Eqrr,
-            _ => panic!("Bad opcode: {}", inst),
-        }
-    }
+#[cfg(any(test, feature = "embedded-inputs"))]
+pub fn get_input_part1() -> String {
+    include_str!("input1.txt").to_owned()
 }
 
+/// A sample of an instruction being executed.
 #[derive(Debug)]
 pub struct Part1Input {
     /// The registers before executing the instruction.
@@ -84,50 +36,71 @@ pub struct Part1Input {
     after: Vec<usize>,
 }
 
-fn line_to_vec(input: &str) -> Vec<usize> {
-    let match_nums = regex::Regex::new(".*?(\\d+).+?(\\d+).+?(\\d+).+?(\\d+).*").unwrap();
-    let captures = match_nums.captures(input).unwrap();
-    (1..captures.len())
-        .map(|e| captures[e].parse().unwrap())
-        .collect()
+/// Parses the four numbers of a line, ie. `Before: [3, 2, 1, 1]` or `9 2 1 2`.
+fn line_to_vec(line: &Line, prefix: &str, expected: &str) -> Result<Vec<usize>, ParseError> {
+    let numbers = line
+        .text
+        .strip_prefix(prefix)
+        .ok_or_else(|| line.error_at(1, expected))?
+        .split(|c: char| !c.is_ascii_digit())
+        .filter(|n| !n.is_empty())
+        .map(|n| line.parse(n, "a number"))
+        .collect::<Result<Vec<_>, _>>()?;
+    if numbers.len() != 4 {
+        return Err(line.error_at(1, expected));
+    }
+    Ok(numbers)
 }
 
-pub fn parse_input(input: &str) -> Vec<Part1Input> {
+/// Parses an instruction, checking the opcode and output register are valid.
+fn parse_inst(line: &Line) -> Result<Vec<usize>, ParseError> {
+    let inst = line_to_vec(line, "", "an instruction like `9 2 1 2`")?;
+    if inst[0] > 0xf {
+        Err(line.error_at(1, "an opcode between 0 and 15"))
+    } else if inst[3] > 3 {
+        Err(line.error_at(1, "an output register between 0 and 3"))
+    } else {
+        Ok(inst)
+    }
+}
+
+/// Parses the samples, stopping at the first empty line between them.
+fn parse_samples<'a>(
+    lines: &mut Peekable<impl Iterator<Item = Line<'a>>>,
+    input: &str,
+) -> Result<Vec<Part1Input>, ParseError> {
     let mut result = vec![];
-    let mut lines = input.lines().peekable();
-    while lines.peek().is_some() {
+    while lines.peek().is_some_and(|line| !line.text.is_empty()) {
+        let mut next = || {
+            lines
+                .next()
+                .ok_or_else(|| parse::unexpected_end(Day16::DAY, input, "the rest of the sample"))
+        };
         result.push(Part1Input {
-            before: line_to_vec(lines.next().unwrap()),
-            inst: line_to_vec(lines.next().unwrap()),
-            after: line_to_vec(lines.next().unwrap()),
+            before: line_to_vec(&next()?, "Before:", "registers like `Before: [3, 2, 1, 1]`")?,
+            inst: parse_inst(&next()?)?,
+            after: line_to_vec(&next()?, "After:", "registers like `After:  [3, 2, 2, 1]`")?,
         });
-        lines.next();
+        // Skip the empty line after the sample.
+        lines.next_if(|line| line.text.is_empty());
     }
-    result
+    Ok(result)
 }
 
-pub fn exec(opcode: Inst, in1: usize, in2: usize, out: usize, reg: &mut [usize]) {
-    reg[out] = match opcode {
-        Inst::Addr => reg[in1] + reg[in2],
-        Inst::Addi => reg[in1] + in2,
-        Inst::Mulr => reg[in1] * reg[in2],
-        Inst::Muli => reg[in1] * in2,
-        Inst::Banr => reg[in1] & reg[in2],
-        Inst::Bani => reg[in1] & in2,
-        Inst::Borr => reg[in1] | reg[in2],
-        Inst::Bori => reg[in1] | in2,
-        Inst::Setr => reg[in1],
-        Inst::Seti => in1,
-        Inst::Gtir => (in1 > reg[in2]) as usize,
-        Inst::Gtri => (reg[in1] > in2) as usize,
-        Inst::Gtrr => (reg[in1] > reg[in2]) as usize,
-        Inst::Eqir => (in1 == reg[in2]) as usize,
-        Inst::Eqri => (reg[in1] == in2) as usize,
-        Inst::Eqrr => (reg[in1] == reg[in2]) as usize,
-    }
+/// Parses the samples from the first section of the input.
+pub fn parse_input(input: &str) -> Result<Vec<Part1Input>, ParseError> {
+    parse_samples(&mut parse::lines(Day16::DAY, input).peekable(), input)
 }
 
-pub fn part1(input: &mut [Part1Input]) -> usize {
+/// Parses the test program from the second section of the input, one `[opcode, a, b, c]` per line.
+pub fn parse_program(input: &str) -> Result<Vec<Vec<usize>>, ParseError> {
+    parse::lines(Day16::DAY, input)
+        .filter(|line| !line.text.is_empty())
+        .map(|line| parse_inst(&line))
+        .collect()
+}
+
+pub fn part1(input: &[Part1Input]) -> usize {
     let mut result = 0;
     for elem in input {
         let mut count = 0;
@@ -147,7 +120,7 @@ pub fn part1(input: &mut [Part1Input]) -> usize {
 }
 
 /// Calculates the opcode <-> instruction mappings, based on the input.
-pub fn calc_mapping(input: &mut [Part1Input]) -> HashMap<usize, Inst> {
+pub fn calc_mapping(input: &[Part1Input]) -> HashMap<usize, Inst> {
     // Maps opcodes -> possible instructions.
     let mut possible_opcodes = HashMap::new();
     for elem in input {
@@ -192,8 +165,7 @@ pub fn calc_mapping(input: &mut [Part1Input]) -> HashMap<usize, Inst> {
             }
         }
         // Remove the inst from the possible opcodes map.
-        if matched.is_some() {
-            let (opcode, inst) = matched.unwrap();
+        if let Some((opcode, inst)) = matched {
             possible_opcodes.remove(&opcode);
             for insts in possible_opcodes.values_mut() {
                 insts.remove(&inst);
@@ -203,16 +175,51 @@ pub fn calc_mapping(input: &mut [Part1Input]) -> HashMap<usize, Inst> {
     mapping
 }
 
-pub fn part2(input: &mut [Part1Input], input2: &[Vec<usize>]) -> usize {
+pub fn part2(input: &[Part1Input], input2: &[Vec<usize>]) -> usize {
     // Determine the opcode mapping.
     let mapping = calc_mapping(input);
 
     // Execute the instructions.
-    let mut reg = vec![0; 4];
-    for line in input2 {
-        exec(mapping[&line[0]], line[1], line[2], line[3], &mut reg);
+    let program = input2
+        .iter()
+        .map(|line| Instruction {
+            inst: mapping[&line[0]],
+            in1: line[1],
+            in2: line[2],
+            out: line[3],
+        })
+        .collect::<Vec<_>>();
+    let mut cpu = Cpu::new(None, program);
+    cpu.run();
+    cpu.registers()[0]
+}
+
+pub struct Day16;
+
+impl Solution for Day16 {
+    const DAY: u8 = 16;
+
+    type Input = (Vec<Part1Input>, Vec<Vec<usize>>);
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        let mut lines = parse::lines(Self::DAY, input).peekable();
+        let samples = parse_samples(&mut lines, input)?;
+        let program = lines
+            .filter(|line| !line.text.is_empty())
+            .map(|line| parse_inst(&line))
+            .collect::<Result<_, _>>()?;
+        Ok((samples, program))
+    }
+
+    fn part1(input: &Self::Input) -> Self::Answer1 {
+        part1(&input.0)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Answer2 {
+        part2(&input.0, &input.1)
     }
-    reg[0]
 }
 
 #[cfg(test)]
@@ -223,21 +230,33 @@ mod tests {
 
     #[test]
     fn part1_examples() {
-        let mut input = parse_input(TEST_INPUT1);
-        assert_eq!(part1(&mut input), 1);
+        let input = parse_input(TEST_INPUT1).unwrap();
+        assert_eq!(part1(&input), 1);
     }
 
     #[test]
     fn part1_result() {
-        let mut input = parse_input(&get_input_part1());
-        assert_eq!(part1(&mut input), 663);
+        let input = parse_input(&get_input_part1()).unwrap();
+        assert_eq!(part1(&input), 663);
     }
 
     #[test]
-    fn part2_result() {
+    fn invalid_samples() {
+        let err = parse_input("Before: [3, 2, 1, 1]\n9 2 1\n").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 1));
+        assert_eq!(err.expected, "an instruction like `9 2 1 2`");
+        let err = parse_input("Before: [3, 2, 1, 1]\n9 2 1 2\n").unwrap_err();
+        assert_eq!(err.expected, "the rest of the sample");
+        let err = Day16::parse(&format!("{}\n\n\n16 0 0 0", TEST_INPUT1)).unwrap_err();
         assert_eq!(
-            part2(&mut parse_input(&get_input_part1()), &get_input_part2()),
-            525
+            (err.line, err.expected.as_str()),
+            (6, "an opcode between 0 and 15")
         );
     }
+
+    #[test]
+    fn part2_result() {
+        let (samples, program) = Day16::parse(&get_input()).unwrap();
+        assert_eq!(part2(&samples, &program), 525);
+    }
 }
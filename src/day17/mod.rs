@@ -1,24 +1,33 @@
-pub struct Grid {
-    grid: Vec<Vec<char>>,
+//! Day 17: Reservoir Research.
+
+use crate::grid::Grid;
+use crate::parse;
+use crate::solution::{ParseError, Solution};
+
+/// The scanned ground, with the clay and the water.
+#[derive(Clone)]
+pub struct Reservoir {
+    grid: Grid<char>,
     min_y: usize,
     max_y: usize,
 }
 
+#[cfg(any(test, feature = "embedded-inputs"))]
 pub fn get_input() -> String {
     include_str!("input.txt").to_owned()
 }
 
-impl Grid {
+impl Reservoir {
     /// Let the water flow until there are no more changes in the grid.
     pub fn flow(&mut self) {
         while self.drip(0, 500) {}
     }
 
     fn drip(&mut self, start_y: usize, start_x: usize) -> bool {
-        for y in start_y..self.grid.len() {
+        for y in start_y..self.grid.height() {
             // Flow down through the dots.
-            if self.grid[y][start_x] == '|' || self.grid[y][start_x] == '.' {
-                self.grid[y][start_x] = '|';
+            if self.grid[(start_x, y)] == '|' || self.grid[(start_x, y)] == '.' {
+                self.grid[(start_x, y)] = '|';
                 continue;
             }
 
@@ -29,12 +38,12 @@ impl Grid {
 
             // Fill to the left, until we're not making progress.
             for x in (0..start_x).rev() {
-                if self.grid[flow_y][x] == '#' {
+                if self.grid[(x, flow_y)] == '#' {
                     hit_left_wall = true;
                     break;
                 }
-                self.grid[flow_y][x] = '|';
-                if self.grid[y][x] != '#' && self.grid[y][x] != '~' {
+                self.grid[(x, flow_y)] = '|';
+                if self.grid[(x, y)] != '#' && self.grid[(x, y)] != '~' {
                     if self.drip(flow_y, x) {
                         made_progress = true;
                     }
@@ -43,13 +52,13 @@ impl Grid {
             }
 
             // Fill to the right, until we're not making progress.
-            for x in start_x..2000 {
-                if self.grid[flow_y][x] == '#' {
+            for x in start_x..self.grid.width() {
+                if self.grid[(x, flow_y)] == '#' {
                     hit_right_wall = true;
                     break;
                 }
-                self.grid[flow_y][x] = '|';
-                if self.grid[y][x] != '#' && self.grid[y][x] != '~' {
+                self.grid[(x, flow_y)] = '|';
+                if self.grid[(x, y)] != '#' && self.grid[(x, y)] != '~' {
                     if self.drip(flow_y, x) {
                         made_progress = true;
                     }
@@ -61,16 +70,16 @@ impl Grid {
             if hit_left_wall && hit_right_wall {
                 made_progress = true;
                 for x in (0..start_x).rev() {
-                    if self.grid[flow_y][x] != '|' {
+                    if self.grid[(x, flow_y)] != '|' {
                         break;
                     }
-                    self.grid[flow_y][x] = '~';
+                    self.grid[(x, flow_y)] = '~';
                 }
-                for x in start_x..self.grid[flow_y].len() {
-                    if self.grid[flow_y][x] != '|' {
+                for x in start_x..self.grid.width() {
+                    if self.grid[(x, flow_y)] != '|' {
                         break;
                     }
-                    self.grid[flow_y][x] = '~';
+                    self.grid[(x, flow_y)] = '~';
                 }
             }
             return made_progress;
@@ -81,7 +90,7 @@ impl Grid {
     /// Find all the water.
     pub fn part1(&self) -> usize {
         self.grid
-            .iter()
+            .rows()
             .skip(self.min_y)
             .take(self.max_y - self.min_y + 1)
             .map(|row| row.iter().filter(|&c| c == &'~' || c == &'|').count())
@@ -91,42 +100,109 @@ impl Grid {
     /// Find all the settled water.
     pub fn part2(&self) -> usize {
         self.grid
-            .iter()
+            .rows()
             .skip(self.min_y)
             .take(self.max_y - self.min_y + 1)
             .map(|row| row.iter().filter(|&c| c == &'~').count())
             .sum()
     }
 
-    pub fn from_input(input: &str) -> Grid {
-        let re_input = regex::Regex::new(r"(\w)=(\d+), (\w)=(\d+)..(\d+)").unwrap();
-
-        let mut grid = vec![vec!['.'; 2000]; 2000];
-        let mut min_y = usize::max_value();
-        let mut max_y = 0;
-
-        for line in input.lines() {
-            let caps = re_input.captures(line).unwrap();
-            let c1 = caps[1].to_string();
-            let c2 = caps[2].parse::<usize>().unwrap();
-            let c4 = caps[4].parse::<usize>().unwrap();
-            let c5 = caps[5].parse::<usize>().unwrap();
-
-            if c1 == "x" {
-                for (y, row) in grid.iter_mut().enumerate().take(c5 + 1).skip(c4) {
-                    max_y = std::cmp::max(max_y, y);
-                    min_y = std::cmp::min(min_y, y);
-                    row[c2] = '#';
-                }
+    /// Draws the scanned ground, leaving out the empty columns on the sides.
+    pub fn render(&self) -> String {
+        let used = |x: &usize| self.grid.column(*x).any(|&c| c != '.');
+        let min_x = (0..self.grid.width()).find(used).unwrap_or(0);
+        let max_x = (0..self.grid.width()).rev().find(used).unwrap_or(0);
+        self.grid
+            .rows()
+            .skip(self.min_y)
+            .take(self.max_y - self.min_y + 1)
+            .map(|row| {
+                row[min_x.saturating_sub(1)..=max_x]
+                    .iter()
+                    .collect::<String>()
+                    + "\n"
+            })
+            .collect()
+    }
+
+    pub fn from_input(input: &str) -> Result<Reservoir, ParseError> {
+        let re_input = regex::Regex::new(r"^([xy])=(\d+), ([xy])=(\d+)\.\.(\d+)$").unwrap();
+
+        // The veins as ranges of x and y coordinates.
+        let mut veins = vec![];
+        for line in parse::lines(Day17::DAY, input) {
+            let caps = line.captures(&re_input, "a vein of clay like `x=495, y=2..7`")?;
+            if caps[3] == caps[1] {
+                return Err(line.error(&caps[3], "the other axis"));
+            }
+            // Leave room for the water to flow around the clay.
+            let coordinate = |i| match line.parse::<usize>(&caps[i], "a coordinate")? {
+                c if (1..1999).contains(&c) => Ok(c),
+                _ => Err(line.error(&caps[i], "a coordinate between 1 and 1998")),
+            };
+            let c2 = coordinate(2)?;
+            let c4 = coordinate(4)?;
+            let c5 = coordinate(5)?;
+            if c4 > c5 {
+                return Err(line.error(&caps[5], "the end of the range to be after the start"));
+            }
+
+            if &caps[1] == "x" {
+                veins.push((c2..=c2, c4..=c5));
             } else {
-                min_y = std::cmp::min(min_y, c2);
-                max_y = std::cmp::max(max_y, c2);
-                for x in c4..=c5 {
-                    grid[c2][x] = '#';
+                veins.push((c4..=c5, c2..=c2));
+            }
+        }
+
+        let min_y = veins.iter().map(|(_, ys)| *ys.start()).min();
+        let max_y = veins.iter().map(|(_, ys)| *ys.end()).max();
+        let max_x = veins.iter().map(|(xs, _)| *xs.end()).max();
+        let (min_y, max_y, max_x) = match (min_y, max_y, max_x) {
+            (Some(min_y), Some(max_y), Some(max_x)) => (min_y, max_y, max_x),
+            _ => return Err(parse::unexpected_end(Day17::DAY, input, "a vein of clay")),
+        };
+        // The water can flow past the rightmost clay, and the spring is at x=500.
+        let mut grid = Grid::new((max_x + 2).max(501), max_y + 1, '.');
+        for (xs, ys) in veins {
+            for y in ys {
+                for x in xs.clone() {
+                    grid[(x, y)] = '#';
                 }
             }
         }
-        Grid { grid, min_y, max_y }
+        Ok(Reservoir { grid, min_y, max_y })
+    }
+}
+
+pub struct Day17;
+
+impl Solution for Day17 {
+    const DAY: u8 = 17;
+
+    type Input = Reservoir;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        Reservoir::from_input(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Answer1 {
+        let mut grid = input.clone();
+        grid.flow();
+        grid.part1()
+    }
+
+    fn part2(input: &Self::Input) -> Self::Answer2 {
+        let mut grid = input.clone();
+        grid.flow();
+        grid.part2()
+    }
+
+    fn render(input: &Self::Input) -> Option<String> {
+        let mut grid = input.clone();
+        grid.flow();
+        Some(grid.render())
     }
 }
 
@@ -138,28 +214,36 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        let mut grid = Grid::from_input(TEST_INPUT);
+        let mut grid = Reservoir::from_input(TEST_INPUT).unwrap();
         grid.flow();
         assert_eq!(grid.part1(), 57);
     }
 
+    #[test]
+    fn render_example() {
+        let picture = Day17::render(&Reservoir::from_input(TEST_INPUT).unwrap()).unwrap();
+        assert_eq!(picture.lines().count(), 13);
+        assert_eq!(picture.matches(['~', '|']).count(), 57);
+        assert_eq!(picture.matches('~').count(), 29);
+    }
+
     #[test]
     fn part1_result() {
-        let mut grid = Grid::from_input(&get_input());
+        let mut grid = Reservoir::from_input(&get_input()).unwrap();
         grid.flow();
         assert_eq!(grid.part1(), 34775);
     }
 
     #[test]
     fn part2_example() {
-        let mut grid = Grid::from_input(TEST_INPUT);
+        let mut grid = Reservoir::from_input(TEST_INPUT).unwrap();
         grid.flow();
         assert_eq!(grid.part2(), 29);
     }
 
     #[test]
     fn part2_result() {
-        let mut grid = Grid::from_input(&get_input());
+        let mut grid = Reservoir::from_input(&get_input()).unwrap();
         grid.flow();
         assert_eq!(grid.part2(), 27086);
     }
@@ -1,4 +1,8 @@
-use std::collections::HashMap;
+//! Day 18: Settlers of The North Pole.
+
+use crate::cycle::{self, Simulation};
+use crate::grid::Grid;
+use crate::solution::{ParseError, Solution};
 
 #[derive(Debug, Eq, PartialEq, Copy, Clone, Hash)]
 pub enum Acre {
@@ -7,142 +11,163 @@ pub enum Acre {
     Lumberyard,
 }
 
+#[cfg(any(test, feature = "embedded-inputs"))]
 pub fn get_input() -> String {
     include_str!("input.txt").to_owned()
 }
 
-pub fn parse_input(input: &str) -> Vec<Vec<Acre>> {
-    let mut result = vec![];
-    for line in input.lines() {
-        let mut row = vec![];
-        for c in line.chars() {
-            row.push(match c {
-                '.' => Acre::Open,
-                '|' => Acre::Trees,
-                '#' => Acre::Lumberyard,
-                _ => unreachable!(),
-            })
-        }
-        result.push(row);
-    }
-    result
+pub fn parse_input(input: &str) -> Result<Grid<Acre>, ParseError> {
+    Grid::parse(
+        Day18::DAY,
+        input,
+        "an acre (`.`, `|` or `#`)",
+        |c| match c {
+            '.' => Some(Acre::Open),
+            '|' => Some(Acre::Trees),
+            '#' => Some(Acre::Lumberyard),
+            _ => None,
+        },
+    )
 }
 
-fn count_acretype_around_point(area: &[Vec<Acre>], x: usize, y: usize, acretype: Acre) -> usize {
-    let mut count = 0;
-    let y = y as i32;
-    let x = x as i32;
-    for dy in y - 1..=y + 1 {
-        if dy < 0 || dy >= area.len() as i32 {
-            continue;
-        }
-        let old_row = &area[dy as usize][..];
-        for dx in x - 1..=x + 1 {
-            if x == dx && y == dy || dx < 0 || dx >= old_row.len() as i32 {
-                continue;
-            }
-            if old_row[dx as usize] == acretype {
-                count += 1;
-            }
-        }
-    }
-    count
+fn count_acretype_around_point(area: &Grid<Acre>, x: usize, y: usize, acretype: Acre) -> usize {
+    area.neighbours8(x, y)
+        .filter(|&pos| area[pos] == acretype)
+        .count()
 }
 
-pub fn tick(area: &[Vec<Acre>]) -> Vec<Vec<Acre>> {
-    let mut result = Vec::with_capacity(area.len());
-    for (y, old_row) in area.iter().enumerate() {
-        let mut row = Vec::with_capacity(old_row.len());
-        for (x, old_acre) in old_row.iter().enumerate() {
-            let lumberyard_count = count_acretype_around_point(&area, x, y, Acre::Lumberyard);
-            let tree_count = count_acretype_around_point(&area, x, y, Acre::Trees);
-            let new_acre = *match old_acre {
-                Acre::Open => {
-                    if tree_count >= 3 {
-                        &Acre::Trees
-                    } else {
-                        old_acre
-                    }
-                }
-                Acre::Trees => {
-                    if lumberyard_count >= 3 {
-                        &Acre::Lumberyard
-                    } else {
-                        old_acre
-                    }
-                }
-                Acre::Lumberyard => {
-                    if lumberyard_count >= 1 && tree_count >= 1 {
-                        old_acre
-                    } else {
-                        &Acre::Open
-                    }
-                }
-            };
-            row.push(new_acre);
-        }
-        result.push(row);
+pub fn tick(area: &Grid<Acre>) -> Grid<Acre> {
+    let mut result = area.clone();
+    for ((x, y), old_acre) in area.iter() {
+        let lumberyard_count = count_acretype_around_point(area, x, y, Acre::Lumberyard);
+        let tree_count = count_acretype_around_point(area, x, y, Acre::Trees);
+        result[(x, y)] = match old_acre {
+            Acre::Open if tree_count >= 3 => Acre::Trees,
+            Acre::Trees if lumberyard_count >= 3 => Acre::Lumberyard,
+            Acre::Lumberyard if lumberyard_count == 0 || tree_count == 0 => Acre::Open,
+            _ => *old_acre,
+        };
     }
     result
 }
 
-fn total_acretype_count(area: &[Vec<Acre>], acretype: Acre) -> usize {
-    area.iter()
-        .map(|row| row.iter().filter(|&e| e == &acretype).count())
-        .sum()
+fn total_acretype_count(area: &Grid<Acre>, acretype: Acre) -> usize {
+    area.cells().iter().filter(|&e| e == &acretype).count()
 }
 
-pub fn part1(area: &[Vec<Acre>]) -> usize {
-    let mut area = area.to_vec();
-    for _ in 0..10 {
-        area = tick(&area);
+impl Simulation for Grid<Acre> {
+    type Key = Grid<Acre>;
+
+    fn step(&mut self) {
+        *self = tick(self);
     }
+
+    fn key(&self) -> Grid<Acre> {
+        self.clone()
+    }
+}
+
+/// The resource value after some minutes.
+pub fn solve(area: &Grid<Acre>, minutes: u64) -> usize {
+    let area = cycle::state_at(area, minutes);
     total_acretype_count(&area, Acre::Trees) * total_acretype_count(&area, Acre::Lumberyard)
 }
 
-pub fn part2(area: &[Vec<Acre>]) -> usize {
-    let mut area = area.to_vec();
-    let mut minute = 1;
-    let mut seen = HashMap::new();
-    let cap = 1_000_000_000;
-    seen.insert(area.clone(), minute);
-    while minute <= cap {
-        // Tick the area and proceed to the next minute.
-        area = tick(&area);
-        minute += 1;
-
-        // Check to see if we've hit this area previously.
-        if seen.contains_key(&area) {
-            let cycle_start = seen[&area];
-            let delta = minute - cycle_start;
-            let remaining = cap - minute;
-            let cycle_count = remaining / delta;
-            // Automate as many cycles as possible.
-            minute += cycle_count * delta;
-        }
-        seen.insert(area.clone(), minute);
+pub fn part1(area: &Grid<Acre>) -> usize {
+    solve(area, 10)
+}
+
+pub fn part2(area: &Grid<Acre>) -> usize {
+    solve(area, 1_000_000_000)
+}
+
+pub struct Day18;
+
+impl Solution for Day18 {
+    const DAY: u8 = 18;
+
+    type Input = Grid<Acre>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_input(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Answer1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Answer2 {
+        part2(input)
+    }
+
+    fn render(input: &Self::Input) -> Option<String> {
+        let area = cycle::state_at(input, 10);
+        Some(area.render(|acre| match acre {
+            Acre::Open => '.',
+            Acre::Trees => '|',
+            Acre::Lumberyard => '#',
+        }))
     }
-    total_acretype_count(&area, Acre::Trees) * total_acretype_count(&area, Acre::Lumberyard)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
     const TEST_INPUT: &str = include_str!("test.txt");
 
     #[test]
     fn part1_examples() {
-        assert_eq!(part1(&parse_input(TEST_INPUT)), 1147);
+        assert_eq!(part1(&parse_input(TEST_INPUT).unwrap()), 1147);
+    }
+
+    #[test]
+    fn any_minute() {
+        let area = parse_input(TEST_INPUT).unwrap();
+        assert_eq!(solve(&area, 0), 27 * 17);
+        // The example ends up without any trees.
+        assert_eq!(solve(&area, 1_000), 0);
+    }
+
+    #[test]
+    fn invalid_acres() {
+        let err = parse_input(".#.\n.x.\n").unwrap_err();
+        assert_eq!((err.day, err.line, err.column), (18, 2, 2));
+        let err = parse_input(".#.\n..\n").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 3));
+        assert_eq!(err.expected, "a row of 3 cells");
     }
 
     #[test]
     fn part1_result() {
-        assert_eq!(part1(&parse_input(&get_input())), 394420);
+        assert_eq!(part1(&parse_input(&get_input()).unwrap()), 394420);
     }
 
     #[test]
     fn part2_result() {
-        assert_eq!(part2(&parse_input(&get_input())), 174420);
+        assert_eq!(part2(&parse_input(&get_input()).unwrap()), 174420);
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        #[test]
+        fn skipping_minutes(acres in prop::collection::vec(0..3usize, 36), minutes in 0..200u64) {
+            let input = acres
+                .chunks(6)
+                .map(|row| row.iter().map(|&a| ['.', '|', '#'][a]).collect::<String>() + "\n")
+                .collect::<String>();
+            let initial = parse_input(&input).unwrap();
+            let mut area = initial.clone();
+            for _ in 0..minutes {
+                area = tick(&area);
+            }
+            let value =
+                total_acretype_count(&area, Acre::Trees) * total_acretype_count(&area, Acre::Lumberyard);
+            prop_assert_eq!(solve(&initial, minutes), value);
+        }
     }
 }
@@ -1,103 +1,106 @@
-use super::day16::{exec, Inst};
-
-#[derive(Debug)]
-pub struct Instruction {
-    pub inst: Inst,
-    pub in1: usize,
-    pub in2: usize,
-    pub out: usize,
-}
+//! Day 19: Go With The Flow.
+
+use crate::elfcode::{self, Cpu};
+use crate::parse;
+use crate::solution::{ParseError, Solution};
+
+pub use crate::elfcode::Instruction;
 
+#[cfg(any(test, feature = "embedded-inputs"))]
 pub fn get_input() -> String {
     include_str!("input.txt").to_owned()
 }
 
-pub fn parse_input(input: &str) -> (usize, Vec<Instruction>) {
-    let mut lines = input.lines();
-    let ip = lines
-        .next()
-        .unwrap()
-        .split_whitespace()
-        .rev()
-        .next()
-        .unwrap()
-        .parse()
-        .unwrap();
-    let instructions = lines
-        .map(|line| {
-            let mut parts = line.split_whitespace();
-            Instruction {
-                inst: match parts.next().unwrap() {
-                    "addr" => Inst::Addr,
-                    "addi" => Inst::Addi,
-                    "mulr" => Inst::Mulr,
-                    "muli" => Inst::Muli,
-                    "banr" => Inst::Banr,
-                    "bani" => Inst::Bani,
-                    "borr" => Inst::Borr,
-                    "bori" => Inst::Bori,
-                    "setr" => Inst::Setr,
-                    "seti" => Inst::Seti,
-                    "gtir" => Inst::Gtir,
-                    "gtrr" => Inst::Gtrr,
-                    "eqir" => Inst::Eqir,
-                    "eqri" => Inst::Eqri,
-                    "eqrr" => Inst::Eqrr,
-                    _ => unreachable!(),
-                },
-                in1: parts.next().unwrap().parse().unwrap(),
-                in2: parts.next().unwrap().parse().unwrap(),
-                out: parts.next().unwrap().parse().unwrap(),
-            }
-        })
-        .collect();
-    (ip, instructions)
+/// Parses a program, returning the register bound to the instruction pointer and the instructions.
+///
+/// See [`elfcode::assemble`] for the syntax, the binding being required here.
+pub fn parse_input(input: &str) -> Result<(usize, Vec<Instruction>), ParseError> {
+    parse_program(Day19::DAY, input)
 }
 
-pub fn part1(ip: usize, instructions: &[Instruction]) -> usize {
-    let mut reg = vec![0; 6];
-    while reg[ip] < instructions.len() {
-        let inst = &instructions[reg[ip]];
-        exec(inst.inst, inst.in1, inst.in2, inst.out, &mut reg);
-        reg[ip] += 1;
+/// Parses a program for the given day, day 21 runs on the same device.
+pub fn parse_program(day: u8, input: &str) -> Result<(usize, Vec<Instruction>), ParseError> {
+    match elfcode::assemble(day, input)? {
+        (Some(ip), instructions) => Ok((ip, instructions)),
+        (None, _) => {
+            let expected = "the instruction pointer binding like `#ip 0`";
+            Err(parse::first_line(day, input, expected)?.error_at(1, expected))
+        }
     }
-    reg[0]
+}
+
+pub fn part1(ip: usize, instructions: &[Instruction]) -> usize {
+    let mut cpu = Cpu::new(Some(ip), instructions);
+    cpu.run();
+    cpu.registers()[0]
 }
 
 pub fn part2(ip: usize, instructions: &[Instruction]) -> usize {
-    let mut reg = vec![1, 0, 0, 0, 0, 0];
-    while reg[ip] != 1 {
-        let inst = &instructions[reg[ip]];
-        exec(inst.inst, inst.in1, inst.in2, inst.out, &mut reg);
-        reg[ip] += 1;
-    }
+    let mut cpu = Cpu::new(Some(ip), instructions).registers_set_to([1, 0, 0, 0, 0, 0]);
+    cpu.run_to(1);
     // Expect all the inner loops to do the same thing, sum() the difference in the outer loops and
     // return the sum.
-    let seed = *reg.iter().max().unwrap();
-    (1..=seed).filter(|e| seed % e == 0).sum()
+    let seed = *cpu.registers().iter().max().unwrap();
+    (1..=seed).filter(|e| seed.is_multiple_of(*e)).sum()
+}
+
+pub struct Day19;
+
+impl Solution for Day19 {
+    const DAY: u8 = 19;
+
+    type Input = (usize, Vec<Instruction>);
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_input(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Answer1 {
+        part1(input.0, &input.1)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Answer2 {
+        part2(input.0, &input.1)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::elfcode::Inst;
 
     const TEST_INPUT: &str = include_str!("test.txt");
 
     #[test]
     fn part1_examples() {
-        let (ip, instructions) = parse_input(TEST_INPUT);
+        let (ip, instructions) = parse_input(TEST_INPUT).unwrap();
         assert_eq!(part1(ip, &instructions), 7);
     }
 
+    #[test]
+    fn parse_programs() {
+        let (ip, instructions) =
+            parse_input("; counts down\n#ip 2\ngtri 0 1 1 ; r1 = r0 > 1\n").unwrap();
+        assert_eq!(ip, 2);
+        assert_eq!(instructions[0].inst, Inst::Gtri);
+
+        let err = parse_input("seti 5 0 1\n").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 1));
+        assert_eq!(err.expected, "the instruction pointer binding like `#ip 0`");
+        assert!(parse_input("").is_err());
+    }
+
     #[test]
     fn part1_result() {
-        let (ip, instructions) = parse_input(&get_input());
+        let (ip, instructions) = parse_input(&get_input()).unwrap();
         assert_eq!(part1(ip, &instructions), 912);
     }
 
     #[test]
     fn part2_result() {
-        let (ip, instructions) = parse_input(&get_input());
+        let (ip, instructions) = parse_input(&get_input()).unwrap();
         assert_eq!(part2(ip, &instructions), 10576224);
     }
 }
@@ -1,35 +1,53 @@
+//! Day 20: A Regular Map.
+
+use crate::geometry::{Direction, Vec2};
+use crate::parse;
+use crate::solution::{ParseError, Solution};
 use pathfinding::prelude::dijkstra_all;
 use std::collections::{BTreeMap, BTreeSet, HashMap};
 
-#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
-pub struct Point {
-    x: i32,
-    y: i32,
-}
-
-impl Point {
-    pub fn new(x: i32, y: i32) -> Point {
-        Point { x, y }
-    }
-}
+pub type Point = Vec2<i32>;
 
+#[cfg(any(test, feature = "embedded-inputs"))]
 pub fn get_input() -> String {
     include_str!("input.txt").to_owned()
 }
 
-pub fn parse_input(input: &str) -> HashMap<Point, (Point, i32)> {
+/// Parses the regex and explores the facility, returning the previous room and the number of
+/// doors on the shortest path to every room reachable from the starting point.
+pub fn parse_input(input: &str) -> Result<HashMap<Point, (Point, i32)>, ParseError> {
+    let line = parse::first_line(Day20::DAY, input, "a regex like `^N(E|W)$`")?;
+    let chars = validate(&line)?;
     let mut map = BTreeMap::new();
-    explore(
-        &mut map,
-        Point::new(0, 0),
-        &input.chars().collect::<Vec<_>>(),
-        &mut 1,
-    );
-    dijkstra_all(&Point::new(0, 0), |pos| {
+    explore(&mut map, Point::new(0, 0), &chars, &mut 1);
+    Ok(dijkstra_all(&Point::new(0, 0), |pos| {
         map.get(pos)
             .into_iter()
             .flat_map(|neighbours| neighbours.iter().map(|n| (*n, 1)))
-    })
+    }))
+}
+
+/// Checks that the regex is made of directions and balanced parentheses between `^` and `$`.
+fn validate(line: &parse::Line) -> Result<Vec<char>, ParseError> {
+    let chars = line.text.trim_end().chars().collect::<Vec<_>>();
+    if chars.first() != Some(&'^') {
+        return Err(line.error_at(1, "`^`"));
+    }
+    let mut depth = 0;
+    for (i, &c) in chars.iter().enumerate().skip(1) {
+        let column = i + 1;
+        match c {
+            'N' | 'E' | 'S' | 'W' => {}
+            '(' => depth += 1,
+            ')' if depth > 0 => depth -= 1,
+            '|' if depth > 0 => {}
+            '$' if depth == 0 && column == chars.len() => return Ok(chars),
+            '$' if depth > 0 => return Err(line.error_at(column, "`)`")),
+            '$' => return Err(line.error_at(column + 1, "the end of the line")),
+            _ => return Err(line.error_at(column, "a direction, `(`, `|`, `)` or `$`")),
+        }
+    }
+    Err(line.error_at(chars.len() + 1, "`$`"))
 }
 
 fn explore(
@@ -46,24 +64,20 @@ fn explore(
                 let mut new_exits = BTreeSet::new();
                 while input[*index] != ')' {
                     let old_index = *index;
-                    new_exits.extend(exits.iter().flat_map(|pos| {
+                    for pos in &exits {
                         *index = old_index + 1;
-                        explore(map, *pos, input, index)
-                    }));
+                        new_exits.extend(explore(map, *pos, input, index));
+                    }
                 }
                 exits = new_exits.into_iter().collect();
             }
             dir => {
-                let delta_pos = match dir {
-                    'N' => Point { x: 0, y: -1 },
-                    'E' => Point { x: 1, y: 0 },
-                    'S' => Point { x: 0, y: 1 },
-                    'W' => Point { x: -1, y: 0 },
-                    _ => unreachable!(),
-                };
+                let delta_pos = Direction::from_compass(dir)
+                    .expect("Validated while parsing")
+                    .delta();
                 for pos in &mut exits {
-                    let newpos = Point::new(pos.x + delta_pos.x, pos.y + delta_pos.y);
-                    map.entry(*pos).or_insert_with(BTreeSet::new).insert(newpos);
+                    let newpos = *pos + delta_pos;
+                    map.entry(*pos).or_default().insert(newpos);
                     *pos = newpos;
                 }
             }
@@ -80,39 +94,87 @@ pub fn part2(map: &HashMap<Point, (Point, i32)>) -> usize {
     map.values().filter(|&(_, c)| *c >= 1000).count()
 }
 
+pub struct Day20;
+
+impl Solution for Day20 {
+    const DAY: u8 = 20;
+
+    type Input = HashMap<Point, (Point, i32)>;
+    type Answer1 = i32;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_input(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Answer1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Answer2 {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::generate;
+    use proptest::prelude::*;
 
     #[test]
     fn part1_examples() {
-        assert_eq!(part1(&parse_input("^WNE$")), 3);
-        assert_eq!(part1(&parse_input("^ENWWW(NEEE|SSE(EE|N))$")), 10);
+        assert_eq!(part1(&parse_input("^WNE$").unwrap()), 3);
+        assert_eq!(part1(&parse_input("^ENWWW(NEEE|SSE(EE|N))$").unwrap()), 10);
         assert_eq!(
-            part1(&parse_input("^ENNWSWW(NEWS|)SSSEEN(WNSE|)EE(SWEN|)NNN$")),
+            part1(&parse_input("^ENNWSWW(NEWS|)SSSEEN(WNSE|)EE(SWEN|)NNN$").unwrap()),
             18
         );
         assert_eq!(
-            part1(&parse_input(
-                "^ESSWWN(E|NNENN(EESS(WNSE|)SSS|WWWSSSSE(SW|NNNE)))$"
-            )),
+            part1(&parse_input("^ESSWWN(E|NNENN(EESS(WNSE|)SSS|WWWSSSSE(SW|NNNE)))$").unwrap()),
             23
         );
         assert_eq!(
-            part1(&parse_input(
-                "^WSSEESWWWNW(S|NENNEEEENN(ESSSSW(NWSW|SSEN)|WSWWN(E|WWS(E|SS))))$"
-            )),
+            part1(
+                &parse_input("^WSSEESWWWNW(S|NENNEEEENN(ESSSSW(NWSW|SSEN)|WSWWN(E|WWS(E|SS))))$")
+                    .unwrap()
+            ),
             31
         );
     }
 
+    #[test]
+    fn invalid_regex() {
+        let column = |input| parse_input(input).unwrap_err().column;
+        assert_eq!(column("NE$"), 1);
+        assert_eq!(column("^N(E|W$"), 7);
+        assert_eq!(column("^N|E$"), 3);
+        assert_eq!(column("^NX$"), 3);
+        assert_eq!(column("^NE"), 4);
+        assert_eq!(column("^N$E"), 4);
+    }
+
     #[test]
     fn part1_result() {
-        assert_eq!(part1(&parse_input(&get_input())), 3966);
+        assert_eq!(part1(&parse_input(&get_input()).unwrap()), 3966);
     }
 
     #[test]
     fn part2_result() {
-        assert_eq!(part2(&parse_input(&get_input())), 8173);
+        assert_eq!(part2(&parse_input(&get_input()).unwrap()), 8173);
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        /// The generated mazes fill up a square, with a single route to every room.
+        #[test]
+        fn explore_mazes(seed in any::<u64>(), width in 2..15usize) {
+            let rooms = width * width;
+            let map = parse_input(&generate::generate(20, seed, Some(rooms)).unwrap()).unwrap();
+            prop_assert_eq!(map.len(), rooms - 1);
+            prop_assert!(part1(&map) >= width as i32 - 1);
+            prop_assert!(part1(&map) < rooms as i32);
+        }
     }
 }
@@ -1,61 +1,176 @@
-use super::day16::exec;
-use super::day19::Instruction;
-use std::collections::HashSet;
+//! Day 21: Chronal Conversion.
 
+use super::day19::{parse_program, Instruction};
+use crate::cycle::{self, Simulation};
+use crate::elfcode::{Cpu, Inst};
+use crate::parse;
+use crate::solution::{ParseError, Solution};
+
+#[cfg(any(test, feature = "embedded-inputs"))]
 pub fn get_input() -> String {
     include_str!("input.txt").to_owned()
 }
 
-pub fn part1(ipr: usize, instructions: &[Instruction]) -> usize {
-    let mut regs = [0; 6];
-    let mut ip = 0;
-    while ip < instructions.len() {
-        let inst = &instructions[ip];
-        exec(inst.inst, inst.in1, inst.in2, inst.out, &mut regs);
-        if ip == 28 {
-            return regs[3];
-        }
-        regs[ipr] += 1;
-        ip = regs[ipr] as usize;
-    }
-    unreachable!();
-}
-
-pub fn part2(ipr: usize, instructions: &[Instruction]) -> usize {
-    let mut regs = [0; 6];
-    let mut ip = 0;
-    let mut prev = 0;
-    let mut seen = HashSet::new();
-    while ip < instructions.len() {
-        let inst = &instructions[ip];
-        exec(inst.inst, inst.in1, inst.in2, inst.out, &mut regs);
-        if ip == 28 {
-            let r3 = regs[3];
-            if !seen.insert(r3) {
-                break;
-            }
-            prev = r3;
-        }
-        regs[ipr] += 1;
-        ip = regs[ipr] as usize;
+/// The program, with the comparison that decides whether it halts.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Program {
+    pub ip: usize,
+    pub instructions: Vec<Instruction>,
+    /// The instruction comparing a register against register 0, followed by a jump on the result.
+    pub check: usize,
+    /// The register compared against register 0.
+    pub compared: usize,
+}
+
+/// Parses the program, see [`day19::parse_input`](super::day19::parse_input), and finds the
+/// comparison against register 0 that halts it.
+pub fn parse_input(input: &str) -> Result<Program, ParseError> {
+    let (ip, instructions) = parse_program(Day21::DAY, input)?;
+    match halting_check(ip, &instructions) {
+        Some((check, compared)) => Ok(Program {
+            ip,
+            instructions,
+            check,
+            compared,
+        }),
+        None => Err(parse::unexpected_end(
+            Day21::DAY,
+            input,
+            "a comparison against register 0 like `eqrr 3 0 2`, followed by a jump like `addr 2 1 1`",
+        )),
+    }
+}
+
+/// Finds the instruction comparing a register against register 0 with `eqrr` or `gtrr`, followed
+/// by adding the result to the instruction pointer. Returns it with the compared register.
+fn halting_check(ip: usize, instructions: &[Instruction]) -> Option<(usize, usize)> {
+    instructions.windows(2).enumerate().find_map(|(i, pair)| {
+        let (compare, jump) = (pair[0], pair[1]);
+        let compared = match compare.inst {
+            Inst::Eqrr | Inst::Gtrr if compare.in1 == 0 && compare.in2 != 0 => compare.in2,
+            Inst::Eqrr | Inst::Gtrr if compare.in2 == 0 && compare.in1 != 0 => compare.in1,
+            _ => return None,
+        };
+        let operands = (jump.in1, jump.in2);
+        let jumps = jump.inst == Inst::Addr
+            && jump.out == ip
+            && (operands == (compare.out, ip) || operands == (ip, compare.out));
+        jumps.then_some((i, compared))
+    })
+}
+
+/// The program, stopped every time it compares a register against register 0 to halt.
+#[derive(Debug, Clone)]
+struct Halting<'a> {
+    cpu: Cpu<'a>,
+    check: usize,
+    compared: usize,
+}
+
+impl<'a> Halting<'a> {
+    fn new(program: &'a Program) -> Halting<'a> {
+        let mut halting = Halting {
+            cpu: Cpu::new(Some(program.ip), &program.instructions[..]),
+            check: program.check,
+            compared: program.compared,
+        };
+        halting.step();
+        halting
+    }
+}
+
+impl<'a> Simulation for Halting<'a> {
+    /// Once the value to compare repeats, the program loops forever.
+    type Key = usize;
+
+    fn step(&mut self) {
+        let compared = self.cpu.run_to(self.check);
+        assert!(
+            compared,
+            "The program halted without comparing against register 0"
+        );
+        self.cpu.step();
+    }
+
+    fn key(&self) -> usize {
+        self.cpu.registers()[self.compared]
+    }
+}
+
+pub fn part1(program: &Program) -> usize {
+    Halting::new(program).key()
+}
+
+pub fn part2(program: &Program) -> usize {
+    let (_, keys) = cycle::find(&Halting::new(program));
+    *keys.last().unwrap()
+}
+
+pub struct Day21;
+
+impl Solution for Day21 {
+    const DAY: u8 = 21;
+
+    type Input = Program;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_input(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Answer1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Answer2 {
+        part2(input)
     }
-    prev
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::day19::parse_input;
+
+    /// Counts register 3 through the multiples of 7 modulo 16, comparing 16 more than it against
+    /// register 0. Reads register 0 before the comparison as well.
+    const COUNTER: &str = "#ip 1
+setr 0 0 4
+addi 3 7 3
+bani 3 15 3
+addi 3 16 5
+eqrr 5 0 2
+addr 2 1 1
+seti 0 0 1
+";
+
+    #[test]
+    fn find_the_halting_check() {
+        let program = parse_input(COUNTER).unwrap();
+        assert_eq!((program.check, program.compared), (4, 5));
+        assert_eq!(part1(&program), 23);
+        assert_eq!(part2(&program), 16);
+
+        let program = parse_input(&get_input()).unwrap();
+        assert_eq!((program.check, program.compared), (28, 3));
+
+        // Comparing against an immediate 0, or not jumping on the result, isn't a halting check.
+        for (from, to) in &[("eqrr 5 0 2", "eqri 5 0 2"), ("addr 2 1 1", "addr 2 3 1")] {
+            let err = parse_input(&COUNTER.replace(from, to)).unwrap_err();
+            assert_eq!(err.line, 8);
+            assert!(err.expected.contains("eqrr 3 0 2"));
+        }
+    }
 
     #[test]
     fn part1_result() {
-        let (ip, insts) = parse_input(&get_input());
-        assert_eq!(part1(ip, &insts), 13_270_004);
+        let program = parse_input(&get_input()).unwrap();
+        assert_eq!(part1(&program), 13_270_004);
     }
 
     #[test]
     fn part2_result() {
-        let (ip, insts) = parse_input(&get_input());
-        assert_eq!(part2(ip, &insts), 12_879_142);
+        let program = parse_input(&get_input()).unwrap();
+        assert_eq!(part2(&program), 12_879_142);
     }
 }
@@ -1,16 +1,54 @@
-use pathfinding::prelude::{absdiff, astar, Matrix};
+//! Day 22: Mode Maze.
 
+use crate::grid::Grid;
+use crate::parse;
+use crate::solution::{ParseError, Solution};
+use pathfinding::prelude::{absdiff, astar};
+
+#[cfg(any(test, feature = "embedded-inputs"))]
 pub fn get_input() -> String {
     include_str!("input.txt").to_owned()
 }
 
-pub fn parse_input(input: &str) -> (usize, (usize, usize)) {
-    let mut lines = input.lines();
-    let depth = lines.next().unwrap()[7..].parse::<usize>().unwrap();
-    let mut target = lines.next().unwrap()[8..]
-        .split(',')
-        .map(|n| n.parse::<usize>().unwrap());
-    (depth, (target.next().unwrap(), target.next().unwrap()))
+/// The largest area up to the target accepted, part 2 maps 49 times that area.
+pub const MAX_AREA: usize = 100_000;
+
+/// The deepest cave accepted.
+pub const MAX_DEPTH: usize = 1_000_000;
+
+pub fn parse_input(input: &str) -> Result<(usize, (usize, usize)), ParseError> {
+    let mut lines = parse::lines(Day22::DAY, input);
+    let line = lines
+        .next()
+        .ok_or_else(|| parse::unexpected_end(Day22::DAY, input, "`depth: N`"))?;
+    let depth = match line.text.strip_prefix("depth: ") {
+        Some(text) => match line.parse(text, "a depth")? {
+            depth if depth > MAX_DEPTH => {
+                return Err(line.error(text, format!("a depth of at most {}", MAX_DEPTH)))
+            }
+            depth => depth,
+        },
+        None => return Err(line.error_at(1, "`depth: N`")),
+    };
+    let line = lines
+        .next()
+        .ok_or_else(|| parse::unexpected_end(Day22::DAY, input, "`target: X,Y`"))?;
+    let (x, y) = match line
+        .text
+        .strip_prefix("target: ")
+        .map(|t| t.split_once(','))
+    {
+        Some(Some(target)) => target,
+        _ => return Err(line.error_at(1, "`target: X,Y`")),
+    };
+    let target: (usize, usize) = (
+        line.parse(x, "a coordinate")?,
+        line.parse(y, "a coordinate")?,
+    );
+    match (target.0.saturating_add(1)).checked_mul(target.1.saturating_add(1)) {
+        Some(area) if area <= MAX_AREA => Ok((depth, target)),
+        _ => Err(line.error(x, format!("a target within an area of {}", MAX_AREA))),
+    }
 }
 
 fn calculate_levels(
@@ -18,8 +56,8 @@ fn calculate_levels(
     max_y: usize,
     depth: usize,
     (tx, ty): (usize, usize),
-) -> Matrix<usize> {
-    let mut el = Matrix::new(max_x + 1, max_y + 1, 0);
+) -> Grid<usize> {
+    let mut el = Grid::new(max_x + 1, max_y + 1, 0);
     for x in 0..=max_x {
         for y in 0..=max_y {
             let index = if (x == 0 && y == 0) || (x == tx && y == ty) {
@@ -29,19 +67,18 @@ fn calculate_levels(
             } else if x == 0 {
                 y * 48271
             } else {
-                el[&(x - 1, y)] * el[&(x, y - 1)]
+                el[(x - 1, y)] * el[(x, y - 1)]
             };
-            el[&(x, y)] = (index + depth) % 20183;
+            el[(x, y)] = (index + depth) % 20183;
         }
     }
-    el.as_mut().iter_mut().for_each(|n| *n %= 3);
-    el
+    el.map(|n| n % 3)
 }
 
 pub fn part1(depth: usize, (tx, ty): (usize, usize)) -> usize {
     calculate_levels(tx, ty, depth, (tx, ty))
-        .to_vec()
-        .into_iter()
+        .cells()
+        .iter()
         .sum()
 }
 
@@ -54,10 +91,10 @@ pub fn part2(depth: usize, (tx, ty): (usize, usize)) -> usize {
     let (_, cost) = astar(
         &((0, 0), torch),
         |&((x, y), eq)| {
-            map.neighbours(&(x, y), false)
-                .filter(|&(nx, ny)| allowed[map[&(nx, ny)]] & eq == eq)
-                .map(|(nx, ny)| (((nx, ny), eq), 1))
-                .chain(std::iter::once((((x, y), allowed[map[&(x, y)]] - eq), 7)))
+            map.neighbours4(x, y)
+                .filter(|&pos| allowed[map[pos]] & eq == eq)
+                .map(|pos| ((pos, eq), 1))
+                .chain(std::iter::once((((x, y), allowed[map[(x, y)]] - eq), 7)))
                 .collect::<Vec<_>>()
         },
         |&((x, y), _)| absdiff(x, tx) + absdiff(y, ty),
@@ -67,19 +104,59 @@ pub fn part2(depth: usize, (tx, ty): (usize, usize)) -> usize {
     cost
 }
 
+pub struct Day22;
+
+impl Solution for Day22 {
+    const DAY: u8 = 22;
+
+    type Input = (usize, (usize, usize));
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_input(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Answer1 {
+        part1(input.0, input.1)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Answer2 {
+        part2(input.0, input.1)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            parse_input("depth: 510\ntarget: 9,9999"),
+            Ok((510, (9, 9999)))
+        );
+        let error = parse_input("depth: 510\ntarget: 10,9999").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 9));
+        assert_eq!(error.expected, "a target within an area of 100000");
+        let error = parse_input("depth: 510\ntarget: 18446744073709551615,1").unwrap_err();
+        assert_eq!(error.expected, "a target within an area of 100000");
+        let error = parse_input("depth: 1000001\ntarget: 10,10").unwrap_err();
+        assert_eq!(
+            (error.column, error.expected.as_str()),
+            (8, "a depth of at most 1000000")
+        );
+    }
+
     #[test]
     fn part1_results() {
-        let (depth, target) = parse_input(&get_input());
+        let (depth, target) = parse_input(&get_input()).unwrap();
         assert_eq!(part1(depth, target), 11810);
     }
 
     #[test]
     fn part2_results() {
-        let (depth, target) = parse_input(&get_input());
+        let (depth, target) = parse_input(&get_input()).unwrap();
         assert_eq!(part2(depth, target), 1015);
     }
 }
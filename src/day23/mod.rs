@@ -1,21 +1,11 @@
-use std::collections::BTreeMap;
+//! Day 23: Experimental Emergency Teleportation.
 
-#[derive(Debug, Eq, PartialEq)]
-pub struct Position {
-    x: i64,
-    y: i64,
-    z: i64,
-}
-
-impl Position {
-    pub fn new(x: i64, y: i64, z: i64) -> Position {
-        Position { x, y, z }
-    }
+use crate::geometry::Vec3;
+use crate::parse;
+use crate::solution::{ParseError, Solution};
+use std::collections::BTreeMap;
 
-    pub fn distance_to(&self, other_pos: &Position) -> i64 {
-        (self.x - other_pos.x).abs() + (self.y - other_pos.y).abs() + (self.z - other_pos.z).abs()
-    }
-}
+pub type Position = Vec3<i64>;
 
 #[derive(Debug, Eq, PartialEq)]
 pub struct Nanobot {
@@ -29,28 +19,25 @@ impl Nanobot {
     }
 
     pub fn in_range(&self, other_bot: &Nanobot) -> bool {
-        self.radius >= self.pos.distance_to(&other_bot.pos)
+        self.radius >= self.pos.manhattan(&other_bot.pos)
     }
 }
 
+#[cfg(any(test, feature = "embedded-inputs"))]
 pub fn get_input() -> String {
     include_str!("input.txt").to_owned()
 }
 
-pub fn parse_input(input: &str) -> Vec<Nanobot> {
-    let m_nanobot = regex::Regex::new(r"pos=<(-?\d+),(-?\d+),(-?\d+)>, r=(\d+)").unwrap();
-    input
-        .lines()
+pub fn parse_input(input: &str) -> Result<Vec<Nanobot>, ParseError> {
+    let m_nanobot = regex::Regex::new(r"^pos=<(-?\d+),(-?\d+),(-?\d+)>, r=(\d+)$").unwrap();
+    parse::lines(Day23::DAY, input)
         .map(|line| {
-            let captures = m_nanobot.captures(line).unwrap();
-            Nanobot::new(
-                Position::new(
-                    captures[1].parse().unwrap(),
-                    captures[2].parse().unwrap(),
-                    captures[3].parse().unwrap(),
-                ),
-                captures[4].parse().unwrap(),
-            )
+            let captures = line.captures(&m_nanobot, "a nanobot like `pos=<0,0,0>, r=4`")?;
+            let number = |i| line.parse(&captures[i], "a number");
+            Ok(Nanobot::new(
+                Position::new(number(1)?, number(2)?, number(3)?),
+                number(4)?,
+            ))
         })
         .collect()
 }
@@ -73,9 +60,9 @@ pub fn part2(nanobots: &[Nanobot]) -> i64 {
     // of insertion is preserved when iterating.
     let mut map = BTreeMap::new();
     for b in nanobots {
-        *map.entry(b.pos.x + b.pos.y + b.pos.z - b.radius)
+        *map.entry(b.pos.x() + b.pos.y() + b.pos.z() - b.radius)
             .or_insert(0) += 1;
-        *map.entry(b.pos.x + b.pos.y + b.pos.z + b.radius + 1)
+        *map.entry(b.pos.x() + b.pos.y() + b.pos.z() + b.radius + 1)
             .or_insert(0) -= 1;
     }
     let mut running = 0;
@@ -88,7 +75,29 @@ pub fn part2(nanobots: &[Nanobot]) -> i64 {
             max_start = pos;
         }
     });
-    *map.keys().skip_while(|&&v| v <= max_start).next().unwrap() - 1
+    *map.keys().find(|&&v| v > max_start).unwrap() - 1
+}
+
+pub struct Day23;
+
+impl Solution for Day23 {
+    const DAY: u8 = 23;
+
+    type Input = Vec<Nanobot>;
+    type Answer1 = usize;
+    type Answer2 = i64;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_input(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Answer1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Answer2 {
+        part2(input)
+    }
 }
 
 #[cfg(test)]
@@ -100,25 +109,25 @@ mod tests {
 
     #[test]
     fn part1_examples() {
-        let nanobots = parse_input(TEST_INPUT);
+        let nanobots = parse_input(TEST_INPUT).unwrap();
         assert_eq!(part1(&nanobots), 7);
     }
 
     #[test]
     fn part1_result() {
-        let nanobots = parse_input(&get_input());
+        let nanobots = parse_input(&get_input()).unwrap();
         assert_eq!(part1(&nanobots), 326);
     }
 
     #[test]
     fn part2_examples() {
-        let nanobots = parse_input(TEST_INPUT2);
+        let nanobots = parse_input(TEST_INPUT2).unwrap();
         assert_eq!(part2(&nanobots), 36);
     }
 
     #[test]
     fn part2_result() {
-        let nanobots = parse_input(TEST_INPUT2);
+        let nanobots = parse_input(TEST_INPUT2).unwrap();
         assert_eq!(part2(&nanobots), 36);
     }
 }
@@ -1,3 +1,7 @@
+//! Day 24: Immune System Simulator 20XX.
+
+use crate::parse;
+use crate::solution::{ParseError, Solution};
 use std::cmp::Reverse;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -55,42 +59,50 @@ impl Army {
     }
 }
 
-fn parse_dmg_type(input: &str) -> DmgType {
-    match input.to_ascii_lowercase().as_ref() {
+fn parse_dmg_type(line: &parse::Line, input: &str) -> Result<DmgType, ParseError> {
+    Ok(match input.to_ascii_lowercase().as_ref() {
         "cold" => DmgType::Cold,
         "fire" => DmgType::Fire,
         "radiation" => DmgType::Radiation,
         "slashing" => DmgType::Slashing,
         "bludgeoning" => DmgType::Bludgeoning,
-        _ => unreachable!("input: {}", input),
-    }
+        _ => return Err(line.error(input, "a damage type")),
+    })
 }
 
+#[cfg(any(test, feature = "embedded-inputs"))]
 pub fn get_input() -> String {
     include_str!("input.txt").to_owned()
 }
 
-pub fn parse_input(input: &str) -> Vec<Army> {
-    let m_army = regex::Regex::new(r"(\d+) units each with (\d+) hit points \(?(.*?)\)? ?with an attack that does (\d+) (.*?) damage at initiative (\d+)").unwrap();
+pub fn parse_input(input: &str) -> Result<Vec<Army>, ParseError> {
+    let m_army = regex::Regex::new(r"^(\d+) units each with (\d+) hit points \(?(.*?)\)? ?with an attack that does (\d+) (.*?) damage at initiative (\d+)$").unwrap();
     let mut result = vec![];
-    let mut team = Team::Immune;
-    for line in input.lines() {
-        if line.is_empty() {
+    let mut team = None;
+    for line in parse::lines(Day24::DAY, input) {
+        if line.text.is_empty() {
             continue;
         }
 
-        if line.ends_with(":") {
-            team = match line {
-                "Immune System:" => Team::Immune,
-                "Infection:" => Team::Infection,
-                _ => unreachable!(line),
+        if line.text.ends_with(':') {
+            team = match line.text {
+                "Immune System:" => Some(Team::Immune),
+                "Infection:" => Some(Team::Infection),
+                _ => return Err(line.error_at(1, "`Immune System:` or `Infection:`")),
             };
             continue;
         }
+        let team = team.ok_or_else(|| line.error_at(1, "a team like `Immune System:`"))?;
 
-        let captures = m_army.captures(line).unwrap();
-        let modifiers = captures[3]
-            .split(|e| e == ',' || e == ';')
+        let captures = line.captures(
+            &m_army,
+            "a group like `17 units each with 5390 hit points with an attack that does 4507 fire damage at initiative 2`",
+        )?;
+        let modifiers = captures
+            .get(3)
+            .unwrap()
+            .as_str()
+            .split([',', ';'])
             .map(|e| e.trim());
         let mut weaknesses = vec![];
         let mut immunities = vec![];
@@ -101,11 +113,11 @@ pub fn parse_input(input: &str) -> Vec<Army> {
                 parsing_immunities = match elems[0] {
                     "weak" => false,
                     "immune" => true,
-                    _ => unreachable!(),
+                    _ => return Err(line.error(elems[0], "`weak` or `immune`")),
                 }
             }
-            if !elems.is_empty() {
-                let dmg_type = parse_dmg_type(elems[elems.len() - 1]);
+            if let Some(last) = elems.last() {
+                let dmg_type = parse_dmg_type(&line, last)?;
                 if parsing_immunities {
                     immunities.push(dmg_type);
                 } else {
@@ -113,19 +125,23 @@ pub fn parse_input(input: &str) -> Vec<Army> {
                 }
             }
         }
+        if weaknesses.len() > 4 || immunities.len() > 4 {
+            return Err(line.error(&captures[3], "at most 4 weaknesses and immunities"));
+        }
 
+        let number = |i| line.parse(captures.get(i).unwrap().as_str(), "a number");
         result.push(Army {
             team,
-            units: captures[1].parse().unwrap(),
-            hp: captures[2].parse().unwrap(),
+            units: number(1)?,
+            hp: number(2)?,
             weak: vec_to_arr(&weaknesses),
             immune: vec_to_arr(&immunities),
-            ap: captures[4].parse().unwrap(),
-            att_type: parse_dmg_type(&captures[5]),
-            initiative: captures[6].parse().unwrap(),
+            ap: number(4)?,
+            att_type: parse_dmg_type(&line, captures.get(5).unwrap().as_str())?,
+            initiative: number(6)?,
         })
     }
-    result
+    Ok(result)
 }
 
 /// Converts a slice of DmgType to an array with 4 DmgTypes, filled with None's at the end.
@@ -201,33 +217,64 @@ pub fn part1(input: &[Army]) -> isize {
     part1
 }
 
+/// Gives the immune system a boost and lets the armies battle it out.
+fn boosted_battle(input: &[Army], boost: isize) -> (Option<Team>, isize) {
+    let mut armies = input.to_vec();
+    armies
+        .iter_mut()
+        .filter(|u| u.team == Team::Immune)
+        .for_each(|u| u.ap += boost);
+    battle(&armies)
+}
+
+/// Whether the immune system wins with the given boost.
+pub fn immune_wins(input: &[Army], boost: isize) -> bool {
+    boosted_battle(input, boost).0 == Some(Team::Immune)
+}
+
 pub fn part2(input: &[Army]) -> isize {
-    let armies = input.to_vec();
     (1..)
-        .filter_map(|b| {
-            let mut armies = armies.clone();
-            armies
-                .iter_mut()
-                .filter(|u| u.team == Team::Immune)
-                .for_each(|u| u.ap += b);
-            match battle(&armies) {
-                (Some(Team::Immune), rem) => Some(rem),
-                _ => None,
-            }
+        .filter_map(|b| match boosted_battle(input, b) {
+            (Some(Team::Immune), rem) => Some(rem),
+            _ => None,
         })
         .next()
         .unwrap()
 }
 
+pub struct Day24;
+
+impl Solution for Day24 {
+    const DAY: u8 = 24;
+
+    type Input = Vec<Army>;
+    type Answer1 = isize;
+    type Answer2 = isize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_input(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Answer1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Answer2 {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::generate;
+    use proptest::prelude::*;
 
     const TEST_INPUT: &str = include_str!("test.txt");
 
     #[test]
     fn test_parse() {
-        let input = parse_input(TEST_INPUT);
+        let input = parse_input(TEST_INPUT).unwrap();
         assert_eq!(input.len(), 4);
         assert_eq!(
             input[0],
@@ -235,8 +282,8 @@ mod tests {
                 team: Team::Immune,
                 units: 17,
                 hp: 5390,
-                weak: vec_to_arr(&vec![DmgType::Radiation, DmgType::Bludgeoning]),
-                immune: vec_to_arr(&vec![]),
+                weak: vec_to_arr(&[DmgType::Radiation, DmgType::Bludgeoning]),
+                immune: vec_to_arr(&[]),
                 ap: 4507,
                 att_type: DmgType::Fire,
                 initiative: 2,
@@ -248,8 +295,8 @@ mod tests {
                 team: Team::Immune,
                 units: 989,
                 hp: 1274,
-                weak: vec_to_arr(&vec![DmgType::Bludgeoning, DmgType::Slashing]),
-                immune: vec_to_arr(&vec![DmgType::Fire]),
+                weak: vec_to_arr(&[DmgType::Bludgeoning, DmgType::Slashing]),
+                immune: vec_to_arr(&[DmgType::Fire]),
                 ap: 25,
                 att_type: DmgType::Slashing,
                 initiative: 3,
@@ -261,8 +308,8 @@ mod tests {
                 team: Team::Infection,
                 units: 801,
                 hp: 4706,
-                weak: vec_to_arr(&vec![DmgType::Radiation]),
-                immune: vec_to_arr(&vec![]),
+                weak: vec_to_arr(&[DmgType::Radiation]),
+                immune: vec_to_arr(&[]),
                 ap: 116,
                 att_type: DmgType::Bludgeoning,
                 initiative: 1,
@@ -270,27 +317,50 @@ mod tests {
         );
     }
 
+    #[test]
+    fn invalid_groups() {
+        let input = "Immune System:\n1 units each with 2 hit points (weak to lava) with an attack that does 3 fire damage at initiative 1";
+        let err = parse_input(input).unwrap_err();
+        assert_eq!((err.day, err.line, err.column), (24, 2, 41));
+        assert_eq!(parse_input("Bystanders:").unwrap_err().column, 1);
+        assert_eq!(parse_input(&input[15..]).unwrap_err().line, 1);
+    }
+
     #[test]
     fn part1_examples() {
-        let input = parse_input(TEST_INPUT);
+        let input = parse_input(TEST_INPUT).unwrap();
         assert_eq!(part1(&input), 5216);
     }
 
     #[test]
     fn part1_result() {
-        let input = parse_input(&get_input());
+        let input = parse_input(&get_input()).unwrap();
         assert_eq!(part1(&input), 14799);
     }
 
     #[test]
     fn part2_examples() {
-        let input = parse_input(TEST_INPUT);
+        let input = parse_input(TEST_INPUT).unwrap();
         assert_eq!(part2(&input), 51);
     }
 
     #[test]
     fn part2_result() {
-        let input = parse_input(&get_input());
+        let input = parse_input(&get_input()).unwrap();
         assert_eq!(part2(&input), 4428);
     }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(16))]
+
+        #[test]
+        fn generated_armies(seed in any::<u64>(), size in 1..8usize) {
+            let armies = parse_input(&generate::generate(24, seed, Some(size)).unwrap()).unwrap();
+            let immune = armies.iter().filter(|a| a.team == Team::Immune).count();
+            prop_assert_eq!((immune, armies.len()), (size, 2 * size));
+            let units = armies.iter().map(|a| a.units).sum::<isize>();
+            prop_assert!(part1(&armies) <= units);
+            prop_assert!(immune_wins(&armies, 1000));
+        }
+    }
 }
@@ -1,23 +1,29 @@
+//! Day 25: Four-Dimensional Adventure.
+
+use crate::geometry::Vec4;
+use crate::parse;
+use crate::solution::{ParseError, Solution};
 use std::collections::HashSet;
 
+#[cfg(any(test, feature = "embedded-inputs"))]
 pub fn get_input() -> String {
     include_str!("input.txt").to_owned()
 }
 
-type Input = [(i64, i64, i64, i64, i64)];
+/// A point and the id of its constellation.
+type Point = (Vec4<i64>, usize);
+
+type Input = [Point];
 
-pub fn parse_input(input: &str) -> Vec<(i64, i64, i64, i64, i64)> {
+pub fn parse_input(input: &str) -> Result<Vec<Point>, ParseError> {
     let mut points = vec![];
-    for (i, line) in input.lines().enumerate() {
-        let el: Vec<i64> = line.split(',').filter_map(|x| x.parse().ok()).collect();
-        // (a, b, c, d, constellation_id)
-        points.push((el[0], el[1], el[2], el[3], i as i64));
+    for (i, line) in parse::lines(Day25::DAY, input).enumerate() {
+        let [a, b, c, d] = line.fields(",", "four numbers like `0,-1,2,3`")?;
+        let number = |n| line.parse(n, "a number");
+        let point = Vec4::new(number(a)?, number(b)?, number(c)?, number(d)?);
+        points.push((point, i));
     }
-    points
-}
-
-fn dis(a: (i64, i64, i64, i64, i64), b: (i64, i64, i64, i64, i64)) -> i64 {
-    (a.0 - b.0).abs() + (a.1 - b.1).abs() + (a.2 - b.2).abs() + (a.3 - b.3).abs()
+    Ok(points)
 }
 
 pub fn part1(input: &Input) -> usize {
@@ -27,34 +33,78 @@ pub fn part1(input: &Input) -> usize {
             let p0 = input[i];
             let p1 = input[j];
 
-            if dis(p0, p1) <= 3 && p0.4 != p1.4 {
+            if p0.0.manhattan(&p1.0) <= 3 && p0.1 != p1.1 {
                 // merge constellations
                 for e in input.iter_mut() {
-                    if e.4 == p1.4 {
-                        e.4 = p0.4;
+                    if e.1 == p1.1 {
+                        e.1 = p0.1;
                     }
                 }
             }
         }
     }
 
-    let part1: HashSet<_> = input.iter().map(|x| x.4).collect();
+    let part1: HashSet<_> = input.iter().map(|x| x.1).collect();
     part1.len()
 }
 
+pub struct Day25;
+
+impl Solution for Day25 {
+    const DAY: u8 = 25;
+    const PARTS: u8 = 1;
+
+    type Input = Vec<Point>;
+    type Answer1 = usize;
+    type Answer2 = &'static str;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        parse_input(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Answer1 {
+        part1(input)
+    }
+
+    /// There's no second puzzle on the last day.
+    fn part2(_input: &Self::Input) -> Self::Answer2 {
+        "Merry Christmas!"
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
     const TEST_INPUT: &str = include_str!("test.txt");
 
     #[test]
     fn part1_examples() {
-        assert_eq!(part1(&parse_input(TEST_INPUT)), 2);
+        assert_eq!(part1(&parse_input(TEST_INPUT).unwrap()), 2);
     }
 
     #[test]
     fn part1_result() {
-        assert_eq!(part1(&parse_input(&get_input())), 331);
+        assert_eq!(part1(&parse_input(&get_input()).unwrap()), 331);
+    }
+
+    fn render(points: &[[i64; 4]]) -> String {
+        points
+            .iter()
+            .map(|p| format!("{},{},{},{}\n", p[0], p[1], p[2], p[3]))
+            .collect()
+    }
+
+    proptest! {
+        #[test]
+        fn shuffled_constellations(
+            (points, shuffled) in prop::collection::vec(prop::array::uniform4(-4..=4i64), 1..60)
+                .prop_flat_map(|points| (Just(points.clone()), Just(points).prop_shuffle()))
+        ) {
+            let count = part1(&parse_input(&render(&points)).unwrap());
+            prop_assert!(count >= 1 && count <= points.len());
+            prop_assert_eq!(part1(&parse_input(&render(&shuffled)).unwrap()), count);
+        }
     }
 }
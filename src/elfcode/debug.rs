@@ -0,0 +1,456 @@
+//! An interactive debugger for ElfCode programs, stepping through them on a [`Cpu`].
+//!
+//! Breakpoints stop before running the instruction at an instruction pointer, watchpoints after
+//! an instruction changed a register (to a given value). See [`HELP`] for the commands.
+
+use super::decompile;
+use super::{Cpu, Instruction, REGISTERS};
+use crate::parse::parse_number;
+use std::collections::BTreeSet;
+use std::io::{self, BufRead, Write};
+
+pub const HELP: &str = "Commands:
+    step [N], s [N]  Run the next N instructions, 1 by default.
+    continue, c      Run until a breakpoint or watchpoint is hit, or the program halts.
+    break <IP>, b <IP>
+                     Stop before running the instruction at IP.
+    delete <IP>      Remove the breakpoint at IP.
+    watch <REG> [VALUE], w <REG> [VALUE]
+                     Stop once the register changes, or changes to VALUE.
+    unwatch <REG>    Remove the watchpoints on the register.
+    info             List the breakpoints and watchpoints.
+    registers, r     Show the registers.
+    set <REG> <VALUE>
+                     Change a register, ie. `set r0 1`.
+    counts           Show how many times every instruction ran.
+    list, l          Show the instructions around the instruction pointer.
+    reset            Start the program over, keeping the breakpoints and watchpoints.
+    help, h          Print this help.
+    quit, q          Leave the debugger.
+
+Registers are written as `r3` or `3`, and `ip` is the register bound to the instruction pointer.
+An empty line repeats the previous command.";
+
+/// A command of the debugger.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Command {
+    Step(u64),
+    Continue,
+    Break(usize),
+    Delete(usize),
+    Watch(Watchpoint),
+    Unwatch(usize),
+    Info,
+    Registers,
+    Set(usize, usize),
+    Counts,
+    List,
+    Reset,
+    Help,
+    Quit,
+}
+
+/// Stops once an instruction changes the register, to `value` if given.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Watchpoint {
+    pub register: usize,
+    pub value: Option<usize>,
+}
+
+impl Watchpoint {
+    fn hit(&self, before: &[usize; REGISTERS], after: &[usize; REGISTERS]) -> bool {
+        let (before, after) = (before[self.register], after[self.register]);
+        before != after && self.value.is_none_or(|value| value == after)
+    }
+}
+
+/// Parses a register like `r3`, `3` or `ip`.
+fn parse_register(text: &str, binding: Option<usize>) -> Result<usize, String> {
+    if text == "ip" {
+        return binding
+            .ok_or_else(|| "The instruction pointer isn't bound to a register".to_owned());
+    }
+    match text.strip_prefix('r').unwrap_or(text).parse() {
+        Ok(register) if register < REGISTERS => Ok(register),
+        _ => Err(format!("Invalid register: {}, expected r0-r5 or ip", text)),
+    }
+}
+
+impl Command {
+    /// Parses a command, `binding` being the register bound to the instruction pointer.
+    pub fn parse(line: &str, binding: Option<usize>) -> Result<Command, String> {
+        let words = line.split_whitespace().collect::<Vec<_>>();
+        let register = |text| parse_register(text, binding);
+        Ok(match words.as_slice() {
+            ["step" | "s"] => Command::Step(1),
+            [command @ ("step" | "s"), n] => Command::Step(parse_number(command, n)?),
+            ["continue" | "c"] => Command::Continue,
+            [command @ ("break" | "b"), ip] => Command::Break(parse_number(command, ip)?),
+            ["delete", ip] => Command::Delete(parse_number("delete", ip)?),
+            ["watch" | "w", reg] => Command::Watch(Watchpoint {
+                register: register(reg)?,
+                value: None,
+            }),
+            [command @ ("watch" | "w"), reg, value] => Command::Watch(Watchpoint {
+                register: register(reg)?,
+                value: Some(parse_number(command, value)?),
+            }),
+            ["unwatch", reg] => Command::Unwatch(register(reg)?),
+            ["info"] => Command::Info,
+            ["registers" | "r"] => Command::Registers,
+            ["set", reg, value] => Command::Set(register(reg)?, parse_number("set", value)?),
+            ["counts"] => Command::Counts,
+            ["list" | "l"] => Command::List,
+            ["reset"] => Command::Reset,
+            ["help" | "h"] => Command::Help,
+            ["quit" | "q"] => Command::Quit,
+            _ => return Err(format!("Unknown command: {}, see `help`", line.trim())),
+        })
+    }
+}
+
+/// Why running the program stopped.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Stop {
+    /// Ran all the steps asked for.
+    Stepped,
+    /// About to run the instruction with a breakpoint.
+    Breakpoint(usize),
+    /// The last instruction hit the watchpoint.
+    Watchpoint(Watchpoint),
+    /// The instruction pointer left the program.
+    Halted,
+}
+
+/// A program being debugged.
+pub struct Debugger<'a> {
+    initial: Cpu<'a>,
+    cpu: Cpu<'a>,
+    comments: Vec<String>,
+    breakpoints: BTreeSet<usize>,
+    watchpoints: Vec<Watchpoint>,
+    /// The number of times every instruction ran.
+    counts: Vec<u64>,
+}
+
+impl<'a> Debugger<'a> {
+    /// Debugs the program on the given device, usually at the start of the program.
+    pub fn new(cpu: Cpu<'a>) -> Debugger<'a> {
+        let comments = decompile::comments(cpu.binding(), cpu.program());
+        Debugger {
+            initial: cpu.clone(),
+            counts: vec![0; cpu.program().len()],
+            cpu,
+            comments,
+            breakpoints: BTreeSet::new(),
+            watchpoints: vec![],
+        }
+    }
+
+    pub fn cpu(&self) -> &Cpu<'a> {
+        &self.cpu
+    }
+
+    /// Runs up to `steps` instructions, stopping early at the breakpoints and watchpoints. The
+    /// breakpoint at the current instruction is skipped, to get going again after stopping at it.
+    pub fn run(&mut self, steps: Option<u64>) -> Stop {
+        let mut ran = 0;
+        loop {
+            if steps == Some(ran) {
+                return Stop::Stepped;
+            }
+            let ip = self.cpu.ip();
+            if ran > 0 && self.breakpoints.contains(&ip) {
+                return Stop::Breakpoint(ip);
+            }
+            let before = *self.cpu.registers();
+            if !self.cpu.step() {
+                return Stop::Halted;
+            }
+            self.counts[ip] += 1;
+            ran += 1;
+            let after = self.cpu.registers();
+            if let Some(watchpoint) = self.watchpoints.iter().find(|w| w.hit(&before, after)) {
+                return Stop::Watchpoint(*watchpoint);
+            }
+        }
+    }
+
+    /// The instruction at `ip`, with what it does.
+    fn describe(&self, ip: usize) -> String {
+        match self.cpu.program().get(ip) {
+            Some(inst) => format!(
+                "{:>3}: {:<18} ; {}",
+                ip,
+                inst.to_string(),
+                self.comments[ip]
+            ),
+            None => format!("{:>3}: halted", ip),
+        }
+    }
+
+    fn registers(&self) -> String {
+        let registers = self
+            .cpu
+            .registers()
+            .iter()
+            .enumerate()
+            .map(|(i, value)| format!("r{}={}", i, value))
+            .collect::<Vec<_>>()
+            .join(" ");
+        format!(
+            "{}  ({} instructions run)\n{}",
+            registers,
+            self.cpu.executed(),
+            self.describe(self.cpu.ip())
+        )
+    }
+
+    /// The instructions that ran, the most frequent first.
+    fn counts(&self) -> String {
+        let mut counts = self
+            .counts
+            .iter()
+            .enumerate()
+            .filter(|&(_, &count)| count > 0)
+            .collect::<Vec<_>>();
+        counts.sort_by_key(|&(ip, &count)| (std::cmp::Reverse(count), ip));
+        if counts.is_empty() {
+            return "Nothing ran yet".to_owned();
+        }
+        counts
+            .iter()
+            .map(|&(ip, count)| format!("{:>12}  {}", count, self.describe(ip)))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn list(&self) -> String {
+        let ip = self.cpu.ip();
+        let len = self.cpu.program().len();
+        (ip.saturating_sub(5)..len.min(ip.saturating_add(6)))
+            .map(|i| {
+                let marker = if i == ip { "=>" } else { "  " };
+                let breakpoint = if self.breakpoints.contains(&i) {
+                    "*"
+                } else {
+                    " "
+                };
+                format!("{}{}{}", marker, breakpoint, self.describe(i))
+            })
+            .chain((ip >= len).then(|| format!("=> {}", self.describe(ip))))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn info(&self) -> String {
+        let mut lines = self
+            .breakpoints
+            .iter()
+            .map(|&ip| format!("Breakpoint at {}", self.describe(ip).trim_start()))
+            .collect::<Vec<_>>();
+        lines.extend(self.watchpoints.iter().map(|w| match w.value {
+            Some(value) => format!("Watchpoint on r{} becoming {}", w.register, value),
+            None => format!("Watchpoint on r{}", w.register),
+        }));
+        if lines.is_empty() {
+            "No breakpoints or watchpoints".to_owned()
+        } else {
+            lines.join("\n")
+        }
+    }
+
+    fn stopped(&self, stop: Stop) -> String {
+        let at = self.describe(self.cpu.ip());
+        match stop {
+            Stop::Stepped => at,
+            Stop::Breakpoint(_) => format!("Breakpoint\n{}", at),
+            Stop::Watchpoint(w) => format!(
+                "Watchpoint, r{} = {}\n{}",
+                w.register,
+                self.cpu.registers()[w.register],
+                at
+            ),
+            Stop::Halted => format!(
+                "The program halted after {} instructions\n{}",
+                self.cpu.executed(),
+                self.registers()
+            ),
+        }
+    }
+
+    /// Runs a command, returning what to show.
+    pub fn execute(&mut self, command: Command) -> String {
+        match command {
+            Command::Step(steps) => {
+                let stop = self.run(Some(steps));
+                self.stopped(stop)
+            }
+            Command::Continue => {
+                let stop = self.run(None);
+                self.stopped(stop)
+            }
+            Command::Break(ip) => {
+                self.breakpoints.insert(ip);
+                format!("Breakpoint at {}", self.describe(ip).trim_start())
+            }
+            Command::Delete(ip) => match self.breakpoints.remove(&ip) {
+                true => format!("Removed the breakpoint at {}", ip),
+                false => format!("There's no breakpoint at {}", ip),
+            },
+            Command::Watch(watchpoint) => {
+                self.watchpoints.push(watchpoint);
+                format!("Watching r{}", watchpoint.register)
+            }
+            Command::Unwatch(register) => {
+                self.watchpoints.retain(|w| w.register != register);
+                format!("Stopped watching r{}", register)
+            }
+            Command::Info => self.info(),
+            Command::Registers => self.registers(),
+            Command::Set(register, value) => {
+                self.cpu.registers_mut()[register] = value;
+                self.registers()
+            }
+            Command::Counts => self.counts(),
+            Command::List => self.list(),
+            Command::Reset => {
+                self.cpu = self.initial.clone();
+                self.counts.iter_mut().for_each(|count| *count = 0);
+                self.registers()
+            }
+            Command::Help => HELP.to_owned(),
+            Command::Quit => String::new(),
+        }
+    }
+
+    /// Reads commands from `input` until it ends or `quit`, prompting for them on `output`.
+    pub fn repl(&mut self, input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+        writeln!(output, "{}", self.describe(self.cpu.ip()))?;
+        let mut previous = None;
+        let mut lines = input.lines();
+        loop {
+            write!(output, "(elfcode) ")?;
+            output.flush()?;
+            let line = match lines.next() {
+                Some(line) => line?,
+                None => return writeln!(output),
+            };
+            let command = if line.trim().is_empty() {
+                match previous {
+                    Some(command) => Ok(command),
+                    None => continue,
+                }
+            } else {
+                Command::parse(&line, self.cpu.binding())
+            };
+            match command {
+                Ok(Command::Quit) => return Ok(()),
+                Ok(command) => {
+                    previous = Some(command);
+                    writeln!(output, "{}", self.execute(command))?;
+                }
+                Err(err) => writeln!(output, "{}", err)?,
+            }
+        }
+    }
+}
+
+/// Debugs the program, reading the commands from stdin.
+pub fn debug(ip: Option<usize>, program: &[Instruction]) -> io::Result<()> {
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    Debugger::new(Cpu::new(ip, program)).repl(stdin.lock(), stdout.lock())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::elfcode::assemble;
+
+    fn day21() -> (Option<usize>, Vec<Instruction>) {
+        assemble(21, include_str!("../day21/input.txt")).unwrap()
+    }
+
+    #[test]
+    fn commands() {
+        let parse = |line| Command::parse(line, Some(1));
+        assert_eq!(parse("s"), Ok(Command::Step(1)));
+        assert_eq!(parse("  step 10 "), Ok(Command::Step(10)));
+        assert_eq!(parse("b 28"), Ok(Command::Break(28)));
+        assert_eq!(
+            parse("watch r3 12"),
+            Ok(Command::Watch(Watchpoint {
+                register: 3,
+                value: Some(12)
+            }))
+        );
+        assert_eq!(parse("set ip 5"), Ok(Command::Set(1, 5)));
+        assert_eq!(parse("set 0 5"), Ok(Command::Set(0, 5)));
+        assert!(parse("set r6 5").is_err());
+        assert_eq!(
+            parse("step x"),
+            Err("Invalid value for step: x, expected a number".to_owned())
+        );
+        assert!(parse("jump 3").is_err());
+        assert!(Command::parse("w ip", None).is_err());
+    }
+
+    #[test]
+    fn breakpoints_and_watchpoints() {
+        let (ip, program) = day21();
+        let mut debugger = Debugger::new(Cpu::new(ip, &program[..]));
+        debugger.execute(Command::Break(28));
+        assert_eq!(debugger.run(None), Stop::Breakpoint(28));
+        let halting = debugger.cpu().registers()[3];
+        assert_eq!(halting, 13_270_004);
+
+        // Continuing skips the breakpoint it's stopped at.
+        let watchpoint = Watchpoint {
+            register: 5,
+            value: None,
+        };
+        debugger.execute(Command::Watch(watchpoint));
+        assert_eq!(debugger.run(None), Stop::Watchpoint(watchpoint));
+        assert_eq!(debugger.cpu().ip(), 7);
+        assert_eq!(debugger.run(Some(3)), Stop::Stepped);
+        assert_eq!(debugger.cpu().ip(), 10);
+
+        // Running the part 1 answer through halts it.
+        debugger.execute(Command::Reset);
+        debugger.execute(Command::Unwatch(5));
+        debugger.execute(Command::Set(0, halting));
+        assert_eq!(debugger.run(None), Stop::Breakpoint(28));
+        assert_eq!(debugger.run(None), Stop::Halted);
+        assert_eq!(debugger.counts[28], 1);
+        assert_eq!(debugger.counts[6], 1);
+        assert_eq!(debugger.counts[18], 259);
+    }
+
+    #[test]
+    fn session() {
+        let (ip, program) = day21();
+        let mut debugger = Debugger::new(Cpu::new(ip, &program[..]));
+        let script = "step\n\nbreak 28\nc\nr\ninfo\nlist\nset r0 13270004\nnope\nc\ncounts\nq\n";
+        let mut output = vec![];
+        debugger.repl(script.as_bytes(), &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let expected = [
+            "  0: seti 123 0 3       ; r3 = 123\n(elfcode)   1: bani 3 456 3       ; r3 &= 456\n",
+            "(elfcode)   2: eqri 3 72 3        ; r3 = r3 == 72\n",
+            "(elfcode) Breakpoint at 28: eqrr 3 0 2         ; r2 = r3 == r0\n",
+            "(elfcode) Breakpoint\n 28: eqrr 3 0 2 ",
+            "(elfcode) r0=0 r1=28 r2=1 r3=13270004 r4=1 r5=1  (1846 instructions run)\n",
+            "(elfcode) Breakpoint at 28: eqrr 3 0 2         ; r2 = r3 == r0\n",
+            "     23: seti 25 1 1        ; goto 26\n",
+            "=>* 28: eqrr 3 0 2         ; r2 = r3 == r0\n",
+            "(elfcode) Unknown command: nope, see `help`\n",
+            "(elfcode) The program halted after 1848 instructions\n",
+            "           1   28: eqrr 3 0 2 ",
+        ];
+        for part in &expected {
+            assert!(output.contains(part), "{:?} not in:\n{}", part, output);
+        }
+        assert!(output.ends_with("(elfcode) "));
+    }
+}
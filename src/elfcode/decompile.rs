@@ -0,0 +1,561 @@
+//! Turns ElfCode programs back into something readable, for figuring out what they compute.
+//!
+//! [`disassemble`] comments every instruction with what it does, while [`pseudo_code`] goes
+//! further and recovers the control flow. Reading the bound register gives the index of the
+//! instruction, writing it is a goto, and a comparison followed by adding it to the instruction
+//! pointer jumps over the next instruction when it holds. Such a jump over a goto is merged into a
+//! single condition, and the gotos are then turned into loops (jumping back) and `if` blocks
+//! (jumping forward) where the jumps nest properly. The leftover gotos out of a loop, to right
+//! after it, become `break`s.
+//!
+//! The inner loop of day 19 comes out as:
+//!
+//! ```text
+//!           do {
+//!  3:         r3 = r2 * r5
+//!  4:         r3 = r3 == r1
+//!  5:         if r3 {
+//!  7:           r0 += r2
+//!             }
+//!  8:         r5 += 1
+//!  9:         r3 = r5 > r1
+//! 10:       } while !r3
+//! ```
+
+use super::{Inst, Instruction, Operand};
+use std::ops::Range;
+
+/// An input of an instruction.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Value {
+    Register(usize),
+    Number(usize),
+}
+
+fn name(register: usize) -> String {
+    format!("r{}", register)
+}
+
+impl Value {
+    fn text(self) -> String {
+        match self {
+            Value::Register(register) => name(register),
+            Value::Number(n) => n.to_string(),
+        }
+    }
+}
+
+/// What an instruction does to the control flow.
+#[derive(Debug, Clone, Eq, PartialEq)]
+enum Stmt {
+    /// Doesn't touch the instruction pointer, ie. `r1 = r2 + 3`.
+    Op(String),
+    /// Jumps to the given instruction, halting when it's past the program.
+    Goto(usize),
+    /// Jumps to `target` when the register is (`set`) or isn't 0.
+    Branch {
+        register: usize,
+        set: bool,
+        target: usize,
+    },
+    /// Jumps to a computed instruction, ie. `goto 26 + r0`, `base` being where it jumps to when
+    /// the register added to the instruction pointer is 0.
+    Jump { text: String, base: Option<usize> },
+    /// A goto merged into the branch before it.
+    Merged,
+}
+
+impl Stmt {
+    /// Where the statement might jump to.
+    fn targets(&self) -> Vec<usize> {
+        match *self {
+            Stmt::Goto(target) | Stmt::Branch { target, .. } => vec![target],
+            // The registers added are usually flags, so 0 or 1.
+            // Saturating, as jumping past the end halts either way.
+            Stmt::Jump {
+                base: Some(base), ..
+            } => vec![base, base.saturating_add(1)],
+            _ => vec![],
+        }
+    }
+}
+
+fn is_comparison(inst: Inst) -> bool {
+    matches!(
+        inst,
+        Inst::Gtir | Inst::Gtri | Inst::Gtrr | Inst::Eqir | Inst::Eqri | Inst::Eqrr
+    )
+}
+
+/// The operator of the instruction, `None` for the set instructions.
+fn operator(inst: Inst) -> Option<&'static str> {
+    match inst {
+        Inst::Addr | Inst::Addi => Some("+"),
+        Inst::Mulr | Inst::Muli => Some("*"),
+        Inst::Banr | Inst::Bani => Some("&"),
+        Inst::Borr | Inst::Bori => Some("|"),
+        Inst::Setr | Inst::Seti => None,
+        Inst::Gtir | Inst::Gtri | Inst::Gtrr => Some(">"),
+        Inst::Eqir | Inst::Eqri | Inst::Eqrr => Some("=="),
+    }
+}
+
+/// Reads the program, with the instruction pointer bound to `ip`.
+struct Decompiler<'a> {
+    ip: Option<usize>,
+    program: &'a [Instruction],
+    stmts: Vec<Stmt>,
+}
+
+impl<'a> Decompiler<'a> {
+    fn new(ip: Option<usize>, program: &'a [Instruction]) -> Decompiler<'a> {
+        let mut decompiler = Decompiler {
+            ip,
+            program,
+            stmts: vec![],
+        };
+        decompiler.stmts = (0..program.len()).map(|i| decompiler.stmt(i)).collect();
+        decompiler
+    }
+
+    /// The inputs of the instruction at `i`, reading the bound register as `i`.
+    fn inputs(&self, i: usize) -> (Value, Option<Value>) {
+        let inst = &self.program[i];
+        let value = |operand, n| match operand {
+            Operand::Register if Some(n) == self.ip => Some(Value::Number(i)),
+            Operand::Register => Some(Value::Register(n)),
+            Operand::Immediate => Some(Value::Number(n)),
+            Operand::Ignored => None,
+        };
+        let (a, b) = inst.inst.operands();
+        let a = value(a, inst.in1).expect("The first input is always used");
+        (a, value(b, inst.in2))
+    }
+
+    /// The value the instruction at `i` computes, when known upfront.
+    fn constant(&self, i: usize) -> Option<usize> {
+        self.computed(i).flatten()
+    }
+
+    /// The value the instruction at `i` computes when its inputs are known upfront, `Some(None)`
+    /// when it overflows.
+    fn computed(&self, i: usize) -> Option<Option<usize>> {
+        let (a, b) = match self.inputs(i) {
+            (Value::Number(a), Some(Value::Number(b))) => (a, b),
+            (Value::Number(a), None) => (a, 0),
+            _ => return None,
+        };
+        let inst = self.program[i].inst;
+        match inst {
+            Inst::Addr | Inst::Addi => return Some(a.checked_add(b)),
+            Inst::Mulr | Inst::Muli => return Some(a.checked_mul(b)),
+            _ => {}
+        }
+        // Run it with the inputs read from registers 0 and 1 when they're registers.
+        let (in1, in2) = match inst.operands() {
+            (Operand::Register, Operand::Register) => (0, 1),
+            (Operand::Register, _) => (0, b),
+            (_, Operand::Register) => (a, 1),
+            _ => (a, b),
+        };
+        let mut regs = [a, b, 0, 0, 0, 0];
+        super::exec(inst, in1, in2, 2, &mut regs);
+        Some(Some(regs[2]))
+    }
+
+    /// The expression computed by the instruction at `i`, ie. `r1 + 3`.
+    fn expression(&self, i: usize) -> String {
+        match (self.inputs(i), operator(self.program[i].inst)) {
+            ((a, Some(b)), Some(op)) => format!("{} {} {}", a.text(), op, b.text()),
+            ((a, _), _) => a.text(),
+        }
+    }
+
+    /// What the instruction at `i` does, before merging anything.
+    fn stmt(&self, i: usize) -> Stmt {
+        let inst = &self.program[i];
+        if Some(inst.out) != self.ip {
+            return Stmt::Op(self.assignment(i));
+        }
+        // An overflowing target is past the end of the program, so it halts.
+        if let Some(value) = self.computed(i) {
+            let target = value.and_then(|value| value.checked_add(1));
+            return Stmt::Goto(target.unwrap_or(usize::MAX));
+        }
+        let (a, b) = self.inputs(i);
+        let register = match (inst.inst, a, b) {
+            (Inst::Addr, Value::Number(_), Some(Value::Register(r)))
+            | (Inst::Addr, Value::Register(r), Some(Value::Number(_))) => Some(r),
+            _ => None,
+        };
+        // Adding the result of the comparison right before skips the next instruction.
+        let flag = register.filter(|&r| {
+            i > 0 && self.program[i - 1].out == r && is_comparison(self.program[i - 1].inst)
+        });
+        match (flag, register, a, b) {
+            (Some(register), ..) => Stmt::Branch {
+                register,
+                set: true,
+                target: i + 2,
+            },
+            (None, Some(r), Value::Number(n), _) | (None, Some(r), _, Some(Value::Number(n))) => {
+                match n.checked_add(1) {
+                    Some(base) => Stmt::Jump {
+                        text: format!("goto {} + {}", base, name(r)),
+                        base: Some(base),
+                    },
+                    None => Stmt::Goto(usize::MAX),
+                }
+            }
+            _ if operator(inst.inst).is_none() => Stmt::Jump {
+                text: format!("goto {} + 1", self.expression(i)),
+                base: None,
+            },
+            _ => Stmt::Jump {
+                text: format!("goto ({}) + 1", self.expression(i)),
+                base: None,
+            },
+        }
+    }
+
+    /// The instruction at `i` as an assignment, ie. `r1 = r2 + 3` or `r1 += 3`.
+    fn assignment(&self, i: usize) -> String {
+        let out = self.program[i].out;
+        if let Some(value) = self.constant(i) {
+            return format!("{} = {}", name(out), value);
+        }
+        let op = operator(self.program[i].inst).filter(|_| !is_comparison(self.program[i].inst));
+        match (self.inputs(i), op) {
+            ((Value::Register(r), Some(other)), Some(op))
+            | ((other, Some(Value::Register(r))), Some(op))
+                if r == out =>
+            {
+                format!("{} {}= {}", name(out), op, other.text())
+            }
+            _ => format!("{} = {}", name(out), self.expression(i)),
+        }
+    }
+
+    /// Whether any of the statements outside of `body` might jump into `targets`.
+    fn entered(&self, body: Range<usize>, targets: Range<usize>) -> bool {
+        self.stmts
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !body.contains(i))
+            .flat_map(|(_, stmt)| stmt.targets())
+            .any(|target| targets.contains(&target))
+    }
+
+    /// Merges a branch over a goto into a single branch, ie. `if r3 goto 7; goto 8` into
+    /// `if !r3 goto 8`.
+    fn merge_branches(&mut self) {
+        for i in 0..self.stmts.len().saturating_sub(1) {
+            let register = match self.stmts[i] {
+                Stmt::Branch {
+                    register,
+                    set: true,
+                    target,
+                } if target == i + 2 => register,
+                _ => continue,
+            };
+            let goto = match self.stmts[i + 1] {
+                Stmt::Goto(target) if !self.entered(i + 1..i + 2, i + 1..i + 2) => target,
+                _ => continue,
+            };
+            self.stmts[i] = Stmt::Branch {
+                register,
+                set: false,
+                target: goto,
+            };
+            self.stmts[i + 1] = Stmt::Merged;
+        }
+    }
+
+    /// The first statement at or after `i` that wasn't merged away.
+    fn next(&self, mut i: usize) -> usize {
+        while matches!(self.stmts.get(i), Some(Stmt::Merged)) {
+            i += 1;
+        }
+        i
+    }
+
+    /// A jump target, `exit` being right after the innermost loop.
+    fn target(&self, target: usize, exit: Option<usize>) -> String {
+        if target >= self.program.len() {
+            "halt".to_owned()
+        } else if Some(target) == exit {
+            "break".to_owned()
+        } else {
+            format!("goto {}", target)
+        }
+    }
+
+    fn condition(register: usize, set: bool) -> String {
+        format!("{}{}", if set { "" } else { "!" }, name(register))
+    }
+
+    /// A single statement, without recovering anything.
+    fn text(&self, i: usize, exit: Option<usize>) -> String {
+        match &self.stmts[i] {
+            Stmt::Op(text) | Stmt::Jump { text, .. } => text.clone(),
+            Stmt::Goto(target) => self.target(*target, exit),
+            Stmt::Branch {
+                register,
+                set,
+                target,
+            } => format!(
+                "if {} {}",
+                Decompiler::condition(*register, *set),
+                self.target(*target, exit)
+            ),
+            Stmt::Merged => String::new(),
+        }
+    }
+
+    /// The end of the loop starting at `start`, the last statement jumping back to it, if the
+    /// loop can be recovered within `start..end`.
+    fn loop_end(&self, start: usize, end: usize) -> Option<usize> {
+        let last = (start..end).rev().find(|&i| match self.stmts[i] {
+            Stmt::Goto(target) | Stmt::Branch { target, .. } => target == start,
+            _ => false,
+        })?;
+        // Only entering at the top.
+        if self.entered(start..last + 1, start + 1..last + 1) {
+            None
+        } else {
+            Some(last)
+        }
+    }
+
+    /// Writes out the statements in `start..end`, recovering the loops and `if` blocks.
+    fn block(&self, start: usize, end: usize, depth: usize, exit: Option<usize>, out: &mut Output) {
+        let mut i = start;
+        while i < end {
+            if let Stmt::Merged = self.stmts[i] {
+                i += 1;
+                continue;
+            }
+            if let Some(last) = self.loop_end(i, end) {
+                let exit = Some(self.next(last + 1));
+                match self.stmts[last] {
+                    Stmt::Branch { register, set, .. } => {
+                        out.structure(depth, "do {");
+                        self.block(i, last, depth + 1, exit, out);
+                        let condition = Decompiler::condition(register, set);
+                        out.line(last, depth, &format!("}} while {}", condition));
+                    }
+                    _ => {
+                        out.structure(depth, "loop {");
+                        self.block(i, last, depth + 1, exit, out);
+                        out.structure(depth, "}");
+                    }
+                }
+                i = last + 1;
+                continue;
+            }
+            if let Stmt::Branch {
+                register,
+                set,
+                target,
+            } = self.stmts[i]
+            {
+                // Jumping forward over a block, it's only run when the condition doesn't hold.
+                if i + 1 < target && target <= end && !self.entered(i..target, i + 1..target) {
+                    let condition = Decompiler::condition(register, !set);
+                    out.line(i, depth, &format!("if {} {{", condition));
+                    self.block(i + 1, target, depth + 1, exit, out);
+                    out.structure(depth, "}");
+                    i = target;
+                    continue;
+                }
+            }
+            out.line(i, depth, &self.text(i, exit));
+            i += 1;
+        }
+    }
+}
+
+/// The lines of pseudo-code, numbered by instruction.
+struct Output {
+    width: usize,
+    lines: Vec<String>,
+}
+
+impl Output {
+    fn line(&mut self, i: usize, depth: usize, text: &str) {
+        let indent = "  ".repeat(depth);
+        let line = format!("{:>width$}: {}{}", i, indent, text, width = self.width);
+        self.lines.push(line);
+    }
+
+    fn structure(&mut self, depth: usize, text: &str) {
+        let indent = "  ".repeat(depth);
+        let line = format!("{:width$}  {}{}", "", indent, text, width = self.width);
+        self.lines.push(line);
+    }
+}
+
+/// What every instruction does on its own, ie. `r2 = r3 == r0` or `goto 6`.
+pub fn comments(ip: Option<usize>, program: &[Instruction]) -> Vec<String> {
+    let decompiler = Decompiler::new(ip, program);
+    (0..program.len())
+        .map(|i| decompiler.text(i, None))
+        .collect()
+}
+
+/// The instructions as assembly, each commented with what it does.
+///
+/// Reading it back with [`assemble`](super::assemble) gives the same program.
+pub fn disassemble(ip: Option<usize>, program: &[Instruction]) -> String {
+    let mut result = ip.map_or(String::new(), |ip| format!("#ip {}\n", ip));
+    let width = program.len().saturating_sub(1).to_string().len();
+    for (i, (inst, comment)) in program.iter().zip(comments(ip, program)).enumerate() {
+        let line = format!(
+            "{:<18} ; {:>width$}: {}",
+            inst.to_string(),
+            i,
+            comment,
+            width = width
+        );
+        result += line.trim_end();
+        result.push('\n');
+    }
+    result
+}
+
+/// The program as pseudo-code, with its jumps turned into loops and conditions where possible.
+pub fn pseudo_code(ip: Option<usize>, program: &[Instruction]) -> String {
+    let mut decompiler = Decompiler::new(ip, program);
+    decompiler.merge_branches();
+    let mut output = Output {
+        width: program.len().saturating_sub(1).to_string().len().max(2),
+        lines: vec![],
+    };
+    if let Some(ip) = ip {
+        output
+            .lines
+            .push(format!("// {} is the instruction pointer", name(ip)));
+    }
+    decompiler.block(0, program.len(), 0, None, &mut output);
+    output
+        .lines
+        .iter()
+        .map(|line| format!("{}\n", line))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::elfcode::assemble;
+
+    fn decompile(source: &str) -> String {
+        let (ip, program) = assemble(19, source).unwrap();
+        pseudo_code(ip, &program)
+    }
+
+    #[test]
+    fn day19() {
+        let source = include_str!("../day19/input.txt");
+        let (ip, program) = assemble(19, source).unwrap();
+        let code = pseudo_code(ip, &program);
+        let expected = "\
+// r4 is the instruction pointer
+ 0: goto 17
+ 1: r2 = 1
+    do {
+ 2:   r5 = 1
+      do {
+ 3:     r3 = r2 * r5
+ 4:     r3 = r3 == r1
+ 5:     if r3 {
+ 7:       r0 += r2
+        }
+ 8:     r5 += 1
+ 9:     r3 = r5 > r1
+10:   } while !r3
+12:   r2 += 1
+13:   r3 = r2 > r1
+14: } while !r3
+16: halt
+";
+        assert!(code.starts_with(expected), "{}", code);
+        assert!(code.contains("\n25: goto 26 + r0\n26: goto 1\n27: r3 = 27\n"));
+    }
+
+    #[test]
+    fn day21() {
+        let (ip, program) = assemble(21, include_str!("../day21/input.txt")).unwrap();
+        let code = pseudo_code(ip, &program);
+        let expected = "
+    do {
+ 6:   r5 = r3 | 65536
+ 7:   r3 = 15028787
+      loop {
+ 8:     r2 = r5 & 255
+ 9:     r3 += r2
+10:     r3 &= 16777215
+11:     r3 *= 65899
+12:     r3 &= 16777215
+13:     r2 = 256 > r5
+14:     if r2 {
+16:       break
+        }
+17:     r2 = 0
+        loop {
+18:       r4 = r2 + 1
+19:       r4 *= 256
+20:       r4 = r4 > r5
+21:       if r4 {
+23:         break
+          }
+24:       r2 += 1
+        }
+26:     r5 = r2
+      }
+28:   r2 = r3 == r0
+29: } while !r2
+";
+        assert!(code.ends_with(expected), "{}", code);
+    }
+
+    #[test]
+    fn leftover_jumps() {
+        // Jumping into the middle of the loop keeps it from being recovered.
+        let code = decompile("#ip 5\nseti 1 0 5\naddi 0 1 0\naddi 1 1 1\nseti 0 0 5\nsetr 0 0 5");
+        assert_eq!(
+            code,
+            "// r5 is the instruction pointer\n 0: goto 2\n 1: r0 += 1\n 2: r1 += 1\n 3: goto 1\n \
+             4: goto r0 + 1\n"
+        );
+        let code = decompile("#ip 5\nseti 3 0 0\nmulr 0 0 5");
+        assert!(code.ends_with(" 1: goto (r0 * r0) + 1\n"), "{}", code);
+        let code = decompile("seti 2 0 0\naddi 0 1 0");
+        assert_eq!(code, " 0: r0 = 2\n 1: r0 += 1\n");
+    }
+
+    #[test]
+    fn overflowing_targets() {
+        let code = decompile("#ip 0\nseti 18446744073709551615 0 0");
+        assert_eq!(code, "// r0 is the instruction pointer\n 0: halt\n");
+        let code = decompile("#ip 0\nseti 0 0 1\nseti 0 0 1\nmuli 0 18446744073709551615 0");
+        assert!(code.ends_with(" 2: halt\n"), "{}", code);
+        let code = decompile("#ip 5\nseti 0 0 1\nseti 0 0 1\nmuli 5 18446744073709551615 0");
+        assert!(
+            code.ends_with(" 2: r0 = 2 * 18446744073709551615\n"),
+            "{}",
+            code
+        );
+    }
+
+    #[test]
+    fn disassembly() {
+        let source = include_str!("../day21/input.txt");
+        let (ip, program) = assemble(21, source).unwrap();
+        let listing = disassemble(ip, &program);
+        assert!(listing.starts_with("#ip 1\nseti 123 0 3       ;  0: r3 = 123\n"));
+        assert!(listing.contains("\naddr 3 1 1         ;  3: if r3 goto 5\n"));
+        assert!(listing.contains("\nseti 5 3 1         ; 30: goto 6\n"));
+        assert_eq!(assemble(21, &listing), Ok((ip, program)));
+    }
+}
@@ -0,0 +1,645 @@
+//! The device of days 16, 19 and 21, running ElfCode programs.
+//!
+//! A [`Cpu`] holds the registers and a program, with the instruction pointer optionally bound to
+//! one of the registers. Binding it makes the register mirror the instruction pointer, so the
+//! program jumps around by writing to that register.
+//!
+//! Programs are written in the assembly of day 19, read by [`assemble`]:
+//!
+//! ```
+//! use adventofcode2018::elfcode::{self, Cpu};
+//!
+//! let source = "
+//! #ip 4             ; binds the instruction pointer to register 4, anywhere in the program
+//!       seti 5 0 1
+//!       seti done-1 0 4 ; jumps to `done`, as the instruction pointer moves on afterwards
+//!       seti 7 0 1
+//! done: addi 1 1 0
+//! ";
+//! let (ip, program) = elfcode::assemble(19, source).unwrap();
+//! let mut cpu = Cpu::new(ip, program);
+//! assert_eq!(cpu.run(), 3);
+//! assert_eq!(cpu.registers()[0], 6);
+//! ```
+
+pub mod debug;
+pub mod decompile;
+pub mod trace;
+
+use crate::parse::{self, Line};
+use crate::solution::ParseError;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+
+/// The number of registers of the device.
+pub const REGISTERS: usize = 6;
+
+/// The instructions supported by the device.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Inst {
+    /// add register
+    Addr,
+    /// add immediate
+    Addi,
+    /// multiply register
+    Mulr,
+    /// multiply immediate
+    Muli,
+    /// bitwise and register
+    Banr,
+    /// bitwise and immediate
+    Bani,
+    /// bitwise or register
+    Borr,
+    /// bitwise or immediate
+    Bori,
+    /// set register
+    Setr,
+    /// set immediate
+    Seti,
+    /// greater than immediate/register
+    Gtir,
+    /// greater than register/immediate
+    Gtri,
+    /// greater than register/register
+    Gtrr,
+    /// equal immediate/register
+    Eqir,
+    /// equal register/immediate
+    Eqri,
+    /// equal register/register
+    Eqrr,
+}
+
+/// The mnemonics of the instructions, by opcode.
+const MNEMONICS: [&str; 16] = [
+    "addr", "addi", "mulr", "muli", "banr", "bani", "borr", "bori", "setr", "seti", "gtir", "gtri",
+    "gtrr", "eqir", "eqri", "eqrr",
+];
+
+/// How an instruction uses one of its inputs.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Operand {
+    /// The value of the given register.
+    Register,
+    /// The value itself.
+    Immediate,
+    /// Not used at all, ie. the second input of `seti`.
+    Ignored,
+}
+
+impl Inst {
+    /// All the instructions, by opcode.
+    pub const ALL: [Inst; 16] = [
+        Inst::Addr,
+        Inst::Addi,
+        Inst::Mulr,
+        Inst::Muli,
+        Inst::Banr,
+        Inst::Bani,
+        Inst::Borr,
+        Inst::Bori,
+        Inst::Setr,
+        Inst::Seti,
+        Inst::Gtir,
+        Inst::Gtri,
+        Inst::Gtrr,
+        Inst::Eqir,
+        Inst::Eqri,
+        Inst::Eqrr,
+    ];
+
+    /// Converts opcode to instruction.
+    pub fn from_usize(inst: usize) -> Inst {
+        match Inst::ALL.get(inst) {
+            Some(&inst) => inst,
+            None => panic!("Bad opcode: {}", inst),
+        }
+    }
+
+    /// The instruction with the given mnemonic, ie. `addr`.
+    pub fn from_mnemonic(mnemonic: &str) -> Option<Inst> {
+        let opcode = MNEMONICS.iter().position(|&m| m == mnemonic)?;
+        Some(Inst::ALL[opcode])
+    }
+
+    /// The mnemonic of the instruction, ie. `addr`.
+    pub fn mnemonic(self) -> &'static str {
+        MNEMONICS[self as usize]
+    }
+
+    /// How the instruction uses its two inputs.
+    pub fn operands(self) -> (Operand, Operand) {
+        use Operand::*;
+        match self {
+            Inst::Addr | Inst::Mulr | Inst::Banr | Inst::Borr | Inst::Gtrr | Inst::Eqrr => {
+                (Register, Register)
+            }
+            Inst::Addi | Inst::Muli | Inst::Bani | Inst::Bori | Inst::Gtri | Inst::Eqri => {
+                (Register, Immediate)
+            }
+            Inst::Gtir | Inst::Eqir => (Immediate, Register),
+            Inst::Setr => (Register, Ignored),
+            Inst::Seti => (Immediate, Ignored),
+        }
+    }
+}
+
+impl Display for Inst {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.mnemonic())
+    }
+}
+
+/// Executes a single instruction on the registers, storing the result in register `out`.
+pub fn exec(opcode: Inst, in1: usize, in2: usize, out: usize, reg: &mut [usize]) {
+    reg[out] = match opcode {
+        Inst::Addr => reg[in1] + reg[in2],
+        Inst::Addi => reg[in1] + in2,
+        Inst::Mulr => reg[in1] * reg[in2],
+        Inst::Muli => reg[in1] * in2,
+        Inst::Banr => reg[in1] & reg[in2],
+        Inst::Bani => reg[in1] & in2,
+        Inst::Borr => reg[in1] | reg[in2],
+        Inst::Bori => reg[in1] | in2,
+        Inst::Setr => reg[in1],
+        Inst::Seti => in1,
+        Inst::Gtir => (in1 > reg[in2]) as usize,
+        Inst::Gtri => (reg[in1] > in2) as usize,
+        Inst::Gtrr => (reg[in1] > reg[in2]) as usize,
+        Inst::Eqir => (in1 == reg[in2]) as usize,
+        Inst::Eqri => (reg[in1] == in2) as usize,
+        Inst::Eqrr => (reg[in1] == reg[in2]) as usize,
+    }
+}
+
+/// An instruction in a program, with its inputs and the output register.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Instruction {
+    pub inst: Inst,
+    pub in1: usize,
+    pub in2: usize,
+    pub out: usize,
+}
+
+impl Instruction {
+    /// Whether the instruction reads the value of the register.
+    pub fn reads(&self, register: usize) -> bool {
+        let (a, b) = self.inst.operands();
+        (a == Operand::Register && self.in1 == register)
+            || (b == Operand::Register && self.in2 == register)
+    }
+}
+
+impl Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {} {} {}", self.inst, self.in1, self.in2, self.out)
+    }
+}
+
+/// An instruction waiting for the labels to be known.
+struct Unresolved<'a> {
+    line: Line<'a>,
+    inst: Inst,
+    operands: [&'a str; 3],
+}
+
+/// Parses a register number.
+fn parse_register(line: &Line, part: &str) -> Result<usize, ParseError> {
+    match line.parse(part, "a register")? {
+        r if r < REGISTERS => Ok(r),
+        _ => Err(line.error(part, "a register between 0 and 5")),
+    }
+}
+
+/// Whether `name` is fit for a label, ie. `loop_2`.
+fn is_label(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Resolves an input or the output of an instruction, a number or a label like `loop` or `loop-1`.
+fn resolve(
+    line: &Line,
+    part: &str,
+    operand: Operand,
+    labels: &HashMap<&str, usize>,
+) -> Result<usize, ParseError> {
+    let value = if part.starts_with(|c: char| c.is_ascii_digit()) {
+        line.parse(part, "a number")?
+    } else {
+        let (label, offset) = match part.rfind(['+', '-']) {
+            Some(i) if i > 0 => (&part[..i], line.parse::<isize>(&part[i..], "an offset")?),
+            _ => (part, 0),
+        };
+        let index = *labels
+            .get(label)
+            .ok_or_else(|| line.error(part, "a number or a label of the program"))?;
+        index
+            .checked_add_signed(offset)
+            .ok_or_else(|| line.error(part, "a label and offset of at least 0"))?
+    };
+    if operand == Operand::Register && value >= REGISTERS {
+        return Err(line.error(part, "a register between 0 and 5"));
+    }
+    Ok(value)
+}
+
+/// Parses a program, returning the register bound to the instruction pointer (if any) and the
+/// instructions.
+///
+/// Every line holds an instruction like `seti 5 0 1`, the `#ip 0` binding, or nothing at all.
+/// Anything after a `;` is a comment, and a line can start with a label like `loop:`, standing for
+/// the index of its instruction (or of the next one) wherever a number goes.
+pub fn assemble(day: u8, input: &str) -> Result<(Option<usize>, Vec<Instruction>), ParseError> {
+    let mut ip = None;
+    let mut labels = HashMap::new();
+    let mut unresolved = vec![];
+    for line in parse::lines(day, input) {
+        let mut text = line.text.split(';').next().unwrap_or_default().trim();
+        if let Some((label, rest)) = text.split_once(':') {
+            let label = label.trim();
+            if !is_label(label) {
+                return Err(line.error(label, "a label like `loop:`"));
+            }
+            if labels.insert(label, unresolved.len()).is_some() {
+                return Err(line.error(label, "a label that isn't defined already"));
+            }
+            text = rest.trim();
+        }
+        if text.is_empty() {
+            continue;
+        }
+
+        if let Some(register) = text.strip_prefix("#ip") {
+            if ip.is_some() {
+                return Err(line.error(text, "a single `#ip` binding"));
+            }
+            ip = Some(parse_register(&line, register.trim())?);
+            continue;
+        }
+        let expected = "an instruction like `seti 5 0 1`";
+        let fields = text.split_whitespace().collect::<Vec<_>>();
+        let (inst, operands) = match fields.as_slice() {
+            [inst, in1, in2, out] => (inst, [*in1, *in2, *out]),
+            _ => return Err(line.error(text, expected)),
+        };
+        unresolved.push(Unresolved {
+            line,
+            inst: Inst::from_mnemonic(inst)
+                .ok_or_else(|| line.error(inst, "an instruction name like `addr`"))?,
+            operands,
+        });
+    }
+
+    let instructions = unresolved
+        .iter()
+        .map(
+            |Unresolved {
+                 line,
+                 inst,
+                 operands,
+             }| {
+                let (a, b) = inst.operands();
+                Ok(Instruction {
+                    inst: *inst,
+                    in1: resolve(line, operands[0], a, &labels)?,
+                    in2: resolve(line, operands[1], b, &labels)?,
+                    out: resolve(line, operands[2], Operand::Register, &labels)?,
+                })
+            },
+        )
+        .collect::<Result<_, _>>()?;
+    Ok((ip, instructions))
+}
+
+/// The device running a program, either borrowed or owned.
+#[derive(Debug, Clone)]
+pub struct Cpu<'a> {
+    regs: [usize; REGISTERS],
+    /// The register bound to the instruction pointer.
+    binding: Option<usize>,
+    /// The instruction pointer, only used while it isn't bound to a register.
+    ip: usize,
+    program: Cow<'a, [Instruction]>,
+    executed: u64,
+}
+
+impl<'a> Cpu<'a> {
+    /// A device at the start of the program, with all registers set to 0.
+    pub fn new(binding: Option<usize>, program: impl Into<Cow<'a, [Instruction]>>) -> Cpu<'a> {
+        Cpu {
+            regs: [0; REGISTERS],
+            binding,
+            ip: 0,
+            program: program.into(),
+            executed: 0,
+        }
+    }
+
+    /// Starts with the given registers instead. The bound register is the instruction pointer.
+    pub fn registers_set_to(mut self, regs: [usize; REGISTERS]) -> Cpu<'a> {
+        self.regs = regs;
+        self
+    }
+
+    /// The registers.
+    pub fn registers(&self) -> &[usize; REGISTERS] {
+        &self.regs
+    }
+
+    /// The registers, for changing them between steps.
+    pub fn registers_mut(&mut self) -> &mut [usize; REGISTERS] {
+        &mut self.regs
+    }
+
+    /// The register bound to the instruction pointer, if any.
+    pub fn binding(&self) -> Option<usize> {
+        self.binding
+    }
+
+    /// The index of the next instruction to run.
+    pub fn ip(&self) -> usize {
+        match self.binding {
+            Some(register) => self.regs[register],
+            None => self.ip,
+        }
+    }
+
+    /// Jumps to the given instruction.
+    pub fn set_ip(&mut self, ip: usize) {
+        match self.binding {
+            Some(register) => self.regs[register] = ip,
+            None => self.ip = ip,
+        }
+    }
+
+    /// The program being run.
+    pub fn program(&self) -> &[Instruction] {
+        &self.program
+    }
+
+    /// The next instruction to run, `None` once the program halted.
+    pub fn next_instruction(&self) -> Option<&Instruction> {
+        self.program.get(self.ip())
+    }
+
+    /// Whether the instruction pointer left the program.
+    pub fn halted(&self) -> bool {
+        self.next_instruction().is_none()
+    }
+
+    /// The number of instructions run so far.
+    pub fn executed(&self) -> u64 {
+        self.executed
+    }
+
+    /// Runs the next instruction, returning false when the program already halted.
+    pub fn step(&mut self) -> bool {
+        let inst = match self.next_instruction() {
+            Some(&inst) => inst,
+            None => return false,
+        };
+        exec(inst.inst, inst.in1, inst.in2, inst.out, &mut self.regs);
+        self.set_ip(self.ip() + 1);
+        self.executed += 1;
+        true
+    }
+
+    /// Runs the program until it halts, returning the number of instructions run.
+    ///
+    /// Never returns when the program doesn't halt.
+    pub fn run(&mut self) -> u64 {
+        let start = self.executed;
+        self.run_loop(None, |_| false);
+        self.executed - start
+    }
+
+    /// Runs the program until `stop` holds before running an instruction, returning false if it
+    /// halted first.
+    pub fn run_until(&mut self, stop: impl FnMut(&Cpu) -> bool) -> bool {
+        self.run_loop(None, stop)
+    }
+
+    /// Runs the program until the instruction at `ip` is next, returning false if it halted first.
+    pub fn run_to(&mut self, ip: usize) -> bool {
+        self.run_loop(Some(ip), |_| true)
+    }
+
+    /// The loop of `step`, only checking `stop` at the breakpoint if there is one. Skips the
+    /// accessors and works on copies of the registers and the counter, as it's where the days
+    /// spend all their time.
+    fn run_loop(&mut self, breakpoint: Option<usize>, mut stop: impl FnMut(&Cpu) -> bool) -> bool {
+        let mut regs = self.regs;
+        let mut ip = self.ip;
+        let mut executed = self.executed;
+        let halted = loop {
+            if let Some(register) = self.binding {
+                ip = regs[register];
+            }
+            let check = match breakpoint {
+                Some(breakpoint) => ip == breakpoint,
+                None => true,
+            };
+            if check {
+                self.regs = regs;
+                self.ip = ip;
+                self.executed = executed;
+                if stop(self) {
+                    return true;
+                }
+            }
+            let inst = match self.program.get(ip) {
+                Some(&inst) => inst,
+                None => break false,
+            };
+            exec(inst.inst, inst.in1, inst.in2, inst.out, &mut regs);
+            match self.binding {
+                Some(register) => regs[register] += 1,
+                None => ip += 1,
+            }
+            executed += 1;
+        };
+        self.regs = regs;
+        self.ip = ip;
+        self.executed = executed;
+        halted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The example of day 19, with the instruction pointer bound to register 0.
+    fn example() -> Vec<Instruction> {
+        [
+            (Inst::Seti, 5, 0, 1),
+            (Inst::Seti, 6, 0, 2),
+            (Inst::Addi, 0, 1, 0),
+            (Inst::Addr, 1, 2, 3),
+            (Inst::Setr, 1, 0, 0),
+            (Inst::Seti, 8, 0, 4),
+            (Inst::Seti, 9, 0, 5),
+        ]
+        .iter()
+        .map(|&(inst, in1, in2, out)| Instruction {
+            inst,
+            in1,
+            in2,
+            out,
+        })
+        .collect()
+    }
+
+    #[test]
+    fn mnemonics() {
+        for (opcode, &inst) in Inst::ALL.iter().enumerate() {
+            assert_eq!(Inst::from_usize(opcode), inst);
+            assert_eq!(Inst::from_mnemonic(inst.mnemonic()), Some(inst));
+        }
+        assert_eq!(Inst::Gtri.to_string(), "gtri");
+        assert_eq!(Inst::from_mnemonic("gtrx"), None);
+        assert_eq!(
+            Inst::Gtir.operands(),
+            (Operand::Immediate, Operand::Register)
+        );
+    }
+
+    #[test]
+    fn assemble_every_instruction() {
+        let program = Inst::ALL
+            .iter()
+            .enumerate()
+            .map(|(i, &inst)| Instruction {
+                inst,
+                in1: i % 6,
+                in2: 5 - i % 6,
+                out: i % 4,
+            })
+            .collect::<Vec<_>>();
+        let source = program
+            .iter()
+            .map(|inst| format!("{}\n", inst))
+            .collect::<String>();
+        assert_eq!(assemble(19, &source), Ok((None, program)));
+    }
+
+    #[test]
+    fn assemble_with_labels_and_comments() {
+        let source = "; The example of day 19, jumping by labels.
+
+start: seti 5 0 1
+seti 6 0 2
+       addi 0 skip-3 0 ; over the next instruction
+addr 1 2 3
+skip:  setr 1 0 0
+       seti end+2 0 4
+end:
+   #ip 0
+       seti 9 0 5";
+        let (ip, program) = assemble(19, source).unwrap();
+        assert_eq!(ip, Some(0));
+        assert_eq!(program, example());
+    }
+
+    #[test]
+    fn assembler_errors() {
+        let error = |source: &str| {
+            let err = assemble(19, source).unwrap_err();
+            (err.line, err.column, err.expected)
+        };
+        assert_eq!(
+            error("seti 5 0 1\n\nfoo 1 2 3"),
+            (3, 1, "an instruction name like `addr`".to_owned())
+        );
+        assert_eq!(
+            error("addi 1 2\n"),
+            (1, 1, "an instruction like `seti 5 0 1`".to_owned())
+        );
+        assert_eq!(
+            error("  addr 1 6 0"),
+            (1, 10, "a register between 0 and 5".to_owned())
+        );
+        assert_eq!(
+            error("seti 1 0 6 ; out of range"),
+            (1, 10, "a register between 0 and 5".to_owned())
+        );
+        assert_eq!(
+            error("seti nowhere 0 1"),
+            (1, 6, "a number or a label of the program".to_owned())
+        );
+        assert_eq!(
+            error("a: seti a-1 0 1"),
+            (1, 9, "a label and offset of at least 0".to_owned())
+        );
+        assert_eq!(
+            error("a: seti 1 0 1\na: seti 1 0 1"),
+            (2, 1, "a label that isn't defined already".to_owned())
+        );
+        assert_eq!(
+            error("2a: seti 1 0 1"),
+            (1, 1, "a label like `loop:`".to_owned())
+        );
+        assert_eq!(
+            error("#ip 1\n#ip 2"),
+            (2, 1, "a single `#ip` binding".to_owned())
+        );
+        assert_eq!(
+            error("#ip 9"),
+            (1, 5, "a register between 0 and 5".to_owned())
+        );
+    }
+
+    #[test]
+    fn step_through_the_example() {
+        let program = example();
+        let mut cpu = Cpu::new(Some(0), &program[..]);
+        let mut ips = vec![];
+        while !cpu.halted() {
+            ips.push(cpu.ip());
+            assert!(cpu.step());
+        }
+        assert_eq!(ips, vec![0, 1, 2, 4, 6]);
+        assert_eq!(cpu.registers(), &[7, 5, 6, 0, 0, 9]);
+        assert_eq!(cpu.executed(), 5);
+        assert!(!cpu.step());
+        assert_eq!(cpu.executed(), 5);
+    }
+
+    #[test]
+    fn run_until() {
+        let mut cpu = Cpu::new(Some(0), example());
+        assert!(cpu.run_to(4));
+        assert_eq!(cpu.executed(), 3);
+        assert!(cpu.run_to(4));
+        assert_eq!(cpu.executed(), 3);
+        assert!(cpu.run_until(|cpu| cpu.registers()[5] == 9));
+        assert!(cpu.halted());
+        assert!(!cpu.run_until(|_| false));
+        assert!(!cpu.run_to(2));
+        assert_eq!(cpu.executed(), 5);
+
+        let mut cpu = Cpu::new(Some(0), example()).registers_set_to([3, 5, 0, 0, 0, 0]);
+        assert_eq!(cpu.run(), 3);
+        assert_eq!(cpu.registers(), &[7, 5, 0, 5, 0, 9]);
+    }
+
+    #[test]
+    fn unbound_instruction_pointer() {
+        // Register 0 is just a register, so the jump of the example doesn't happen.
+        let mut cpu = Cpu::new(None, example());
+        assert_eq!(cpu.run(), 7);
+        assert_eq!(cpu.ip(), 7);
+        assert_eq!(cpu.registers(), &[5, 5, 6, 11, 8, 9]);
+
+        cpu.set_ip(6);
+        cpu.registers_mut()[5] = 0;
+        assert_eq!(cpu.run(), 1);
+        assert_eq!(cpu.registers()[5], 9);
+    }
+}
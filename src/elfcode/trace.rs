@@ -0,0 +1,310 @@
+//! Tracing and profiling ElfCode programs, to find where they spend their time.
+//!
+//! A [`Tracer`] runs a [`Cpu`] one instruction at a time, writing the instructions with the
+//! registers before and after them, and counting them in a [`Profile`]. Long runs are cut down by
+//! only writing every Nth instruction, or only the last ones. Every jump back is counted as well,
+//! giving the loops the program spends its time in, ie. the inner loop of day 19 at instructions
+//! 3 to 11.
+
+use super::decompile;
+use super::{Cpu, Instruction, REGISTERS};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::{self, Display};
+use std::io::{self, Write};
+
+/// An instruction that ran, with the registers before and after it.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Step {
+    /// The number of instructions run before it.
+    pub executed: u64,
+    pub ip: usize,
+    pub instruction: Instruction,
+    pub before: [usize; REGISTERS],
+    pub after: [usize; REGISTERS],
+}
+
+impl Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:>10} {:>3}: {:<18} {:?} -> {:?}",
+            self.executed,
+            self.ip,
+            self.instruction.to_string(),
+            self.before,
+            self.after
+        )
+    }
+}
+
+/// A loop found by jumping back from its last instruction to its first one.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct HotLoop {
+    pub start: usize,
+    pub end: usize,
+    /// The number of jumps back to the start.
+    pub iterations: u64,
+    /// The number of instructions run within the loop, including the nested loops.
+    pub executed: u64,
+}
+
+/// How often every instruction ran, and the jumps back.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Profile {
+    hits: Vec<u64>,
+    /// The number of jumps back, by start and end of the loop.
+    back_jumps: BTreeMap<(usize, usize), u64>,
+}
+
+impl Profile {
+    /// The number of times every instruction ran, by instruction pointer.
+    pub fn hits(&self) -> &[u64] {
+        &self.hits
+    }
+
+    /// The number of instructions run.
+    pub fn executed(&self) -> u64 {
+        self.hits.iter().sum()
+    }
+
+    /// The loops that ran, the most iterated first.
+    pub fn hot_loops(&self) -> Vec<HotLoop> {
+        let mut loops = self
+            .back_jumps
+            .iter()
+            .map(|(&(start, end), &iterations)| HotLoop {
+                start,
+                end,
+                iterations,
+                executed: self.hits[start..=end].iter().sum(),
+            })
+            .collect::<Vec<_>>();
+        loops.sort_by_key(|l| (Reverse(l.iterations), l.start, l.end));
+        loops
+    }
+
+    /// The instructions that ran and read the register, ie. where day 21 checks register 0 to
+    /// halt.
+    pub fn reading(&self, program: &[Instruction], register: usize) -> Vec<usize> {
+        (0..self.hits.len().min(program.len()))
+            .filter(|&ip| self.hits[ip] > 0 && program[ip].reads(register))
+            .collect()
+    }
+
+    /// A summary of the profile, with the hot loops and the instructions by number of hits.
+    pub fn report(&self, binding: Option<usize>, program: &[Instruction]) -> String {
+        let executed = self.executed();
+        let percent = |hits: u64| 100.0 * hits as f64 / executed.max(1) as f64;
+        let comments = decompile::comments(binding, program);
+        let mut lines = vec![];
+        let loops = self.hot_loops();
+        if !loops.is_empty() {
+            lines.push("Hot loops:".to_owned());
+            lines.extend(loops.iter().map(|l| {
+                format!(
+                    "{:>12}  {:>5.1}%  {:>3}..={:<3} {} iterations",
+                    l.executed,
+                    percent(l.executed),
+                    l.start,
+                    l.end,
+                    l.iterations
+                )
+            }));
+            lines.push(String::new());
+        }
+        lines.push("Instructions:".to_owned());
+        let mut hits = self
+            .hits
+            .iter()
+            .enumerate()
+            .filter(|&(_, &hits)| hits > 0)
+            .collect::<Vec<_>>();
+        hits.sort_by_key(|&(ip, &hits)| (Reverse(hits), ip));
+        lines.extend(hits.iter().map(|&(ip, &hits)| {
+            format!(
+                "{:>12}  {:>5.1}%  {:>3}: {:<18} ; {}",
+                hits,
+                percent(hits),
+                ip,
+                program[ip].to_string(),
+                comments[ip]
+            )
+        }));
+        lines.join("\n")
+    }
+}
+
+/// Runs a program, tracing and profiling it.
+#[derive(Debug, Clone)]
+pub struct Tracer {
+    every: u64,
+    last: Option<usize>,
+    buffer: VecDeque<Step>,
+    profile: Profile,
+}
+
+impl Default for Tracer {
+    fn default() -> Tracer {
+        Tracer::new()
+    }
+}
+
+impl Tracer {
+    /// Traces every instruction run.
+    pub fn new() -> Tracer {
+        Tracer {
+            every: 1,
+            last: None,
+            buffer: VecDeque::new(),
+            profile: Profile::default(),
+        }
+    }
+
+    /// Only traces every `every`th instruction, starting with the first one.
+    pub fn sample_every(mut self, every: u64) -> Tracer {
+        assert!(every > 0, "Can't sample every 0th instruction");
+        self.every = every;
+        self
+    }
+
+    /// Only traces the last `last` instructions sampled, written once the run stops.
+    pub fn keep_last(mut self, last: usize) -> Tracer {
+        self.last = Some(last);
+        self
+    }
+
+    pub fn profile(&self) -> &Profile {
+        &self.profile
+    }
+
+    /// Runs the next instruction, returning it unless the program halted.
+    pub fn step(&mut self, cpu: &mut Cpu) -> Option<Step> {
+        let ip = cpu.ip();
+        let instruction = *cpu.next_instruction()?;
+        let executed = cpu.executed();
+        let before = *cpu.registers();
+        cpu.step();
+        if self.profile.hits.len() < cpu.program().len() {
+            self.profile.hits.resize(cpu.program().len(), 0);
+        }
+        self.profile.hits[ip] += 1;
+        let next = cpu.ip();
+        if next <= ip {
+            *self.profile.back_jumps.entry((next, ip)).or_insert(0) += 1;
+        }
+        Some(Step {
+            executed,
+            ip,
+            instruction,
+            before,
+            after: *cpu.registers(),
+        })
+    }
+
+    /// Runs the program until it halts or `limit` instructions ran, writing the trace. Returns
+    /// whether it halted.
+    pub fn run(
+        &mut self,
+        cpu: &mut Cpu,
+        limit: Option<u64>,
+        mut trace: impl Write,
+    ) -> io::Result<bool> {
+        let mut ran = 0;
+        let halted = loop {
+            if limit == Some(ran) {
+                break false;
+            }
+            let step = match self.step(cpu) {
+                Some(step) => step,
+                None => break true,
+            };
+            ran += 1;
+            if step.executed % self.every != 0 {
+                continue;
+            }
+            match self.last {
+                Some(0) => {}
+                Some(last) => {
+                    if self.buffer.len() == last {
+                        self.buffer.pop_front();
+                    }
+                    self.buffer.push_back(step);
+                }
+                None => writeln!(trace, "{}", step)?,
+            }
+        };
+        for step in self.buffer.drain(..) {
+            writeln!(trace, "{}", step)?;
+        }
+        Ok(halted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::elfcode::assemble;
+
+    fn program(day: u8, input: &str) -> (Option<usize>, Vec<Instruction>) {
+        assemble(day, input).unwrap()
+    }
+
+    #[test]
+    fn trace() {
+        let (ip, program) = program(19, include_str!("../day19/test.txt"));
+        let mut trace = vec![];
+        let halted = Tracer::new()
+            .run(&mut Cpu::new(ip, &program[..]), None, &mut trace)
+            .unwrap();
+        assert!(halted);
+        let trace = String::from_utf8(trace).unwrap();
+        let lines = trace.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(
+            lines[0],
+            "         0   0: seti 5 0 1         [0, 0, 0, 0, 0, 0] -> [1, 5, 0, 0, 0, 0]"
+        );
+        assert!(lines[4].starts_with("         4   6: seti 9 0 5 "));
+
+        let mut trace = vec![];
+        let mut tracer = Tracer::new().sample_every(2).keep_last(2);
+        tracer
+            .run(&mut Cpu::new(ip, &program[..]), Some(4), &mut trace)
+            .unwrap();
+        let trace = String::from_utf8(trace).unwrap();
+        let executed = trace
+            .lines()
+            .map(|line| line.split_whitespace().next().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(executed, ["0", "2"]);
+        assert_eq!(tracer.profile().executed(), 4);
+    }
+
+    #[test]
+    fn day19_inner_loop() {
+        let (ip, program) = program(19, include_str!("../day19/input.txt"));
+        let mut tracer = Tracer::new();
+        tracer
+            .run(&mut Cpu::new(ip, &program[..]), Some(1_000_000), io::sink())
+            .unwrap();
+        let loops = tracer.profile().hot_loops();
+        assert_eq!((loops[0].start, loops[0].end), (3, 11));
+        assert!(loops[0].executed > 900_000);
+        assert_eq!((loops[1].start, loops[1].end), (2, 15));
+    }
+
+    #[test]
+    fn day21_halting_check() {
+        let (ip, program) = program(21, include_str!("../day21/input.txt"));
+        let mut tracer = Tracer::new();
+        tracer
+            .run(&mut Cpu::new(ip, &program[..]), Some(100_000), io::sink())
+            .unwrap();
+        let profile = tracer.profile();
+        assert_eq!(profile.reading(&program, 0), [28]);
+        let loops = profile.hot_loops();
+        assert_eq!((loops[0].start, loops[0].end), (18, 25));
+        assert!(profile.report(ip, &program).contains(" 28: eqrr 3 0 2 "));
+    }
+}
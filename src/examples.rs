@@ -0,0 +1,293 @@
+//! Extraction of the examples from saved puzzle descriptions, into fixtures for the tests.
+//!
+//! The examples of every part are the `<pre><code>` blocks of its `<article>`, each paired with
+//! the next answer (`<em>` within a `<code>`) following it. When a part only refers back to the
+//! example above, its first answer goes with the last example of the previous part. The pairing
+//! is a guess, so the fixtures are meant to be looked over before committing them.
+//!
+//! A fixture holds the examples of a single day:
+//!
+//! ```toml
+//! [[example]]
+//! part = 1
+//! answer = "7"
+//! input = """
+//! pos=<0,0,0>, r=4
+//! """
+//! ```
+
+use crate::solution;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The directory the fixtures are written to, as `dayNN.toml`.
+pub const FIXTURES_DIR: &str = "fixtures";
+
+/// The test module running the fixtures, generated from the contents of [`FIXTURES_DIR`].
+pub const TEST_MODULE: &str = "tests/examples.rs";
+
+/// An example input and its expected answer.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Example {
+    pub part: u8,
+    pub answer: String,
+    pub input: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Fixture {
+    example: Vec<Example>,
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// The text of a piece of HTML, without the tags.
+fn text(html: &str) -> String {
+    let tags = Regex::new(r"<[^>]*>").unwrap();
+    decode_entities(&tags.replace_all(html, ""))
+}
+
+/// Extracts the day and its examples from a saved puzzle page.
+pub fn extract(html: &str) -> Result<(u8, Vec<Example>), String> {
+    let title = Regex::new(r"--- Day (\d+):").unwrap();
+    let day = title
+        .captures(html)
+        .and_then(|caps| caps[1].parse::<u8>().ok())
+        .filter(|day| (1..=25).contains(day))
+        .ok_or("Not a puzzle page, the `--- Day N: ... ---` title is missing")?;
+
+    let article = Regex::new(r"(?s)<article[^>]*>(.*?)</article>").unwrap();
+    let block =
+        Regex::new(r"(?s)<pre><code>(.*?)</code></pre>|<code>[^<]*<em>([^<]*)</em>[^<]*</code>")
+            .unwrap();
+    let mut examples = Vec::<Example>::new();
+    let mut previous = None;
+    for (part, caps) in (1..=2).zip(article.captures_iter(html)) {
+        let mut pending = None;
+        let mut paired = false;
+        for block in block.captures_iter(&caps[1]) {
+            if let Some(code) = block.get(1) {
+                // Only the first of several blocks in a row, the others tend to be the steps of
+                // working through it.
+                pending = pending.or_else(|| Some(text(code.as_str())));
+                continue;
+            }
+            let input = match pending.take() {
+                Some(input) => input,
+                None if !paired => match previous.take() {
+                    Some(input) => input,
+                    None => continue,
+                },
+                None => continue,
+            };
+            paired = true;
+            previous = Some(input.clone());
+            let example = Example {
+                part,
+                answer: text(&block[2]).trim().to_owned(),
+                input,
+            };
+            if !examples.contains(&example) {
+                examples.push(example);
+            }
+        }
+    }
+    Ok((day, examples))
+}
+
+/// Writes out the examples of a day as a fixture.
+pub fn to_fixture(day: u8, examples: &[Example]) -> String {
+    let fixture = Fixture {
+        example: examples.to_vec(),
+    };
+    format!(
+        "# The examples from the puzzle description of day {}, run by `{}`.\n\n{}",
+        day,
+        TEST_MODULE,
+        toml::to_string(&fixture).expect("Examples are always valid TOML")
+    )
+}
+
+/// Reads the examples from a fixture.
+pub fn parse_fixture(fixture: &str) -> Result<Vec<Example>, String> {
+    toml::from_str::<Fixture>(fixture)
+        .map(|fixture| fixture.example)
+        .map_err(|err| err.to_string())
+}
+
+/// The path of the fixture for the given day in `dir`.
+pub fn fixture_path(dir: &Path, day: u8) -> PathBuf {
+    dir.join(format!("day{:02}.toml", day))
+}
+
+/// The days with a fixture in `dir`.
+pub fn fixture_days(dir: &Path) -> Result<Vec<u8>, String> {
+    let entries =
+        fs::read_dir(dir).map_err(|err| format!("Unable to read {}: {}", dir.display(), err))?;
+    let mut days = entries
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        .filter_map(|name| {
+            name.strip_prefix("day")?
+                .strip_suffix(".toml")?
+                .parse::<u8>()
+                .ok()
+        })
+        .filter(|day| (1..=25).contains(day))
+        .collect::<Vec<_>>();
+    days.sort_unstable();
+    Ok(days)
+}
+
+/// Runs the examples in a fixture through the registered solvers, returning how many there were.
+pub fn check(day: u8, fixture: &str) -> Result<usize, String> {
+    let examples = parse_fixture(fixture)?;
+    let solvers = solution::solvers();
+    let mut failures = Vec::new();
+    for (i, example) in examples.iter().enumerate() {
+        let solver = solvers
+            .iter()
+            .find(|s| s.day == day && s.part == example.part)
+            .ok_or_else(|| format!("Day {} has no part {}", day, example.part))?;
+        match solver.solve(&example.input) {
+            Ok(answer) if answer == example.answer => {}
+            Ok(answer) => failures.push(format!(
+                "Example {} (part {}): expected {}, got {}",
+                i + 1,
+                example.part,
+                example.answer,
+                answer
+            )),
+            Err(err) => failures.push(format!(
+                "Example {} (part {}): {}",
+                i + 1,
+                example.part,
+                err
+            )),
+        }
+    }
+    if failures.is_empty() {
+        Ok(examples.len())
+    } else {
+        Err(failures.join("\n"))
+    }
+}
+
+/// Generates the test module, with a test for each of the days with a fixture.
+pub fn test_module(days: &[u8]) -> String {
+    let mut module = format!(
+        "//! Generated by `cargo run -- extract`, runs the examples in `{}/` through the solvers.\n\n\
+         use adventofcode2018::examples;\n",
+        FIXTURES_DIR
+    );
+    for day in days {
+        module += &format!(
+            "\n#[test]\nfn day{day:02}() {{\n    let fixture = include_str!(\"../{dir}/day{day:02}.toml\");\n    \
+             examples::check({day}, fixture).unwrap();\n}}\n",
+            day = day,
+            dir = FIXTURES_DIR
+        );
+    }
+    module
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: &str = "<main>
+<article class=\"day-desc\"><h2>--- Day 23: Experimental Emergency Teleportation ---</h2>
+<p>For example, given these nanobots:</p>
+<pre><code>pos=&lt;0,0,0&gt;, r=4
+pos=&lt;1,0,0&gt;, r=1
+</code></pre>
+<p>The strongest one is the first, and going through them one at a time:</p>
+<pre><code>The nanobot at 0,0,0 is distance 0 away, and so it is <em>in range</em>.
+</code></pre>
+<p>In this example, <code><em>2</em></code> nanobots are in range.</p>
+<p>Another one, with <code>pos=&lt;3,0,0&gt;</code> being the strongest:</p>
+<pre><code>pos=&lt;0,0,0&gt;, r=1
+pos=&lt;3,0,0&gt;, r=2
+</code></pre>
+<p>Now there's <code>0 + 1 = <em>1</em></code> of them.</p>
+</article>
+<article class=\"day-desc\"><h2 id=\"part2\">--- Part Two ---</h2>
+<p>In the example above, the distance is <code><em>1</em></code>, and <em>not</em> <code><em>5</em></code>.</p>
+</article>
+</main>";
+
+    #[test]
+    fn extract_examples() {
+        let (day, examples) = extract(PAGE).unwrap();
+        assert_eq!(day, 23);
+        assert_eq!(
+            examples,
+            vec![
+                Example {
+                    part: 1,
+                    answer: "2".to_owned(),
+                    input: "pos=<0,0,0>, r=4\npos=<1,0,0>, r=1\n".to_owned(),
+                },
+                Example {
+                    part: 1,
+                    answer: "1".to_owned(),
+                    input: "pos=<0,0,0>, r=1\npos=<3,0,0>, r=2\n".to_owned(),
+                },
+                Example {
+                    part: 2,
+                    answer: "1".to_owned(),
+                    input: "pos=<0,0,0>, r=1\npos=<3,0,0>, r=2\n".to_owned(),
+                },
+            ]
+        );
+        assert!(extract("<p>Not a puzzle</p>").is_err());
+    }
+
+    #[test]
+    fn fixtures() {
+        let (day, examples) = extract(PAGE).unwrap();
+        let fixture = to_fixture(day, &examples);
+        assert_eq!(parse_fixture(&fixture).unwrap(), examples);
+        assert_eq!(check(day, &fixture), Ok(3));
+
+        let wrong = fixture.replace("part = 2\nanswer = \"1\"", "part = 2\nanswer = \"5\"");
+        assert_eq!(
+            check(day, &wrong).unwrap_err(),
+            "Example 3 (part 2): expected 5, got 1"
+        );
+    }
+
+    #[test]
+    fn days_with_fixtures() {
+        let dir = std::env::temp_dir().join(format!("aoc2018-fixtures-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for name in &[
+            "day12.toml",
+            "day03.toml",
+            "day30.toml",
+            "day04.txt",
+            "notes.toml",
+        ] {
+            fs::write(dir.join(name), "").unwrap();
+        }
+        assert_eq!(fixture_days(&dir).unwrap(), vec![3, 12]);
+        assert_eq!(fixture_path(&dir, 3), dir.join("day03.toml"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn generated_tests() {
+        let module = test_module(&[7]);
+        assert!(module.contains("fn day07() {"));
+        assert!(module.contains("include_str!(\"../fixtures/day07.toml\")"));
+        assert!(module.contains("examples::check(7, fixture).unwrap();"));
+    }
+}
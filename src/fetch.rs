@@ -0,0 +1,146 @@
+//! Downloading of the puzzle inputs into a local cache, read by [`input`](crate::input).
+
+use crate::input;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Where the puzzle inputs are downloaded from, unless told otherwise.
+pub const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+
+/// The minimum time between two requests to the server.
+pub const DEFAULT_DELAY: Duration = Duration::from_secs(3);
+
+const YEAR: u16 = 2018;
+
+/// Identifies the requests, as asked for by the Advent of Code maintainers.
+const USER_AGENT: &str = concat!(
+    env!("CARGO_PKG_REPOSITORY"),
+    " (",
+    env!("CARGO_PKG_NAME"),
+    " ",
+    env!("CARGO_PKG_VERSION"),
+    ")"
+);
+
+/// Where an input came from.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Fetched {
+    /// The input was already in the cache, the server wasn't asked.
+    Cached,
+    Downloaded,
+}
+
+/// Downloads the inputs of a user into the cache directory, never fetching the same day twice.
+pub struct Fetcher {
+    session: String,
+    cache_dir: PathBuf,
+    base_url: String,
+    delay: Duration,
+    last_request: Option<Instant>,
+}
+
+impl Fetcher {
+    /// Creates a fetcher for the given session token, caching the inputs as `dayNN.txt` in
+    /// `cache_dir`.
+    pub fn new(session: &str, cache_dir: &Path) -> Fetcher {
+        Fetcher {
+            session: session.trim().to_owned(),
+            cache_dir: cache_dir.to_owned(),
+            base_url: DEFAULT_BASE_URL.to_owned(),
+            delay: DEFAULT_DELAY,
+            last_request: None,
+        }
+    }
+
+    /// Downloads from another server, ie. a mirror or a local one for testing.
+    pub fn base_url(mut self, url: &str) -> Fetcher {
+        self.base_url = url.trim_end_matches('/').to_owned();
+        self
+    }
+
+    /// Changes the minimum time between two requests.
+    pub fn delay(mut self, delay: Duration) -> Fetcher {
+        self.delay = delay;
+        self
+    }
+
+    /// The URL of the input for the given day.
+    pub fn url(&self, day: u8) -> String {
+        format!("{}/{}/day/{}/input", self.base_url, YEAR, day)
+    }
+
+    /// Makes sure the input for the given day is in the cache, downloading it if it isn't.
+    pub fn fetch(&mut self, day: u8) -> Result<Fetched, String> {
+        let path = input::day_path(&self.cache_dir, day);
+        if path.exists() {
+            return Ok(Fetched::Cached);
+        }
+        if self.session.is_empty() || self.session.contains(char::is_whitespace) {
+            return Err("Invalid session token".to_owned());
+        }
+
+        let input = self.download(day)?;
+        fs::create_dir_all(&self.cache_dir)
+            .map_err(|err| format!("Unable to create {}: {}", self.cache_dir.display(), err))?;
+        // Write to a temporary file first, an interrupted download shouldn't end up cached.
+        let partial = path.with_extension("part");
+        fs::write(&partial, input)
+            .and_then(|_| fs::rename(&partial, &path))
+            .map_err(|err| format!("Unable to write {}: {}", path.display(), err))?;
+        Ok(Fetched::Downloaded)
+    }
+
+    fn download(&mut self, day: u8) -> Result<String, String> {
+        if let Some(last) = self.last_request {
+            thread::sleep(self.delay.saturating_sub(last.elapsed()));
+        }
+        let url = self.url(day);
+        let response = ureq::get(&url)
+            .set("Cookie", &format!("session={}", self.session))
+            .set("User-Agent", USER_AGENT)
+            .call();
+        self.last_request = Some(Instant::now());
+
+        match response {
+            Ok(response) => response
+                .into_string()
+                .map_err(|err| format!("Unable to download day {}: {}", day, err)),
+            Err(ureq::Error::Status(429, response)) => Err(format!(
+                "Rate limited by the server, try again in {} seconds",
+                response.header("Retry-After").unwrap_or("a few")
+            )),
+            Err(ureq::Error::Status(404, _)) => Err(format!("Day {} isn't available yet", day)),
+            Err(ureq::Error::Status(400, _)) | Err(ureq::Error::Status(401, _)) => {
+                Err("The session token was rejected, try logging in again".to_owned())
+            }
+            Err(ureq::Error::Status(status, _)) => Err(format!(
+                "Unable to download day {}: {} returned {}",
+                day, url, status
+            )),
+            Err(err) => Err(format!("Unable to download day {}: {}", day, err)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn urls() {
+        let fetcher = Fetcher::new("abc", Path::new("inputs"));
+        assert_eq!(fetcher.url(3), "https://adventofcode.com/2018/day/3/input");
+        let fetcher = fetcher.base_url("http://localhost:8080/");
+        assert_eq!(fetcher.url(25), "http://localhost:8080/2018/day/25/input");
+    }
+
+    #[test]
+    fn invalid_session() {
+        let dir = std::env::temp_dir().join(format!("aoc2018-session-{}", std::process::id()));
+        let mut fetcher = Fetcher::new("abc\r\nX-Injected: 1", &dir).base_url("http://127.0.0.1:1");
+        assert_eq!(fetcher.fetch(1).unwrap_err(), "Invalid session token");
+        assert!(!dir.exists());
+    }
+}
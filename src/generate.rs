@@ -0,0 +1,1041 @@
+//! Random puzzle inputs, for exercising the solutions beyond the bundled inputs.
+//!
+//! Every generator takes a seed and a size, the same seed and size always giving the same input.
+//! What the size means depends on the day, usually the number of lines, and the default sizes are
+//! about the sizes of the real inputs. The inputs are valid in the sense that both parts have an
+//! answer, ie. day 1 always reaches a frequency twice and day 24 can always be won by the immune
+//! system with a large enough boost.
+
+use crate::day09;
+use crate::day12;
+use crate::day16::{self, Inst};
+use crate::day22;
+use crate::day24;
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+
+/// A small and fast pseudorandom number generator (SplitMix64), so the inputs for a seed never
+/// change.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..n`.
+    pub fn below(&mut self, n: usize) -> usize {
+        assert!(n > 0, "Empty range");
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// A number in `min..=max`.
+    pub fn range(&mut self, min: i64, max: i64) -> i64 {
+        assert!(min <= max, "Empty range");
+        min + (self.next_u64() % (max - min + 1) as u64) as i64
+    }
+
+    /// True with the given probability.
+    pub fn chance(&mut self, probability: f64) -> bool {
+        ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) < probability
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+type Generator = fn(&mut Rng, usize) -> String;
+
+/// The generator for the day, with its default size.
+fn generator(day: u8) -> Option<(Generator, usize)> {
+    Some(match day {
+        1 => (day01, 1000),
+        2 => (day02, 250),
+        3 => (day03, 1300),
+        4 => (day04, 300),
+        5 => (day05, 50_000),
+        6 => (day06, 50),
+        7 => (day07, 26),
+        8 => (day08, 1500),
+        9 => (day09, 70_000),
+        10 => (day10, 350),
+        11 => (day11, 0),
+        12 => (day12, 100),
+        13 => (day13, 16),
+        14 => (day14, 6),
+        15 => (day15, 32),
+        16 => (day16, 800),
+        17 => (day17, 200),
+        18 => (day18, 50),
+        19 => (day19, 0),
+        20 => (day20, 10_000),
+        21 => (day21, 0),
+        22 => (day22, 750),
+        23 => (day23, 1000),
+        24 => (day24, 10),
+        25 => (day25, 1300),
+        _ => return None,
+    })
+}
+
+/// The size used for the day when none is given.
+pub fn default_size(day: u8) -> Option<usize> {
+    generator(day).map(|(_, size)| size)
+}
+
+/// Generates an input for the day, with the default size when `size` is `None`.
+pub fn generate(day: u8, seed: u64, size: Option<usize>) -> Result<String, String> {
+    let (generator, default_size) =
+        generator(day).ok_or_else(|| format!("No generator for day {}", day))?;
+    // Mix the day into the seed, so the days don't all start from the same numbers.
+    let mut rng = Rng::new(seed ^ (u64::from(day) << 56));
+    Ok(generator(&mut rng, size.unwrap_or(default_size)))
+}
+
+fn lines(lines: impl IntoIterator<Item = String>) -> String {
+    let mut result = lines.into_iter().collect::<Vec<_>>().join("\n");
+    result.push('\n');
+    result
+}
+
+/// Frequency changes, `size` of them.
+fn day01(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(2);
+    let mut changes = (1..size)
+        .map(|_| match rng.range(-20, 19) {
+            0 => 20,
+            change => change,
+        })
+        .collect::<Vec<_>>();
+    // With a drift smaller than the number of changes, two of the frequencies in a single pass
+    // are congruent modulo the drift, so one of them is eventually reached twice.
+    let bound = size as i64 / 2;
+    let drift = rng.range(-bound, bound);
+    changes.push(drift - changes.iter().sum::<i64>());
+    lines(changes.iter().map(|change| format!("{:+}", change)))
+}
+
+fn random_id(rng: &mut Rng) -> Vec<u8> {
+    (0..26).map(|_| b'a' + rng.below(26) as u8).collect()
+}
+
+/// Box IDs, `size` of them, with exactly one pair differing by a single character.
+fn day02(rng: &mut Rng, size: usize) -> String {
+    let mut ids = (1..size.max(2)).map(|_| random_id(rng)).collect::<Vec<_>>();
+    let mut twin = rng.choose(&ids).clone();
+    let index = rng.below(twin.len());
+    twin[index] = b'a' + (twin[index] - b'a' + 1 + rng.below(25) as u8) % 26;
+    ids.insert(rng.below(ids.len() + 1), twin);
+    lines(ids.into_iter().map(|id| String::from_utf8(id).unwrap()))
+}
+
+/// Claims on a 1000x1000 fabric, `size` of them, one of them not overlapping any other.
+fn day03(rng: &mut Rng, size: usize) -> String {
+    let claim = |rng: &mut Rng| {
+        let (width, height) = (rng.range(5, 29), rng.range(5, 29));
+        (
+            rng.range(0, 999 - width),
+            rng.range(0, 999 - height),
+            width,
+            height,
+        )
+    };
+    let overlaps = |(x1, y1, w1, h1): (i64, i64, i64, i64), (x2, y2, w2, h2)| {
+        x1 < x2 + w2 && x2 < x1 + w1 && y1 < y2 + h2 && y2 < y1 + h1
+    };
+    let size = size.max(1);
+    let intact = claim(rng);
+    let mut claims = vec![intact];
+    while claims.len() < size {
+        let next = claim(rng);
+        if !overlaps(intact, next) {
+            claims.push(next);
+        }
+    }
+    rng.shuffle(&mut claims);
+    lines(
+        claims
+            .iter()
+            .enumerate()
+            .map(|(i, (x, y, w, h))| format!("#{} @ {},{}: {}x{}", i + 1, x, y, w, h)),
+    )
+}
+
+/// The date `day` days after 1518-01-01, ignoring leap years.
+fn date(mut day: usize) -> String {
+    const MONTHS: [usize; 12] = [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+    let year = 1518 + day / 365;
+    day %= 365;
+    let mut month = 0;
+    while day >= MONTHS[month] {
+        day -= MONTHS[month];
+        month += 1;
+    }
+    format!("{}-{:02}-{:02}", year, month + 1, day + 1)
+}
+
+/// Shuffled guard records, for `size` nights.
+fn day04(rng: &mut Rng, size: usize) -> String {
+    let guards = (0..(size / 15).max(2))
+        .map(|_| rng.range(10, 3500))
+        .collect::<Vec<_>>();
+    let mut records = vec![];
+    for night in 1..=size.max(1) {
+        // The shift starts just before or just after midnight.
+        let start = if rng.chance(0.5) {
+            format!("{} 23:{}", date(night - 1), rng.range(45, 59))
+        } else {
+            format!("{} 00:{:02}", date(night), rng.range(0, 3))
+        };
+        records.push(format!(
+            "[{}] Guard #{} begins shift",
+            start,
+            rng.choose(&guards)
+        ));
+        // Some guards stay awake, but there has to be someone sleeping.
+        if night > 1 && rng.chance(0.1) {
+            continue;
+        }
+        let mut minute = 5;
+        loop {
+            let asleep = minute + rng.range(1, 15);
+            let awake = asleep + rng.range(1, 25);
+            if awake > 59 {
+                break;
+            }
+            records.push(format!("[{} 00:{:02}] falls asleep", date(night), asleep));
+            records.push(format!("[{} 00:{:02}] wakes up", date(night), awake));
+            minute = awake;
+            if rng.chance(0.3) {
+                break;
+            }
+        }
+    }
+    rng.shuffle(&mut records);
+    lines(records)
+}
+
+/// A polymer of `size` units, with plenty of units reacting.
+fn day05(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(1);
+    let mut polymer = Vec::<u8>::with_capacity(size);
+    while polymer.len() < size {
+        let unit = match polymer.last() {
+            Some(&last) if rng.chance(0.3) => last ^ 0x20,
+            _ => b'a' + rng.below(26) as u8 - 0x20 * rng.below(2) as u8,
+        };
+        polymer.push(unit);
+    }
+    lines(Some(String::from_utf8(polymer).unwrap()))
+}
+
+/// Coordinates, `size` of them, with at least one finite area.
+fn day06(rng: &mut Rng, size: usize) -> String {
+    const MIN: i64 = 40;
+    const EXTENT: i64 = 320;
+    // The one in the middle of a 3x3 lattice has a finite area, whatever the other ones.
+    let mut points = (0..9)
+        .map(|i| (MIN + i % 3 * EXTENT / 2, MIN + i / 3 * EXTENT / 2))
+        .collect::<Vec<_>>();
+    let mut seen = points.iter().copied().collect::<HashSet<_>>();
+    while points.len() < size {
+        let point = (rng.range(MIN, MIN + EXTENT), rng.range(MIN, MIN + EXTENT));
+        if seen.insert(point) {
+            points.push(point);
+        }
+    }
+    rng.shuffle(&mut points);
+    lines(points.iter().map(|(x, y)| format!("{}, {}", x, y)))
+}
+
+/// Instructions for `size` steps, at most 26.
+fn day07(rng: &mut Rng, size: usize) -> String {
+    let mut steps = (b'A'..=b'Z').map(char::from).collect::<Vec<_>>();
+    rng.shuffle(&mut steps);
+    steps.truncate(size.clamp(2, 26));
+    // Every step depends on an earlier one, so the steps are always in order.
+    let mut edges = BTreeSet::new();
+    for after in 1..steps.len() {
+        edges.insert((rng.below(after), after));
+        for before in 0..after {
+            if rng.chance(0.15) {
+                edges.insert((before, after));
+            }
+        }
+    }
+    let mut edges = edges.into_iter().collect::<Vec<_>>();
+    rng.shuffle(&mut edges);
+    lines(edges.into_iter().map(|(before, after)| {
+        format!(
+            "Step {} must be finished before step {} can begin.",
+            steps[before], steps[after]
+        )
+    }))
+}
+
+fn day08_node(rng: &mut Rng, nodes: usize, numbers: &mut Vec<usize>) {
+    let rest = nodes - 1;
+    let children = if rest == 0 {
+        0
+    } else {
+        rng.range(1, rest.min(7) as i64) as usize
+    };
+    let metadata = rng.range(1, 11) as usize;
+    numbers.push(children);
+    numbers.push(metadata);
+
+    // Split the rest of the nodes between the children.
+    let mut cuts = BTreeSet::new();
+    while cuts.len() + 1 < children {
+        cuts.insert(rng.range(1, rest as i64 - 1) as usize);
+    }
+    cuts.insert(rest);
+    let mut previous = 0;
+    for cut in cuts.into_iter().filter(|_| children > 0) {
+        day08_node(rng, cut - previous, numbers);
+        previous = cut;
+    }
+    numbers.extend((0..metadata).map(|_| rng.range(1, 9) as usize));
+}
+
+/// A tree of `size` nodes.
+fn day08(rng: &mut Rng, size: usize) -> String {
+    let mut numbers = vec![];
+    day08_node(rng, size.max(1), &mut numbers);
+    let numbers = numbers.iter().map(usize::to_string).collect::<Vec<_>>();
+    lines(Some(numbers.join(" ")))
+}
+
+/// A game with `size` as the last marble.
+fn day09(rng: &mut Rng, size: usize) -> String {
+    format!(
+        "{} players; last marble is worth {} points\n",
+        rng.range(9, 500),
+        size.clamp(1, day09::MAX_MARBLE)
+    )
+}
+
+/// The letters of the day 10 messages, 6 pixels wide and 10 high like in the puzzle.
+const LETTERS: [[&str; 10]; 15] = [
+    // A
+    [
+        "..##..", ".#..#.", "#....#", "#....#", "#....#", "######", "#....#", "#....#", "#....#",
+        "#....#",
+    ],
+    // B
+    [
+        "#####.", "#....#", "#....#", "#....#", "#####.", "#....#", "#....#", "#....#", "#....#",
+        "#####.",
+    ],
+    // C
+    [
+        ".####.", "#....#", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#....#",
+        ".####.",
+    ],
+    // E
+    [
+        "######", "#.....", "#.....", "#.....", "#####.", "#.....", "#.....", "#.....", "#.....",
+        "######",
+    ],
+    // F
+    [
+        "######", "#.....", "#.....", "#.....", "#####.", "#.....", "#.....", "#.....", "#.....",
+        "#.....",
+    ],
+    // G
+    [
+        ".####.", "#....#", "#.....", "#.....", "#.....", "#..###", "#....#", "#....#", "#...##",
+        ".###.#",
+    ],
+    // H
+    [
+        "#....#", "#....#", "#....#", "#....#", "######", "#....#", "#....#", "#....#", "#....#",
+        "#....#",
+    ],
+    // J
+    [
+        "...###", "....#.", "....#.", "....#.", "....#.", "....#.", "....#.", "#...#.", "#...#.",
+        ".###..",
+    ],
+    // K
+    [
+        "#....#", "#...#.", "#..#..", "#.#...", "##....", "##....", "#.#...", "#..#..", "#...#.",
+        "#....#",
+    ],
+    // L
+    [
+        "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....",
+        "######",
+    ],
+    // N
+    [
+        "#....#", "##...#", "##...#", "#.#..#", "#.#..#", "#..#.#", "#..#.#", "#...##", "#...##",
+        "#....#",
+    ],
+    // P
+    [
+        "#####.", "#....#", "#....#", "#....#", "#####.", "#.....", "#.....", "#.....", "#.....",
+        "#.....",
+    ],
+    // R
+    [
+        "#####.", "#....#", "#....#", "#....#", "#####.", "#..#..", "#...#.", "#...#.", "#....#",
+        "#....#",
+    ],
+    // X
+    [
+        "#....#", "#....#", ".#..#.", ".#..#.", "..##..", "..##..", ".#..#.", ".#..#.", "#....#",
+        "#....#",
+    ],
+    // Z
+    [
+        "######", ".....#", ".....#", "....#.", "...#..", "..#...", ".#....", "#.....", "#.....",
+        "######",
+    ],
+];
+
+/// A message of 8 random letters, appearing after 10000-ish seconds. Every pixel of the message
+/// gets a point, and more points are added on random pixels with other velocities until there are
+/// `size` of them.
+fn day10(rng: &mut Rng, size: usize) -> String {
+    let time = rng.range(10_000, 11_000);
+    let velocity = |rng: &mut Rng| match rng.range(-5, 4) {
+        0 => 5,
+        v => v,
+    };
+    let mut pixels = vec![];
+    for i in 0..8 {
+        for (y, row) in rng.choose(&LETTERS).iter().enumerate() {
+            let lit = row.char_indices().filter(|&(_, c)| c == '#');
+            pixels.extend(lit.map(|(x, _)| (i * 8 + x as i64, y as i64)));
+        }
+    }
+    let mut points = pixels
+        .iter()
+        .map(|&pixel| (pixel, (velocity(rng), velocity(rng))))
+        .collect::<Vec<_>>();
+    // A point on every edge moving out either way, so the message is smallest at `time`. Every
+    // letter spans its whole box, so the message spans 62 by 10 pixels.
+    let edge = |on: fn(i64, i64) -> bool| *pixels.iter().find(|&&(x, y)| on(x, y)).unwrap();
+    let edges = [
+        edge(|x, _| x == 0),
+        edge(|x, _| x == 61),
+        edge(|_, y| y == 0),
+        edge(|_, y| y == 9),
+    ];
+    for sign in [-1, 1] {
+        let speed = rng.range(1, 5) * sign;
+        points.push((edges[0], (speed, velocity(rng))));
+        points.push((edges[1], (speed, velocity(rng))));
+        points.push((edges[2], (velocity(rng), speed)));
+        points.push((edges[3], (velocity(rng), speed)));
+    }
+    while points.len() < size {
+        points.push((*rng.choose(&pixels), (velocity(rng), velocity(rng))));
+    }
+    rng.shuffle(&mut points);
+    lines(points.iter().map(|&((x, y), (dx, dy))| {
+        format!(
+            "position=<{:6}, {:6}> velocity=<{:2}, {:2}>",
+            x - dx * time,
+            y - dy * time,
+            dx,
+            dy
+        )
+    }))
+}
+
+/// A grid serial number, the size doesn't matter.
+fn day11(rng: &mut Rng, _size: usize) -> String {
+    format!("{}\n", rng.range(1, 9999))
+}
+
+/// An initial state of `size` pots, settling into a pattern within a thousand generations. The
+/// rules are the ones from the example, random rules rarely settle at all.
+fn day12(rng: &mut Rng, size: usize) -> String {
+    const RULES: [&str; 14] = [
+        "...##", "..#..", ".#...", ".#.#.", ".#.##", ".##..", ".####", "#.#.#", "#.###", "##.#.",
+        "##.##", "###..", "###.#", "####.",
+    ];
+    loop {
+        let state = (0..size.max(1))
+            .map(|_| if rng.chance(0.5) { '#' } else { '.' })
+            .collect::<String>();
+        let mut input = format!("initial state: {}\n\n", state);
+        for rule in RULES.iter() {
+            input.push_str(&format!("{} => #\n", rule));
+        }
+        let (state, rules) = day12::parse_input(&input).expect("Generated invalid pots");
+        if day12::settles_within(&state, &rules, 1000) {
+            return input;
+        }
+    }
+}
+
+/// `size` separate loops of track, each with a pair of carts about to crash into each other, and
+/// a loop with a single cart that survives.
+fn day13(rng: &mut Rng, size: usize) -> String {
+    const CELL: usize = 12;
+    let loops = size.max(1) + 1;
+    let columns = (loops as f64).sqrt().ceil() as usize;
+    let rows = loops.div_ceil(columns);
+    let mut grid = vec![vec![' '; columns * CELL]; rows * CELL];
+    let survivor = rng.below(loops);
+    for i in 0..loops {
+        let (width, height) = (rng.range(3, 10) as usize, rng.range(3, 10) as usize);
+        let (left, top) = (i % columns * CELL, i / columns * CELL);
+        let (right, bottom) = (left + width, top + height);
+        for y in [top, bottom] {
+            grid[y][left + 1..right].fill('-');
+        }
+        for row in &mut grid[top + 1..bottom] {
+            row[left] = '|';
+            row[right] = '|';
+        }
+        grid[top][left] = '/';
+        grid[top][right] = '\\';
+        grid[bottom][left] = '\\';
+        grid[bottom][right] = '/';
+
+        // Carts on the top and bottom edges, heading the opposite way around the loop.
+        grid[top][rng.range(left as i64 + 1, right as i64 - 1) as usize] = '>';
+        if i != survivor {
+            grid[bottom][rng.range(left as i64 + 1, right as i64 - 1) as usize] = '>';
+        }
+    }
+    lines(
+        grid.iter()
+            .map(|row| row.iter().collect::<String>().trim_end().to_owned()),
+    )
+}
+
+/// A number of recipes with `size` digits, at most 7 to stay within the most recipes.
+fn day14(rng: &mut Rng, size: usize) -> String {
+    let mut digits = rng.range(1, 9).to_string();
+    for _ in 1..size.clamp(1, 7) {
+        digits.push_str(&rng.range(0, 9).to_string());
+    }
+    lines(Some(digits))
+}
+
+/// A connected `size` by `size` cave, with a few elves and twice as many goblins.
+fn day15(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(5);
+    let mut map = vec![vec!['#'; size]; size];
+    for row in &mut map[1..size - 1] {
+        for cell in &mut row[1..size - 1] {
+            if rng.chance(0.7) {
+                *cell = '.';
+            }
+        }
+    }
+
+    // Only keep the largest cavern, so every unit can reach every other one.
+    let mut largest: Vec<(usize, usize)> = vec![];
+    let mut seen = HashSet::new();
+    for y in 0..size {
+        for x in 0..size {
+            if map[y][x] != '.' || !seen.insert((x, y)) {
+                continue;
+            }
+            let mut cavern = vec![];
+            let mut queue = VecDeque::from(vec![(x, y)]);
+            while let Some((x, y)) = queue.pop_front() {
+                cavern.push((x, y));
+                for (nx, ny) in [(x, y - 1), (x - 1, y), (x + 1, y), (x, y + 1)] {
+                    if map[ny][nx] == '.' && seen.insert((nx, ny)) {
+                        queue.push_back((nx, ny));
+                    }
+                }
+            }
+            if cavern.len() > largest.len() {
+                largest = cavern;
+            }
+        }
+    }
+    let cavern = largest.iter().copied().collect::<HashSet<_>>();
+    for (y, row) in map.iter_mut().enumerate() {
+        for (x, cell) in row.iter_mut().enumerate() {
+            if !cavern.contains(&(x, y)) {
+                *cell = '#';
+            }
+        }
+    }
+
+    rng.shuffle(&mut largest);
+    let units = (largest.len() / 10).clamp(2, 30);
+    let elves = (units / 3).max(1);
+    for (i, &(x, y)) in largest.iter().take(units).enumerate() {
+        map[y][x] = if i < elves { 'E' } else { 'G' };
+    }
+    lines(map.iter().map(|row| row.iter().collect()))
+}
+
+/// Whether the samples pin down every opcode.
+fn day16_resolvable(samples: &[([usize; 4], [usize; 4], [usize; 4])]) -> bool {
+    // The opcodes are only figured out from the samples using them.
+    if samples
+        .iter()
+        .map(|(_, inst, _)| inst[0])
+        .collect::<HashSet<_>>()
+        .len()
+        < 16
+    {
+        return false;
+    }
+    let mut possible = vec![(0..16).collect::<HashSet<_>>(); 16];
+    for (before, inst, after) in samples {
+        possible[inst[0]].retain(|&candidate| {
+            let mut regs = *before;
+            day16::exec(
+                Inst::from_usize(candidate),
+                inst[1],
+                inst[2],
+                inst[3],
+                &mut regs,
+            );
+            &regs == after
+        });
+    }
+    let mut resolved = HashSet::new();
+    while resolved.len() < 16 {
+        let single = possible.iter().position(|candidates| {
+            candidates.len() == 1 && !resolved.contains(candidates.iter().next().unwrap())
+        });
+        match single {
+            Some(opcode) => {
+                let inst = *possible[opcode].iter().next().unwrap();
+                resolved.insert(inst);
+                for candidates in &mut possible {
+                    if candidates.len() > 1 {
+                        candidates.remove(&inst);
+                    }
+                }
+            }
+            None => return false,
+        }
+    }
+    true
+}
+
+/// `size` samples, with enough of them to figure out the opcodes, and a program of `size`
+/// instructions.
+fn day16(rng: &mut Rng, size: usize) -> String {
+    let mut opcodes = (0..16).collect::<Vec<_>>();
+    rng.shuffle(&mut opcodes);
+    let inst = |rng: &mut Rng| [rng.below(16), rng.below(4), rng.below(4), rng.below(4)];
+    let mut samples = vec![];
+    while samples.len() < size || !day16_resolvable(&samples) {
+        let before = [rng.below(4), rng.below(4), rng.below(4), rng.below(4)];
+        let inst = inst(rng);
+        let mut after = before;
+        day16::exec(
+            Inst::from_usize(opcodes[inst[0]]),
+            inst[1],
+            inst[2],
+            inst[3],
+            &mut after,
+        );
+        samples.push((before, inst, after));
+    }
+
+    let registers =
+        |regs: &[usize; 4]| format!("[{}, {}, {}, {}]", regs[0], regs[1], regs[2], regs[3]);
+    let instruction =
+        |inst: &[usize; 4]| format!("{} {} {} {}", inst[0], inst[1], inst[2], inst[3]);
+    let samples = samples
+        .iter()
+        .map(|(before, inst, after)| {
+            format!(
+                "Before: {}\n{}\nAfter:  {}\n",
+                registers(before),
+                instruction(inst),
+                registers(after)
+            )
+        })
+        .collect::<Vec<_>>();
+    let program = (0..size.max(1)).map(|_| instruction(&inst(rng)));
+    format!("{}\n\n{}", samples.join("\n"), lines(program))
+}
+
+/// Veins of clay for `size` separate buckets, some with a shelf in them.
+fn day17(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(1);
+    let depth = (size as i64 * 9).clamp(50, 1800);
+    let spread = (size as i64).clamp(20, 200);
+    let mut buckets: Vec<(i64, i64, i64, i64)> = vec![];
+    let mut veins = vec![];
+    let mut attempts = 0;
+    while buckets.len() < size && attempts < size * 100 {
+        attempts += 1;
+        let (width, height) = (rng.range(2, 20), rng.range(2, 15));
+        let left = rng.range(500 - spread, 500 + spread - width);
+        let top = rng.range(2, depth - height);
+        let bucket = (left, top, left + width, top + height);
+        // Leave room for the water to flow between the buckets.
+        let (l, t, r, b) = bucket;
+        if buckets
+            .iter()
+            .any(|&(l2, t2, r2, b2)| l <= r2 + 2 && l2 <= r + 2 && t <= b2 + 2 && t2 <= b + 2)
+        {
+            continue;
+        }
+        buckets.push(bucket);
+        veins.push(format!("x={}, y={}..{}", l, t, b));
+        veins.push(format!("x={}, y={}..{}", r, t, b));
+        veins.push(format!("y={}, x={}..{}", b, l, r));
+        if width > 6 && height > 4 && rng.chance(0.3) {
+            let y = rng.range(t + 1, b - 3);
+            let x = rng.range(l + 2, r - 3);
+            veins.push(format!("y={}, x={}..{}", y, x, x + 1));
+        }
+    }
+    rng.shuffle(&mut veins);
+    lines(veins)
+}
+
+/// A `size` by `size` area.
+fn day18(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(1);
+    lines((0..size).map(|_| {
+        (0..size)
+            .map(|_| *rng.choose(&['.', '.', '.', '|', '|', '#']))
+            .collect()
+    }))
+}
+
+/// The background process, with a different number to sum the divisors of, the size doesn't
+/// matter.
+fn day19(rng: &mut Rng, _size: usize) -> String {
+    format!(
+        "#ip 4\naddi 4 16 4\nseti 1 7 2\nseti 1 1 5\nmulr 2 5 3\neqrr 3 1 3\naddr 3 4 4\n\
+         addi 4 1 4\naddr 2 0 0\naddi 5 1 5\ngtrr 5 1 3\naddr 4 3 4\nseti 2 7 4\naddi 2 1 2\n\
+         gtrr 2 1 3\naddr 3 4 4\nseti 1 3 4\nmulr 4 4 4\naddi 1 2 1\nmulr 1 1 1\nmulr 4 1 1\n\
+         muli 1 11 1\naddi 3 {} 3\nmulr 3 4 3\naddi 3 {} 3\naddr 1 3 1\naddr 4 0 4\nseti 0 1 4\n\
+         setr 4 9 3\nmulr 3 4 3\naddr 4 3 3\nmulr 4 3 3\nmuli 3 {} 3\nmulr 3 4 3\naddr 1 3 1\n\
+         seti 0 6 0\nseti 0 7 4\n",
+        rng.range(1, 9),
+        rng.range(1, 30),
+        rng.range(1, 30)
+    )
+}
+
+/// A maze of about `size` rooms, as a regex branching wherever the maze does.
+fn day20(rng: &mut Rng, size: usize) -> String {
+    const DIRECTIONS: [(char, (i64, i64)); 4] =
+        [('N', (0, -1)), ('E', (1, 0)), ('S', (0, 1)), ('W', (-1, 0))];
+    let width = ((size as f64).sqrt().ceil() as i64).max(1);
+
+    // Carve out the maze from the middle, with long winding corridors like the real ones.
+    let start = (width / 2, width / 2);
+    let mut children = HashMap::<_, Vec<_>>::new();
+    let mut visited = HashSet::new();
+    visited.insert(start);
+    let mut path = vec![start];
+    while let Some(&(x, y)) = path.last() {
+        let unvisited = DIRECTIONS
+            .iter()
+            .map(|&(c, (dx, dy))| (c, (x + dx, y + dy)))
+            .filter(|&(_, (nx, ny))| {
+                (0..width).contains(&nx) && (0..width).contains(&ny) && !visited.contains(&(nx, ny))
+            })
+            .collect::<Vec<_>>();
+        if unvisited.is_empty() {
+            path.pop();
+            continue;
+        }
+        let (c, next) = *rng.choose(&unvisited);
+        visited.insert(next);
+        children.entry((x, y)).or_default().push((c, next));
+        path.push(next);
+    }
+
+    // Write out the maze, without recursing as the branches nest deeply.
+    enum Action {
+        Visit((i64, i64)),
+        Push(char),
+    }
+    let mut regex = String::from("^");
+    let mut actions = vec![Action::Visit(start)];
+    while let Some(action) = actions.pop() {
+        let room = match action {
+            Action::Visit(room) => room,
+            Action::Push(c) => {
+                regex.push(c);
+                continue;
+            }
+        };
+        match children.get(&room).map(Vec::as_slice).unwrap_or_default() {
+            [] => {}
+            &[(c, next)] => {
+                regex.push(c);
+                actions.push(Action::Visit(next));
+            }
+            branches => {
+                regex.push('(');
+                actions.push(Action::Push(')'));
+                for (i, &(c, next)) in branches.iter().enumerate().rev() {
+                    actions.push(Action::Visit(next));
+                    actions.push(Action::Push(c));
+                    if i > 0 {
+                        actions.push(Action::Push('|'));
+                    }
+                }
+            }
+        }
+    }
+    regex.push_str("$\n");
+    regex
+}
+
+/// The activation system, with a different hash seed, the size doesn't matter.
+fn day21(rng: &mut Rng, _size: usize) -> String {
+    format!(
+        "#ip 1\nseti 123 0 3\nbani 3 456 3\neqri 3 72 3\naddr 3 1 1\nseti 0 0 1\nseti 0 9 3\n\
+         bori 3 65536 5\nseti {} 4 3\nbani 5 255 2\naddr 3 2 3\nbani 3 16777215 3\n\
+         muli 3 65899 3\nbani 3 16777215 3\ngtir 256 5 2\naddr 2 1 1\naddi 1 1 1\nseti 27 3 1\n\
+         seti 0 9 2\naddi 2 1 4\nmuli 4 256 4\ngtrr 4 5 4\naddr 4 1 1\naddi 1 1 1\nseti 25 1 1\n\
+         addi 2 1 2\nseti 17 8 1\nsetr 2 4 5\nseti 7 3 1\neqrr 3 0 2\naddr 2 1 1\nseti 5 3 1\n",
+        rng.range(1, 0xff_ffff)
+    )
+}
+
+/// A cave with the target `size` regions deep.
+fn day22(rng: &mut Rng, size: usize) -> String {
+    // The mouth and the target are rocky, so the torch can be used there.
+    format!(
+        "depth: {}\ntarget: {},{}\n",
+        rng.range(1000, 4000) * 3,
+        rng.range(5, 15),
+        size.clamp(1, day22::MAX_AREA / 16 - 1)
+    )
+}
+
+/// `size` nanobots.
+fn day23(rng: &mut Rng, size: usize) -> String {
+    let coordinate = |rng: &mut Rng| rng.range(-100_000_000, 200_000_000);
+    lines((0..size.max(1)).map(|_| {
+        format!(
+            "pos=<{},{},{}>, r={}",
+            coordinate(rng),
+            coordinate(rng),
+            coordinate(rng),
+            rng.range(50_000_000, 100_000_000)
+        )
+    }))
+}
+
+fn day24_group(rng: &mut Rng, initiative: usize, attacks: &[&str], resists: &[&str]) -> String {
+    let units = rng.range(10, 9000);
+    let mut types = resists.to_vec();
+    rng.shuffle(&mut types);
+    let immune = &types[..rng.below(3).min(types.len())];
+    let weak = &types[immune.len()..immune.len() + rng.below(3).min(types.len() - immune.len())];
+    let mut traits = vec![];
+    if !immune.is_empty() {
+        traits.push(format!("immune to {}", immune.join(", ")));
+    }
+    if !weak.is_empty() {
+        traits.push(format!("weak to {}", weak.join(", ")));
+    }
+    rng.shuffle(&mut traits);
+    let traits = if traits.is_empty() {
+        String::new()
+    } else {
+        format!("({}) ", traits.join("; "))
+    };
+    format!(
+        "{} units each with {} hit points {}with an attack that does {} {} damage at initiative {}",
+        units,
+        rng.range(1000, 12_000),
+        traits,
+        rng.range(1, 60_000 / units),
+        rng.choose(attacks),
+        initiative
+    )
+}
+
+/// Two armies of `size` groups each.
+fn day24(rng: &mut Rng, size: usize) -> String {
+    const TYPES: [&str; 5] = ["bludgeoning", "cold", "fire", "radiation", "slashing"];
+    let size = size.max(1);
+    loop {
+        let mut initiatives = (1..=2 * size).collect::<Vec<_>>();
+        rng.shuffle(&mut initiatives);
+        // The infection is never immune to the attacks of the immune system.
+        let mut types = TYPES.to_vec();
+        rng.shuffle(&mut types);
+        let (attacks, resists) = types.split_at(2);
+        let immune = (0..size)
+            .map(|i| day24_group(rng, initiatives[i], attacks, &TYPES))
+            .collect::<Vec<_>>();
+        let infection = (size..2 * size)
+            .map(|i| day24_group(rng, initiatives[i], &TYPES, resists))
+            .collect::<Vec<_>>();
+        let mut input = format!("Immune System:\n{}\n", immune.join("\n"));
+        input.push_str(&format!("\nInfection:\n{}\n", infection.join("\n")));
+
+        // Make sure part 2 doesn't have to look forever for a boost that works.
+        let armies = day24::parse_input(&input).expect("Generated an invalid army");
+        if day24::immune_wins(&armies, 1000) {
+            return input;
+        }
+    }
+}
+
+/// `size` points in spacetime.
+fn day25(rng: &mut Rng, size: usize) -> String {
+    lines((0..size.max(1)).map(|_| {
+        let mut coordinate = || rng.range(-8, 8).to_string();
+        [coordinate(), coordinate(), coordinate(), coordinate()].join(",")
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::{self, Status};
+    use crate::solution;
+    use proptest::prelude::{any, prop, prop_assert_eq, proptest, ProptestConfig};
+    use proptest::sample::Index;
+    use std::time::Duration;
+
+    #[test]
+    fn random_numbers() {
+        let mut rng = Rng::new(42);
+        let numbers = (0..1000).map(|_| rng.range(-3, 3)).collect::<Vec<_>>();
+        assert!(numbers.iter().all(|n| (-3..=3).contains(n)));
+        assert_eq!(numbers.iter().collect::<HashSet<_>>().len(), 7);
+        assert_eq!(
+            Rng::new(42).next_u64(),
+            Rng::new(42).next_u64(),
+            "Same seed, same numbers"
+        );
+
+        let mut items = (0..10).collect::<Vec<_>>();
+        rng.shuffle(&mut items);
+        items.sort_unstable();
+        assert_eq!(items, (0..10).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn reproducible() {
+        assert_eq!(generate(15, 1, Some(10)), generate(15, 1, Some(10)));
+        assert_ne!(generate(15, 1, Some(10)), generate(15, 2, Some(10)));
+        assert_eq!(generate(1, 7, Some(25)).unwrap().lines().count(), 25);
+        assert!(generate(26, 1, None).is_err());
+        assert_eq!(default_size(7), Some(26));
+    }
+
+    #[test]
+    fn day10_messages() {
+        for seed in 0..3 {
+            let input = generate(10, seed, None).unwrap();
+            let message = crate::day10::part1(&crate::day10::parse_input(&input).unwrap());
+            // Skip the empty line and the free row around the message.
+            let rows = message.lines().skip(2).take(10).collect::<Vec<_>>();
+            for i in 0..8 {
+                let letter = rows.iter().map(|row| &row[1 + i * 8..7 + i * 8]);
+                assert!(
+                    LETTERS.iter().any(|l| l.iter().copied().eq(letter.clone())),
+                    "Seed {}:{}",
+                    seed,
+                    message
+                );
+            }
+        }
+    }
+
+    /// Even an input of size 0 is a valid one.
+    #[test]
+    fn empty_size() {
+        for day in 1..=25 {
+            let input = generate(day, 0, Some(0)).unwrap();
+            assert_eq!(parser(day).parse(&input), Ok(()), "Day {}:\n{}", day, input);
+        }
+    }
+
+    /// Small inputs for every day have answers for both parts.
+    #[test]
+    fn valid_inputs() {
+        let sizes: HashMap<u8, usize> = [(5, 500), (9, 1000), (14, 5), (20, 200), (22, 20)]
+            .iter()
+            .copied()
+            .collect();
+        for solver in solution::solvers() {
+            // A day 11 input always takes as long to solve, no need to do it again.
+            if solver.day == 11 {
+                continue;
+            }
+            let size = sizes.get(&solver.day).copied().unwrap_or(8);
+            for seed in 0..3 {
+                let input = generate(solver.day, seed, Some(size)).unwrap();
+                let record = runner::run(&solver, Ok(&input), Some(Duration::from_secs(20)));
+                assert_eq!(
+                    record.status,
+                    Status::Ok,
+                    "Day {} part {} with seed {}: {:?}\n{}",
+                    solver.day,
+                    solver.part,
+                    seed,
+                    record.error,
+                    input
+                );
+            }
+        }
+    }
+
+    fn parser(day: u8) -> solution::Solver {
+        solution::solvers()[usize::from(day - 1) * 2]
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn generated_inputs_parse(day in 1..=25u8, seed in any::<u64>(), size in 0..20usize) {
+            let input = generate(day, seed, Some(size)).unwrap();
+            prop_assert_eq!(parser(day).parse(&input), Ok(()));
+        }
+
+        /// Messing up a few characters of a valid input makes for an error, not a panic.
+        #[test]
+        fn mutated_inputs_never_panic(
+            day in 1..=25u8,
+            seed in any::<u64>(),
+            edits in prop::collection::vec((any::<Index>(), 0..3u8, "[ -~\n]"), 1..5),
+        ) {
+            let mut input = generate(day, seed, Some(10)).unwrap().chars().collect::<Vec<_>>();
+            for (index, edit, text) in edits {
+                let c = text.chars().next().unwrap();
+                let i = index.index(input.len() + 1);
+                match edit {
+                    0 => input.insert(i, c),
+                    _ if i == input.len() => {}
+                    1 => {
+                        input.remove(i);
+                    }
+                    _ => input[i] = c,
+                }
+            }
+            let _ = parser(day).parse(&input.into_iter().collect::<String>());
+        }
+    }
+}
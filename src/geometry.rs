@@ -0,0 +1,440 @@
+//! Points, distances, bounding boxes and directions, shared by the days working in space.
+//!
+//! The 2D types follow the puzzles' conventions: `y` grows downwards, and points are ordered
+//! in reading order (top to bottom, then left to right).
+
+use std::array;
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+/// The numbers points can be made of.
+pub trait Coordinate:
+    Copy + Ord + fmt::Debug + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self>
+{
+    /// Zero, the coordinate of the origin.
+    const ZERO: Self;
+    /// One, the length of a single step.
+    const ONE: Self;
+
+    /// The absolute difference between two numbers, which works for unsigned numbers as well.
+    fn distance(self, other: Self) -> Self {
+        if self > other {
+            self - other
+        } else {
+            other - self
+        }
+    }
+}
+
+macro_rules! coordinates {
+    ($($t:ty),*) => {
+        $(impl Coordinate for $t {
+            const ZERO: $t = 0;
+            const ONE: $t = 1;
+        })*
+    };
+}
+
+coordinates!(i32, i64, isize, u32, u64, usize);
+
+/// A point (or a vector) in `N` dimensions.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct Point<const N: usize, T> {
+    pub coords: [T; N],
+}
+
+/// A point in 2D.
+pub type Vec2<T> = Point<2, T>;
+/// A point in 3D.
+pub type Vec3<T> = Point<3, T>;
+/// A point in 4D.
+pub type Vec4<T> = Point<4, T>;
+
+impl<T> Vec2<T> {
+    pub const fn new(x: T, y: T) -> Vec2<T> {
+        Point { coords: [x, y] }
+    }
+}
+
+impl<T> Vec3<T> {
+    pub const fn new(x: T, y: T, z: T) -> Vec3<T> {
+        Point { coords: [x, y, z] }
+    }
+}
+
+impl<T> Vec4<T> {
+    pub const fn new(x: T, y: T, z: T, w: T) -> Vec4<T> {
+        Point {
+            coords: [x, y, z, w],
+        }
+    }
+}
+
+impl<const N: usize, T: Copy> Point<N, T> {
+    /// The first coordinate.
+    pub fn x(&self) -> T {
+        self.coords[0]
+    }
+
+    /// The second coordinate.
+    pub fn y(&self) -> T {
+        self.coords[1]
+    }
+
+    /// The third coordinate.
+    pub fn z(&self) -> T {
+        self.coords[2]
+    }
+
+    /// The fourth coordinate.
+    pub fn w(&self) -> T {
+        self.coords[3]
+    }
+
+    /// Applies `f` to every coordinate.
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Point<N, U> {
+        Point {
+            coords: self.coords.map(f),
+        }
+    }
+}
+
+impl<const N: usize, T: Coordinate> Point<N, T> {
+    /// The point with all the coordinates at zero.
+    pub fn origin() -> Point<N, T> {
+        Point {
+            coords: [T::ZERO; N],
+        }
+    }
+
+    fn zip(self, other: Point<N, T>, mut f: impl FnMut(T, T) -> T) -> Point<N, T> {
+        Point {
+            coords: array::from_fn(|i| f(self.coords[i], other.coords[i])),
+        }
+    }
+
+    /// The sum of the distances along every axis.
+    pub fn manhattan(&self, other: &Point<N, T>) -> T {
+        (0..N).fold(T::ZERO, |sum, i| {
+            sum + self.coords[i].distance(other.coords[i])
+        })
+    }
+
+    /// The largest distance along any of the axes, ie. the number of king moves on a grid.
+    pub fn chebyshev(&self, other: &Point<N, T>) -> T {
+        (0..N).fold(T::ZERO, |max, i| {
+            max.max(self.coords[i].distance(other.coords[i]))
+        })
+    }
+
+    /// The smallest coordinates of both points, unlike [`Ord::min`] which picks either point.
+    pub fn component_min(self, other: Point<N, T>) -> Point<N, T> {
+        self.zip(other, Ord::min)
+    }
+
+    /// The largest coordinates of both points.
+    pub fn component_max(self, other: Point<N, T>) -> Point<N, T> {
+        self.zip(other, Ord::max)
+    }
+}
+
+impl Vec2<usize> {
+    /// The neighbouring point in the direction, or `None` when it would go below zero.
+    pub fn step(self, direction: Direction) -> Option<Vec2<usize>> {
+        let delta = direction.delta::<isize>();
+        Some(Vec2::new(
+            self.x().checked_add_signed(delta.x())?,
+            self.y().checked_add_signed(delta.y())?,
+        ))
+    }
+}
+
+/// Points are ordered by their last coordinate first, which is reading order for 2D points.
+impl<const N: usize, T: Ord> Ord for Point<N, T> {
+    fn cmp(&self, other: &Point<N, T>) -> Ordering {
+        self.coords.iter().rev().cmp(other.coords.iter().rev())
+    }
+}
+
+impl<const N: usize, T: Ord> PartialOrd for Point<N, T> {
+    fn partial_cmp(&self, other: &Point<N, T>) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<const N: usize, T: Coordinate> Default for Point<N, T> {
+    fn default() -> Point<N, T> {
+        Point::origin()
+    }
+}
+
+impl<const N: usize, T> From<[T; N]> for Point<N, T> {
+    fn from(coords: [T; N]) -> Point<N, T> {
+        Point { coords }
+    }
+}
+
+impl<T> From<(T, T)> for Vec2<T> {
+    fn from((x, y): (T, T)) -> Vec2<T> {
+        Vec2::new(x, y)
+    }
+}
+
+/// Formats the point the way the puzzles do, ie. `7,3`.
+impl<const N: usize, T: fmt::Display> fmt::Display for Point<N, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, coord) in self.coords.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{}", coord)?;
+        }
+        Ok(())
+    }
+}
+
+impl<const N: usize, T: Coordinate> Add for Point<N, T> {
+    type Output = Point<N, T>;
+
+    fn add(self, other: Point<N, T>) -> Point<N, T> {
+        self.zip(other, Add::add)
+    }
+}
+
+impl<const N: usize, T: Coordinate> Sub for Point<N, T> {
+    type Output = Point<N, T>;
+
+    fn sub(self, other: Point<N, T>) -> Point<N, T> {
+        self.zip(other, Sub::sub)
+    }
+}
+
+impl<const N: usize, T: Coordinate> Mul<T> for Point<N, T> {
+    type Output = Point<N, T>;
+
+    fn mul(self, factor: T) -> Point<N, T> {
+        self.map(|c| c * factor)
+    }
+}
+
+impl<const N: usize, T: Coordinate + Neg<Output = T>> Neg for Point<N, T> {
+    type Output = Point<N, T>;
+
+    fn neg(self) -> Point<N, T> {
+        self.map(Neg::neg)
+    }
+}
+
+impl<const N: usize, T: Coordinate> AddAssign for Point<N, T> {
+    fn add_assign(&mut self, other: Point<N, T>) {
+        *self = *self + other;
+    }
+}
+
+impl<const N: usize, T: Coordinate> SubAssign for Point<N, T> {
+    fn sub_assign(&mut self, other: Point<N, T>) {
+        *self = *self - other;
+    }
+}
+
+/// The smallest box containing a set of points, with inclusive bounds.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct BoundingBox<const N: usize, T> {
+    pub min: Point<N, T>,
+    pub max: Point<N, T>,
+}
+
+impl<const N: usize, T: Coordinate> BoundingBox<N, T> {
+    /// The box containing just the point.
+    pub fn new(point: Point<N, T>) -> BoundingBox<N, T> {
+        BoundingBox {
+            min: point,
+            max: point,
+        }
+    }
+
+    /// The box containing all the points, or `None` when there are none.
+    pub fn from_points(points: impl IntoIterator<Item = Point<N, T>>) -> Option<BoundingBox<N, T>> {
+        let mut points = points.into_iter();
+        let mut result = BoundingBox::new(points.next()?);
+        points.for_each(|point| result.include(point));
+        Some(result)
+    }
+
+    /// Grows the box to contain the point.
+    pub fn include(&mut self, point: Point<N, T>) {
+        self.min = self.min.component_min(point);
+        self.max = self.max.component_max(point);
+    }
+
+    /// Grows the box by `margin` on every side.
+    pub fn grow(self, margin: T) -> BoundingBox<N, T> {
+        BoundingBox {
+            min: self.min.map(|c| c - margin),
+            max: self.max.map(|c| c + margin),
+        }
+    }
+
+    /// Whether the point is inside the box, or on its edge.
+    pub fn contains(&self, point: &Point<N, T>) -> bool {
+        (0..N).all(|i| (self.min.coords[i]..=self.max.coords[i]).contains(&point.coords[i]))
+    }
+
+    /// Whether the point is on the edge of the box (or outside of it).
+    pub fn on_edge(&self, point: &Point<N, T>) -> bool {
+        (0..N)
+            .any(|i| point.coords[i] <= self.min.coords[i] || point.coords[i] >= self.max.coords[i])
+    }
+
+    /// The number of points along every axis.
+    pub fn size(&self) -> Point<N, T> {
+        (self.max - self.min).map(|c| c + T::ONE)
+    }
+}
+
+/// One of the four directions on a grid.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+/// Which way to turn.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum Turn {
+    Left,
+    Straight,
+    Right,
+}
+
+impl Direction {
+    /// All the directions, clockwise from up.
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Right,
+        Direction::Down,
+        Direction::Left,
+    ];
+
+    /// The direction of an arrow, ie. `^` or `>`.
+    pub fn from_arrow(c: char) -> Option<Direction> {
+        match c {
+            '^' => Some(Direction::Up),
+            '>' => Some(Direction::Right),
+            'v' => Some(Direction::Down),
+            '<' => Some(Direction::Left),
+            _ => None,
+        }
+    }
+
+    /// The direction of a compass point, `N`, `E`, `S` or `W`, with north being up.
+    pub fn from_compass(c: char) -> Option<Direction> {
+        match c {
+            'N' => Some(Direction::Up),
+            'E' => Some(Direction::Right),
+            'S' => Some(Direction::Down),
+            'W' => Some(Direction::Left),
+            _ => None,
+        }
+    }
+
+    /// The direction after turning.
+    pub fn turn(self, turn: Turn) -> Direction {
+        let index = self as usize;
+        match turn {
+            Turn::Left => Direction::ALL[(index + 3) % 4],
+            Turn::Straight => self,
+            Turn::Right => Direction::ALL[(index + 1) % 4],
+        }
+    }
+
+    /// The opposite direction.
+    pub fn reverse(self) -> Direction {
+        Direction::ALL[(self as usize + 2) % 4]
+    }
+
+    /// A single step in the direction.
+    pub fn delta<T: Coordinate + Neg<Output = T>>(self) -> Vec2<T> {
+        match self {
+            Direction::Up => Vec2::new(T::ZERO, -T::ONE),
+            Direction::Right => Vec2::new(T::ONE, T::ZERO),
+            Direction::Down => Vec2::new(T::ZERO, T::ONE),
+            Direction::Left => Vec2::new(-T::ONE, T::ZERO),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arithmetic() {
+        let a = Vec2::new(1, -2);
+        let b = Vec2::new(3, 4);
+        assert_eq!(a + b, Vec2::new(4, 2));
+        assert_eq!(a - b, Vec2::new(-2, -6));
+        assert_eq!(-a * 2, Vec2::new(-2, 4));
+        let mut c = Vec3::new(1i64, 2, 3);
+        c += Vec3::new(1, 1, 1);
+        c -= Vec3::new(0, 0, 4);
+        assert_eq!(c, Vec3::new(2, 3, 0));
+        assert_eq!((c.x(), c.y(), c.z()), (2, 3, 0));
+        assert_eq!(Vec4::new(1, 2, 3, 4).to_string(), "1,2,3,4");
+    }
+
+    #[test]
+    fn distances() {
+        let a = Vec3::new(0i64, 0, 0);
+        let b = Vec3::new(1, -3, 2);
+        assert_eq!(a.manhattan(&b), 6);
+        assert_eq!(a.chebyshev(&b), 3);
+        assert_eq!(Vec2::new(5usize, 1).manhattan(&Vec2::new(2, 7)), 9);
+    }
+
+    #[test]
+    fn reading_order() {
+        let mut points = vec![Vec2::new(2, 1), Vec2::new(1, 2), Vec2::new(1, 1)];
+        points.sort();
+        assert_eq!(
+            points,
+            vec![Vec2::new(1, 1), Vec2::new(2, 1), Vec2::new(1, 2)]
+        );
+    }
+
+    #[test]
+    fn bounding_box() {
+        let points = vec![Vec2::new(1, 5), Vec2::new(-2, 3), Vec2::new(4, 4)];
+        let bounds = BoundingBox::from_points(points).unwrap();
+        assert_eq!(bounds.min, Vec2::new(-2, 3));
+        assert_eq!(bounds.max, Vec2::new(4, 5));
+        assert_eq!(bounds.size(), Vec2::new(7, 3));
+        assert!(bounds.contains(&Vec2::new(0, 4)));
+        assert!(!bounds.contains(&Vec2::new(0, 6)));
+        assert!(bounds.on_edge(&Vec2::new(0, 3)));
+        assert!(!bounds.on_edge(&Vec2::new(0, 4)));
+        assert_eq!(bounds.grow(1).min, Vec2::new(-3, 2));
+        assert!(BoundingBox::<2, i32>::from_points(vec![]).is_none());
+    }
+
+    #[test]
+    fn directions() {
+        assert_eq!(Direction::Up.turn(Turn::Left), Direction::Left);
+        assert_eq!(Direction::Left.turn(Turn::Right), Direction::Up);
+        assert_eq!(Direction::Down.turn(Turn::Straight), Direction::Down);
+        assert_eq!(Direction::Right.reverse(), Direction::Left);
+        assert_eq!(
+            Direction::from_compass('N').unwrap().delta(),
+            Vec2::new(0, -1)
+        );
+        assert_eq!(Direction::from_arrow('>'), Some(Direction::Right));
+        assert_eq!(Vec2::new(0usize, 0).step(Direction::Up), None);
+        assert_eq!(
+            Vec2::new(0usize, 0).step(Direction::Down),
+            Some(Vec2::new(0, 1))
+        );
+    }
+}
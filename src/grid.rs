@@ -0,0 +1,330 @@
+//! A two-dimensional grid, shared by the days working on maps.
+//!
+//! Cells are addressed by `(x, y)`, with `x` the column and `y` the row, counting from the top
+//! left corner. Neighbours are always returned in reading order, which is what most of the
+//! puzzles use to break ties.
+
+use crate::geometry::Vec2;
+use crate::parse;
+use crate::solution::ParseError;
+use std::ops::{Index, IndexMut};
+
+/// The offsets of the 4 orthogonal neighbours, in reading order.
+const OFFSETS4: [(isize, isize); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+/// The offsets of all 8 neighbours, in reading order.
+const OFFSETS8: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+/// A rectangular grid of cells, stored row by row.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T: Clone> Grid<T> {
+    /// Creates a grid with every cell set to `value`.
+    pub fn new(width: usize, height: usize, value: T) -> Grid<T> {
+        Grid {
+            width,
+            height,
+            cells: vec![value; width * height],
+        }
+    }
+
+    /// Parses a map where the lines may be of different lengths, padding them with `fill`.
+    pub fn parse_ragged(
+        day: u8,
+        input: &str,
+        fill: T,
+        expected: &str,
+        cell: impl FnMut(char) -> Option<T>,
+    ) -> Result<Grid<T>, ParseError> {
+        let width = input.lines().map(|l| l.chars().count()).max().unwrap_or(0);
+        Grid::parse_rows(day, input, expected, cell, |row, _| {
+            row.resize(width, fill.clone());
+            Ok(())
+        })
+    }
+}
+
+impl<T> Grid<T> {
+    /// Creates a grid from the cells, row by row.
+    ///
+    /// Panics when the number of cells isn't a multiple of the width.
+    pub fn from_vec(width: usize, cells: Vec<T>) -> Grid<T> {
+        let height = cells.len().checked_div(width).unwrap_or(0);
+        assert_eq!(width * height, cells.len(), "Incomplete row");
+        Grid {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    /// Parses a map of characters, turning every character into a cell.
+    ///
+    /// All the lines should be equally long, a character `cell` doesn't accept is reported as
+    /// an error expecting `expected`.
+    pub fn parse(
+        day: u8,
+        input: &str,
+        expected: &str,
+        cell: impl FnMut(char) -> Option<T>,
+    ) -> Result<Grid<T>, ParseError> {
+        let mut width = None;
+        Grid::parse_rows(day, input, expected, cell, |row, line| {
+            match *width.get_or_insert(row.len()) {
+                width if width == row.len() => Ok(()),
+                width => Err(line.error_at(
+                    row.len().min(width) + 1,
+                    format!("a row of {} cells", width),
+                )),
+            }
+        })
+    }
+
+    fn parse_rows(
+        day: u8,
+        input: &str,
+        expected: &str,
+        mut cell: impl FnMut(char) -> Option<T>,
+        mut check_row: impl FnMut(&mut Vec<T>, &parse::Line) -> Result<(), ParseError>,
+    ) -> Result<Grid<T>, ParseError> {
+        let mut cells = vec![];
+        let mut width = 0;
+        for line in parse::lines(day, input) {
+            let mut row = vec![];
+            for (x, c) in line.text.chars().enumerate() {
+                row.push(cell(c).ok_or_else(|| line.error_at(x + 1, expected))?);
+            }
+            check_row(&mut row, &line)?;
+            width = row.len();
+            cells.extend(row);
+        }
+        if cells.is_empty() {
+            return Err(parse::unexpected_end(day, input, expected));
+        }
+        Ok(Grid::from_vec(width, cells))
+    }
+
+    /// The number of columns.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// The number of rows.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Whether the position is inside the grid.
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height
+    }
+
+    /// The cell at the position, or `None` when outside the grid.
+    pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+        if self.contains(x, y) {
+            Some(&self.cells[y * self.width + x])
+        } else {
+            None
+        }
+    }
+
+    /// The cell at the position, or `None` when outside the grid.
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut T> {
+        if self.contains(x, y) {
+            Some(&mut self.cells[y * self.width + x])
+        } else {
+            None
+        }
+    }
+
+    /// All the cells, row by row.
+    pub fn cells(&self) -> &[T] {
+        &self.cells
+    }
+
+    /// A single row.
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    /// A single row.
+    pub fn row_mut(&mut self, y: usize) -> &mut [T] {
+        &mut self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    /// The rows, from top to bottom.
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width.max(1))
+    }
+
+    /// The cells of a single column, from top to bottom.
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        assert!(x < self.width, "Column {} out of bounds", x);
+        self.cells[x..].iter().step_by(self.width)
+    }
+
+    /// The positions and cells, in reading order.
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        let width = self.width;
+        self.cells
+            .iter()
+            .enumerate()
+            .map(move |(i, cell)| ((i % width, i / width), cell))
+    }
+
+    fn offsets(
+        &self,
+        x: usize,
+        y: usize,
+        offsets: &'static [(isize, isize)],
+    ) -> impl Iterator<Item = (usize, usize)> {
+        let (width, height) = (self.width, self.height);
+        offsets.iter().filter_map(move |&(dx, dy)| {
+            let nx = x.checked_add_signed(dx).filter(|&nx| nx < width)?;
+            let ny = y.checked_add_signed(dy).filter(|&ny| ny < height)?;
+            Some((nx, ny))
+        })
+    }
+
+    /// The orthogonal neighbours of the position inside the grid, in reading order.
+    pub fn neighbours4(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> {
+        self.offsets(x, y, &OFFSETS4)
+    }
+
+    /// The orthogonal and diagonal neighbours of the position inside the grid, in reading order.
+    pub fn neighbours8(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> {
+        self.offsets(x, y, &OFFSETS8)
+    }
+
+    /// Creates a grid of the same size, mapping every cell.
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+
+    /// Renders the grid as text, a line per row.
+    pub fn render(&self, mut cell: impl FnMut(&T) -> char) -> String {
+        let mut result = String::with_capacity((self.width + 1) * self.height);
+        for row in self.rows() {
+            result.extend(row.iter().map(&mut cell));
+            result.push('\n');
+        }
+        result
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (x, y): (usize, usize)) -> &T {
+        self.get(x, y)
+            .unwrap_or_else(|| panic!("Position {},{} out of bounds", x, y))
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut T {
+        self.get_mut(x, y)
+            .unwrap_or_else(|| panic!("Position {},{} out of bounds", x, y))
+    }
+}
+
+impl<T> Index<Vec2<usize>> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Vec2<usize>) -> &T {
+        &self[(pos.x(), pos.y())]
+    }
+}
+
+impl<T> IndexMut<Vec2<usize>> for Grid<T> {
+    fn index_mut(&mut self, pos: Vec2<usize>) -> &mut T {
+        &mut self[(pos.x(), pos.y())]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digits(input: &str) -> Result<Grid<u32>, ParseError> {
+        Grid::parse(1, input, "a digit", |c| c.to_digit(10))
+    }
+
+    #[test]
+    fn access() {
+        let mut grid = digits("123\n456\n").unwrap();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[(2, 0)], 3);
+        assert_eq!(grid.get(0, 1), Some(&4));
+        assert_eq!(grid.get(3, 0), None);
+        assert_eq!(grid.row(1), &[4, 5, 6]);
+        assert_eq!(grid.column(1).copied().collect::<Vec<_>>(), vec![2, 5]);
+        assert_eq!(grid.rows().count(), 2);
+        assert_eq!(grid.iter().nth(4), Some(((1, 1), &5)));
+
+        assert_eq!(grid[Vec2::new(1, 0)], 2);
+        grid[(1, 1)] = 0;
+        grid.row_mut(0)[0] = 9;
+        assert_eq!(grid.cells(), &[9, 2, 3, 4, 0, 6]);
+    }
+
+    #[test]
+    fn neighbours() {
+        let grid = Grid::new(3, 3, ());
+        assert_eq!(
+            grid.neighbours4(1, 1).collect::<Vec<_>>(),
+            vec![(1, 0), (0, 1), (2, 1), (1, 2)]
+        );
+        assert_eq!(
+            grid.neighbours4(0, 0).collect::<Vec<_>>(),
+            vec![(1, 0), (0, 1)]
+        );
+        assert_eq!(grid.neighbours8(1, 1).count(), 8);
+        assert_eq!(
+            grid.neighbours8(2, 2).collect::<Vec<_>>(),
+            vec![(1, 1), (2, 1), (1, 2)]
+        );
+    }
+
+    #[test]
+    fn parse_and_render() {
+        let grid = digits("12\n34\n").unwrap();
+        let rendered = grid
+            .map(|d| d * 2)
+            .render(|&d| std::char::from_digit(d, 10).unwrap());
+        assert_eq!(rendered, "24\n68\n");
+
+        let err = digits("12\n3x\n").unwrap_err();
+        assert_eq!(
+            (err.line, err.column, err.expected.as_ref()),
+            (2, 2, "a digit")
+        );
+        let err = digits("12\n3\n").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 2));
+        assert_eq!(err.expected, "a row of 2 cells");
+        assert!(digits("").is_err());
+
+        let grid = Grid::parse_ragged(1, "1\n234", 0, "a digit", |c| c.to_digit(10)).unwrap();
+        assert_eq!(
+            grid.render(|&d| if d == 0 { ' ' } else { '#' }),
+            "#  \n###\n"
+        );
+    }
+}
@@ -0,0 +1,183 @@
+//! Loading of puzzle inputs from files, stdin or the inputs bundled in the binary.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+/// The directory puzzle inputs are read from, unless told otherwise.
+pub const DEFAULT_DIR: &str = "inputs";
+
+/// Where to read the puzzle inputs from.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub enum Source {
+    /// `inputs/dayNN.txt`, falling back to the embedded inputs when available.
+    #[default]
+    Default,
+    /// `dayNN.txt` in the given directory.
+    Dir(PathBuf),
+    /// The given file, for all the selected days.
+    File(PathBuf),
+    /// Standard input, for all the selected days.
+    Stdin,
+}
+
+impl Source {
+    /// Whether the source provides the same input to all the days.
+    pub fn is_single(&self) -> bool {
+        matches!(self, Source::File(_) | Source::Stdin)
+    }
+}
+
+/// The path of the input file for the given day in `dir`.
+pub fn day_path(dir: &Path, day: u8) -> PathBuf {
+    dir.join(format!("day{:02}.txt", day))
+}
+
+fn read_file(path: &Path) -> Result<String, String> {
+    fs::read_to_string(path).map_err(|err| format!("Unable to read {}: {}", path.display(), err))
+}
+
+/// Returns the puzzle input bundled in the binary for the given day.
+#[cfg(feature = "embedded-inputs")]
+pub fn embedded(day: u8) -> Option<String> {
+    Some(match day {
+        1 => crate::day01::get_input(),
+        2 => crate::day02::get_input(),
+        3 => crate::day03::get_input(),
+        4 => crate::day04::get_input(),
+        5 => crate::day05::get_input(),
+        6 => crate::day06::get_input(),
+        7 => crate::day07::get_input(),
+        8 => crate::day08::get_input(),
+        9 => crate::day09::get_input(),
+        10 => crate::day10::get_input(),
+        11 => crate::day11::get_input(),
+        12 => crate::day12::get_input(),
+        13 => crate::day13::get_input(),
+        14 => crate::day14::get_input(),
+        15 => crate::day15::get_input(),
+        16 => crate::day16::get_input(),
+        17 => crate::day17::get_input(),
+        18 => crate::day18::get_input(),
+        19 => crate::day19::get_input(),
+        20 => crate::day20::get_input(),
+        21 => crate::day21::get_input(),
+        22 => crate::day22::get_input(),
+        23 => crate::day23::get_input(),
+        24 => crate::day24::get_input(),
+        25 => crate::day25::get_input(),
+        _ => return None,
+    })
+}
+
+/// Built without the `embedded-inputs` feature, there's nothing to fall back to.
+#[cfg(not(feature = "embedded-inputs"))]
+pub fn embedded(_day: u8) -> Option<String> {
+    None
+}
+
+/// Loads the puzzle input for the given day from the source.
+pub fn load(source: &Source, day: u8) -> Result<String, String> {
+    match source {
+        Source::Default => {
+            let path = day_path(Path::new(DEFAULT_DIR), day);
+            if path.exists() {
+                read_file(&path)
+            } else {
+                embedded(day).ok_or_else(|| format!("No input found at {}", path.display()))
+            }
+        }
+        Source::Dir(dir) => read_file(&day_path(dir, day)),
+        Source::File(path) => read_file(path),
+        Source::Stdin => {
+            let mut result = String::new();
+            io::stdin()
+                .read_to_string(&mut result)
+                .map_err(|err| format!("Unable to read stdin: {}", err))?;
+            Ok(result)
+        }
+    }
+}
+
+/// Loads inputs on demand, making sure each day (and stdin) is only read once.
+pub struct Inputs {
+    source: Source,
+    cache: HashMap<u8, Result<String, String>>,
+}
+
+impl Inputs {
+    /// Creates a loader reading from the given source.
+    pub fn new(source: Source) -> Inputs {
+        Inputs {
+            source,
+            cache: HashMap::new(),
+        }
+    }
+
+    /// Returns the input for the given day, loading it on the first call.
+    pub fn get(&mut self, day: u8) -> Result<&str, String> {
+        // Single inputs are shared between all the days.
+        let key = if self.source.is_single() { 0 } else { day };
+        let source = &self.source;
+        match self.cache.entry(key).or_insert_with(|| load(source, day)) {
+            Ok(input) => Ok(input),
+            Err(err) => Err(err.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates an empty directory for the test to play around in.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("aoc2018-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn day_paths() {
+        assert_eq!(
+            day_path(Path::new("inputs"), 3),
+            Path::new("inputs").join("day03.txt")
+        );
+        assert_eq!(
+            day_path(Path::new("x"), 25),
+            Path::new("x").join("day25.txt")
+        );
+    }
+
+    #[test]
+    fn load_from_dir() {
+        let dir = temp_dir("dir");
+        fs::write(day_path(&dir, 1), "+1, +2").unwrap();
+
+        let source = Source::Dir(dir.clone());
+        assert_eq!(load(&source, 1).unwrap(), "+1, +2");
+        assert!(load(&source, 2).unwrap_err().contains("day02.txt"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn single_file_is_shared_between_days() {
+        let dir = temp_dir("file");
+        let path = dir.join("custom.txt");
+        fs::write(&path, "hello").unwrap();
+
+        let mut inputs = Inputs::new(Source::File(path));
+        assert_eq!(inputs.get(3).unwrap(), "hello");
+        assert_eq!(inputs.get(4).unwrap(), "hello");
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(feature = "embedded-inputs")]
+    #[test]
+    fn embedded_inputs() {
+        assert_eq!(embedded(14).unwrap(), "704321");
+        assert!(embedded(26).is_none());
+    }
+}
@@ -0,0 +1,63 @@
+//! Solutions for [Advent of Code 2018](https://adventofcode.com/2018).
+//!
+//! Every day lives in its own `dayNN` module, with a `parse_input` function and `part1`/`part2`
+//! functions for solving the puzzle. All the days implement the [`Solution`](solution::Solution)
+//! trait as well, so they can be handled the same way through [`solution::solvers`].
+//!
+//! ```
+//! use adventofcode2018::day01::Day01;
+//! use adventofcode2018::solution::Solution;
+//!
+//! let input = Day01::parse("+1, -2, +3, +1").unwrap();
+//! assert_eq!(Day01::part1(&input), 3);
+//! assert_eq!(Day01::part2(&input), 2);
+//! ```
+//!
+//! Puzzle inputs are loaded with the [`input`] module (after downloading them with [`fetch`]), and
+//! the [`runner`] and [`report`] modules run the solvers and write out the results. The helpers
+//! shared between the days live in [`parse`], [`grid`], [`geometry`] and [`cycle`], and the device
+//! of days 16, 19 and 21 in [`elfcode`].
+
+pub mod bench;
+pub mod cycle;
+#[cfg(feature = "tui")]
+pub mod dashboard;
+pub mod day01;
+pub mod day02;
+pub mod day03;
+pub mod day04;
+pub mod day05;
+pub mod day06;
+pub mod day07;
+pub mod day08;
+pub mod day09;
+pub mod day10;
+pub mod day11;
+pub mod day12;
+pub mod day13;
+pub mod day14;
+pub mod day15;
+pub mod day16;
+pub mod day17;
+pub mod day18;
+pub mod day19;
+pub mod day20;
+pub mod day21;
+pub mod day22;
+pub mod day23;
+pub mod day24;
+pub mod day25;
+pub mod elfcode;
+pub mod examples;
+pub mod fetch;
+pub mod generate;
+pub mod geometry;
+pub mod grid;
+pub mod input;
+pub mod parse;
+pub mod report;
+pub mod runner;
+#[cfg(feature = "serve")]
+pub mod serve;
+pub mod solution;
+pub mod verify;
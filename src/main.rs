@@ -1,390 +1,427 @@
-#[macro_use]
-extern crate prettytable;
-extern crate pathfinding;
-extern crate time;
+#[cfg(feature = "tui")]
+use adventofcode2018::dashboard;
+use adventofcode2018::elfcode::{self, Instruction};
+#[cfg(feature = "serve")]
+use adventofcode2018::serve;
+use adventofcode2018::{bench, examples, fetch, generate, input, report, runner, solution, verify};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process;
+use std::time::{Duration, Instant};
 
-use prettytable::{format, Table};
-
-mod day01;
-mod day02;
-mod day03;
-mod day04;
-mod day05;
-mod day06;
-mod day07;
-mod day08;
-mod day09;
-mod day10;
-mod day11;
-mod day12;
-mod day13;
-mod day14;
-mod day15;
-mod day16;
-mod day17;
-mod day18;
-mod day19;
-mod day20;
-mod day21;
-mod day22;
-mod day23;
-mod day24;
-mod day25;
+mod cli;
 
 fn main() {
-    let mut table = Table::new();
-    table.set_format(*format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
-    table.set_titles(row!["Day", "Part", "Result", "Duration"]);
-    let total_before = time::now();
-
-    {
-        let input = day01::parse_input(&day01::raw_input());
-        let before = time::now();
-        let result = day01::part1(&input);
-        let diff = time::now() - before;
-        table.add_row(row![1, 1, result, diff]);
-    }
-    {
-        let input = day01::parse_input(&day01::raw_input());
-        let before = time::now();
-        let result = day01::part2(&input);
-        let diff = time::now() - before;
-        table.add_row(row![1, 2, result, diff]);
-    }
-    {
-        let input = day02::get_input();
-        let before = time::now();
-        let result = day02::part1(&input);
-        let diff = time::now() - before;
-        table.add_row(row![2, 1, result, diff]);
-    }
-    {
-        let input = day02::get_input();
-        let before = time::now();
-        let result = day02::part2(&input);
-        let diff = time::now() - before;
-        table.add_row(row![2, 2, result, diff]);
-    }
-    {
-        let input = day03::parse_input(&day03::get_input());
-        let before = time::now();
-        let result = day03::part1(&input);
-        let diff = time::now() - before;
-        table.add_row(row![3, 1, result, diff]);
-    }
-    {
-        let input = day03::parse_input(&day03::get_input());
-        let before = time::now();
-        let result = day03::part2(&input);
-        let diff = time::now() - before;
-        table.add_row(row![3, 2, result, diff]);
-    }
-    {
-        let input = day04::parse_input(&day04::get_input());
-        let before = time::now();
-        let result = day04::part1(&input);
-        let diff = time::now() - before;
-        table.add_row(row![4, 1, result, diff]);
-    }
-    {
-        let input = day04::parse_input(&day04::get_input());
-        let before = time::now();
-        let result = day04::part2(&input);
-        let diff = time::now() - before;
-        table.add_row(row![4, 2, result, diff]);
-    }
-    {
-        let input = day05::get_input();
-        let before = time::now();
-        let result = day05::part1(&input).len();
-        let diff = time::now() - before;
-        table.add_row(row![5, 1, result, diff]);
-    }
-    {
-        let input = day05::get_input();
-        let before = time::now();
-        let result = day05::part2(&input);
-        let diff = time::now() - before;
-        table.add_row(row![5, 2, result, diff]);
-    }
-    {
-        let input = day06::get_input();
-        let before = time::now();
-        let result = day06::part1(&day06::parse_input(&input));
-        let diff = time::now() - before;
-        table.add_row(row![6, 1, result, diff]);
-    }
-    {
-        let input = day06::get_input();
-        let before = time::now();
-        let result = day06::part2(&day06::parse_input(&input), 10_000);
-        let diff = time::now() - before;
-        table.add_row(row![6, 2, result, diff]);
-    }
-    {
-        let input = day07::get_input();
-        let before = time::now();
-        let result = day07::part1(&mut day07::parse_input(&input));
-        let diff = time::now() - before;
-        table.add_row(row![7, 1, result, diff]);
-    }
-    {
-        let input = day07::get_input();
-        let before = time::now();
-        let result = day07::part2(&day07::parse_input_part2(&input), 5, 60);
-        let diff = time::now() - before;
-        table.add_row(row![7, 2, result, diff]);
-    }
-    {
-        let input = day08::get_input();
-        let before = time::now();
-        let result = day08::part1(&day08::parse_input(&input));
-        let diff = time::now() - before;
-        table.add_row(row![8, 1, result, diff]);
-    }
-    {
-        let input = day08::get_input();
-        let before = time::now();
-        let result = day08::part2(&day08::parse_input(&input));
-        let diff = time::now() - before;
-        table.add_row(row![8, 2, result, diff]);
-    }
-    {
-        let input = day09::get_input();
-        let before = time::now();
-        let result = day09::part1(input.0, input.1);
-        let diff = time::now() - before;
-        table.add_row(row![9, 1, result, diff]);
+    let mut args = std::env::args().skip(1).peekable();
+    if args.next_if(|arg| arg == "debug").is_some() {
+        run_debug(args);
+        return;
     }
-    {
-        let input = day09::get_input();
-        let before = time::now();
-        let result = day09::part2(input.0, input.1);
-        let diff = time::now() - before;
-        table.add_row(row![9, 2, result, diff]);
+    if args.next_if(|arg| arg == "decompile").is_some() {
+        run_decompile(args);
+        return;
     }
-    {
-        let input = day10::get_input();
-        let before = time::now();
-        let result = day10::part1(&day10::parse_input(&input));
-        let diff = time::now() - before;
-        table.add_empty_row();
-        table.add_row(row![10, 1, result.trim(), diff]);
-        table.add_empty_row();
+    if args.next_if(|arg| arg == "extract").is_some() {
+        run_extract(args);
+        return;
     }
-    {
-        let input = day10::get_input();
-        let before = time::now();
-        let result = day10::part2(&day10::parse_input(&input));
-        let diff = time::now() - before;
-        table.add_row(row![10, 2, result, diff]);
+    if args.next_if(|arg| arg == "fetch").is_some() {
+        run_fetch(args);
+        return;
     }
-    {
-        let input = day11::get_input();
-        let before = time::now();
-        let result = day11::part1(input);
-        let diff = time::now() - before;
-        table.add_row(row![11, 1, format!("{:?}", result), diff]);
+    if args.next_if(|arg| arg == "generate").is_some() {
+        run_generate(args);
+        return;
     }
-    {
-        let input = day11::get_input();
-        let before = time::now();
-        let result = day11::part2(input);
-        let diff = time::now() - before;
-        table.add_row(row![11, 2, format!("{:?}", result), diff]);
+    if args.next_if(|arg| arg == "serve").is_some() {
+        run_serve(args);
+        return;
     }
-    {
-        let (state, instructions) = day12::parse_input(&day12::get_input());
-        let before = time::now();
-        let result = day12::part1(&state, &instructions);
-        let diff = time::now() - before;
-        table.add_row(row![12, 1, format!("{:?}", result), diff]);
+    if args.next_if(|arg| arg == "trace").is_some() {
+        run_trace(args);
+        return;
     }
-    {
-        let (state, instructions) = day12::parse_input(&day12::get_input());
-        let before = time::now();
-        let result = day12::part2(&state, &instructions);
-        let diff = time::now() - before;
-        table.add_row(row![12, 2, format!("{:?}", result), diff]);
-    }
-    {
-        let (board, carts) = day13::parse_input(&day13::get_input());
-        let before = time::now();
-        let result = day13::part1(&board, &carts);
-        let diff = time::now() - before;
-        table.add_row(row![13, 1, format!("{:?}", result), diff]);
-    }
-    {
-        let (board, carts) = day13::parse_input(&day13::get_input());
-        let before = time::now();
-        let result = day13::part2(&board, &carts);
-        let diff = time::now() - before;
-        table.add_row(row![13, 2, format!("{:?}", result), diff]);
-    }
-    {
-        let before = time::now();
-        let result = day14::part1(day14::get_input());
-        let diff = time::now() - before;
-        table.add_row(row![14, 1, format!("{:?}", result), diff]);
-    }
-    {
-        let before = time::now();
-        let result = day14::part2(&format!("{}", day14::get_input()));
-        let diff = time::now() - before;
-        table.add_row(row![14, 2, format!("{:?}", result), diff]);
-    }
-    {
-        let input = day15::get_input();
-        let before = time::now();
-        let result = day15::part1(&input);
-        let diff = time::now() - before;
-        table.add_row(row![15, 1, result, diff]);
-    }
-    {
-        let input = day15::get_input();
-        let before = time::now();
-        let result = day15::part2(&input);
-        let diff = time::now() - before;
-        table.add_row(row![15, 2, result, diff]);
-    }
-    {
-        let mut input = day16::parse_input(&day16::get_input_part1());
-        let before = time::now();
-        let result = day16::part1(&mut input);
-        let diff = time::now() - before;
-        table.add_row(row![16, 1, result, diff]);
-    }
-    {
-        let mut input = day16::parse_input(&day16::get_input_part1());
-        let input2 = day16::get_input_part2();
-        let before = time::now();
-        let result = day16::part2(&mut input, &input2);
-        let diff = time::now() - before;
-        table.add_row(row![16, 2, result, diff]);
-    }
-    {
-        let mut grid = day17::Grid::from_input(&day17::get_input());
-        let before = time::now();
-        grid.flow();
-        let result = grid.part1();
-        let diff = time::now() - before;
-        table.add_row(row![17, 1, result, diff]);
-    }
-    {
-        let mut grid = day17::Grid::from_input(&day17::get_input());
-        let before = time::now();
-        grid.flow();
-        let result = grid.part2();
-        let diff = time::now() - before;
-        table.add_row(row![17, 2, result, diff]);
-    }
-    {
-        let input = day18::parse_input(&day18::get_input());
-        let before = time::now();
-        let result = day18::part1(&input);
-        let diff = time::now() - before;
-        table.add_row(row![18, 1, result, diff]);
-    }
-    {
-        let input = day18::parse_input(&day18::get_input());
-        let before = time::now();
-        let result = day18::part2(&input);
-        let diff = time::now() - before;
-        table.add_row(row![18, 2, result, diff]);
-    }
-    {
-        let (ip, instructions) = day19::parse_input(&day19::get_input());
-        let before = time::now();
-        let result = day19::part1(ip, &instructions);
-        let diff = time::now() - before;
-        table.add_row(row![19, 1, result, diff]);
-    }
-    {
-        let (ip, instructions) = day19::parse_input(&day19::get_input());
-        let before = time::now();
-        let result = day19::part2(ip, &instructions);
-        let diff = time::now() - before;
-        table.add_row(row![19, 2, result, diff]);
-    }
-    {
-        let map = day20::parse_input(&day20::get_input());
-        let before = time::now();
-        let result = day20::part1(&map);
-        let diff = time::now() - before;
-        table.add_row(row![20, 1, result, diff]);
+
+    let options = match cli::parse_args(args) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{}\n\n{}", err, cli::USAGE);
+            process::exit(2);
+        }
+    };
+    if options.help {
+        println!("{}", cli::USAGE);
+        return;
     }
-    {
-        let map = day20::parse_input(&day20::get_input());
-        let before = time::now();
-        let result = day20::part2(&map);
-        let diff = time::now() - before;
-        table.add_row(row![20, 2, result, diff]);
+
+    let solvers = solution::solvers()
+        .into_iter()
+        .filter(|s| options.selection.matches(s.day, s.part))
+        .collect::<Vec<_>>();
+
+    if options.list {
+        for solver in &solvers {
+            println!("Day {:02} part {}", solver.day, solver.part);
+        }
+        return;
     }
-    {
-        let (ip, instructions) = day19::parse_input(&day21::get_input());
-        let before = time::now();
-        let result = day21::part1(ip, &instructions);
-        let diff = time::now() - before;
-        table.add_row(row![21, 1, result, diff]);
+
+    let answers = options.verify.as_ref().map(|path| {
+        verify::Answers::load(path).unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            process::exit(1);
+        })
+    });
+
+    let mut inputs = input::Inputs::new(options.source.clone());
+    if let Some(bench_options) = &options.bench {
+        run_benchmarks(&solvers, &mut inputs, bench_options, options.timeout);
+        return;
     }
-    {
-        let (ip, instructions) = day19::parse_input(&day21::get_input());
-        let before = time::now();
-        let result = day21::part2(ip, &instructions);
-        let diff = time::now() - before;
-        table.add_row(row![21, 2, result, diff]);
+
+    let (mut records, total) = if options.tui {
+        match run_dashboard(&solvers, &options) {
+            Some(run) => run,
+            // Closed before all the parts finished, there's nothing to report.
+            None => return,
+        }
+    } else {
+        let before = Instant::now();
+        let records = runner::run_all(&solvers, &mut inputs, options.timeout, options.jobs);
+        (records, before.elapsed())
+    };
+    let mismatches = answers.map_or(0, |answers| verify::check(&mut records, &answers));
+
+    let stdout = io::stdout();
+    if let Err(err) = report::write(&records, total, options.format, &mut stdout.lock()) {
+        eprintln!("Unable to write the results: {}", err);
+        process::exit(1);
     }
-    {
-        let (depth, (tx, ty)) = day22::parse_input(&day22::get_input());
-        let before = time::now();
-        let result = day22::part1(depth, (tx, ty));
-        let diff = time::now() - before;
-        table.add_row(row![22, 1, result, diff]);
+    if mismatches > 0 {
+        eprintln!("{} answer(s) didn't match the expected ones", mismatches);
+        process::exit(1);
     }
-    {
-        let (depth, (tx, ty)) = day22::parse_input(&day22::get_input());
-        let before = time::now();
-        let result = day22::part2(depth, (tx, ty));
-        let diff = time::now() - before;
-        table.add_row(row![22, 2, result, diff]);
+}
+
+/// Loads and assembles an ElfCode program, exiting on errors.
+fn load_program(program: &cli::Program) -> (Option<usize>, Vec<Instruction>) {
+    let source = program
+        .file
+        .clone()
+        .map_or(input::Source::Default, input::Source::File);
+    let result = input::load(&source, program.day)
+        .and_then(|input| elfcode::assemble(program.day, &input).map_err(|err| err.to_string()));
+    result.unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        process::exit(1);
+    })
+}
+
+fn run_debug(args: impl Iterator<Item = String>) {
+    let options = cli::parse_debug_args(args).unwrap_or_else(|err| {
+        eprintln!("{}\n\n{}", err, cli::DEBUG_USAGE);
+        process::exit(2);
+    });
+    if options.help {
+        println!("{}", cli::DEBUG_USAGE);
+        return;
+    }
+    let (ip, program) = load_program(&options.program);
+    if let Err(err) = elfcode::debug::debug(ip, &program) {
+        eprintln!("{}", err);
+        process::exit(1);
     }
-    {
-        let nanobots = day23::parse_input(&day23::get_input());
-        let before = time::now();
-        let result = day23::part1(&nanobots);
-        let diff = time::now() - before;
-        table.add_row(row![23, 1, result, diff]);
+}
+
+fn run_decompile(args: impl Iterator<Item = String>) {
+    let options = cli::parse_decompile_args(args).unwrap_or_else(|err| {
+        eprintln!("{}\n\n{}", err, cli::DECOMPILE_USAGE);
+        process::exit(2);
+    });
+    if options.help {
+        println!("{}", cli::DECOMPILE_USAGE);
+        return;
+    }
+    let (ip, program) = load_program(&options.program);
+    if options.disassemble {
+        print!("{}", elfcode::decompile::disassemble(ip, &program));
+    } else {
+        print!("{}", elfcode::decompile::pseudo_code(ip, &program));
     }
-    {
-        let nanobots = day23::parse_input(&day23::get_input());
-        let before = time::now();
-        let result = day23::part2(&nanobots);
-        let diff = time::now() - before;
-        table.add_row(row![23, 2, result, diff]);
+}
+
+fn run_extract(args: impl Iterator<Item = String>) {
+    let options = cli::parse_extract_args(args).unwrap_or_else(|err| {
+        eprintln!("{}\n\n{}", err, cli::EXTRACT_USAGE);
+        process::exit(2);
+    });
+    if options.help {
+        println!("{}", cli::EXTRACT_USAGE);
+        return;
+    }
+    let fail = |err: String| -> ! {
+        eprintln!("{}", err);
+        process::exit(1);
+    };
+
+    let dir = Path::new(examples::FIXTURES_DIR);
+    if let Err(err) = fs::create_dir_all(dir) {
+        fail(format!("Unable to create {}: {}", dir.display(), err));
+    }
+    for page in &options.pages {
+        let html = fs::read_to_string(page)
+            .unwrap_or_else(|err| fail(format!("Unable to read {}: {}", page.display(), err)));
+        let (day, found) = examples::extract(&html)
+            .unwrap_or_else(|err| fail(format!("{}: {}", page.display(), err)));
+        if found.is_empty() {
+            eprintln!("{}: no examples found", page.display());
+            continue;
+        }
+        let path = examples::fixture_path(dir, day);
+        if path.exists() && !options.force {
+            fail(format!(
+                "{} already exists, use --force to overwrite it",
+                path.display()
+            ));
+        }
+        if let Err(err) = fs::write(&path, examples::to_fixture(day, &found)) {
+            fail(format!("Unable to write {}: {}", path.display(), err));
+        }
+        println!(
+            "Day {:02}: {} example(s) written to {}",
+            day,
+            found.len(),
+            path.display()
+        );
     }
-    {
-        let armies = day24::parse_input(&day24::get_input());
-        let before = time::now();
-        let result = day24::part1(&armies);
-        let diff = time::now() - before;
-        table.add_row(row![24, 1, result, diff]);
+
+    let days = examples::fixture_days(dir).unwrap_or_else(|err| fail(err));
+    if let Err(err) = fs::write(examples::TEST_MODULE, examples::test_module(&days)) {
+        fail(format!(
+            "Unable to write {}: {}",
+            examples::TEST_MODULE,
+            err
+        ));
+    }
+    println!(
+        "{} regenerated with {} day(s)",
+        examples::TEST_MODULE,
+        days.len()
+    );
+}
+
+fn run_fetch(args: impl Iterator<Item = String>) {
+    let options = cli::parse_fetch_args(args).unwrap_or_else(|err| {
+        eprintln!("{}\n\n{}", err, cli::FETCH_USAGE);
+        process::exit(2);
+    });
+    if options.help {
+        println!("{}", cli::FETCH_USAGE);
+        return;
+    }
+    let session = options
+        .session
+        .or_else(|| std::env::var("AOC_SESSION").ok())
+        .unwrap_or_else(|| {
+            eprintln!("fetch requires a --session or $AOC_SESSION");
+            process::exit(2);
+        });
+
+    let mut fetcher =
+        fetch::Fetcher::new(&session, &options.output_dir).base_url(&options.base_url);
+    for &day in &options.days {
+        match fetcher.fetch(day) {
+            Ok(fetch::Fetched::Cached) => println!("Day {:02}: already downloaded", day),
+            Ok(fetch::Fetched::Downloaded) => println!("Day {:02}: downloaded", day),
+            // Stop at the first error, there's no point in hammering the server.
+            Err(err) => {
+                eprintln!("{}", err);
+                process::exit(1);
+            }
+        }
     }
-    {
-        let armies = day24::parse_input(&day24::get_input());
-        let before = time::now();
-        let result = day24::part2(&armies);
-        let diff = time::now() - before;
-        table.add_row(row![24, 2, result, diff]);
+}
+
+fn run_generate(args: impl Iterator<Item = String>) {
+    let options = cli::parse_generate_args(args).unwrap_or_else(|err| {
+        eprintln!("{}\n\n{}", err, cli::GENERATE_USAGE);
+        process::exit(2);
+    });
+    if options.help {
+        println!("{}", cli::GENERATE_USAGE);
+        return;
+    }
+    if let Some(dir) = &options.output_dir {
+        if let Err(err) = fs::create_dir_all(dir) {
+            eprintln!("Unable to create {}: {}", dir.display(), err);
+            process::exit(1);
+        }
     }
-    {
-        let input = day25::parse_input(&day25::get_input());
-        let before = time::now();
-        let result = day25::part1(&input);
-        let diff = time::now() - before;
-        table.add_row(row![25, 1, result, diff]);
+
+    for &day in &options.days {
+        let input = generate::generate(day, options.seed, options.size).unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            process::exit(1);
+        });
+        match &options.output_dir {
+            Some(dir) => {
+                let path = input::day_path(dir, day);
+                if let Err(err) = fs::write(&path, input) {
+                    eprintln!("Unable to write {}: {}", path.display(), err);
+                    process::exit(1);
+                }
+            }
+            None => print!("{}", input),
+        }
     }
+}
 
-    table.add_row(row!["", "", "Total duration:", time::now() - total_before]);
+#[cfg(feature = "tui")]
+fn run_dashboard(
+    solvers: &[solution::Solver],
+    options: &cli::Options,
+) -> Option<(Vec<runner::Record>, std::time::Duration)> {
+    let history = options
+        .history
+        .iter()
+        .map(|path| {
+            let name = path.file_stem().unwrap_or_default().to_string_lossy();
+            bench::load_baseline(path).map(|baseline| (name.into_owned(), baseline))
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            process::exit(1);
+        });
+    let source = options.source.clone();
+    dashboard::run(solvers, source, options.timeout, options.jobs, history).unwrap_or_else(|err| {
+        eprintln!("Unable to show the dashboard: {}", err);
+        process::exit(1);
+    })
+}
+
+/// Built without the `tui` feature, there's no dashboard to show.
+#[cfg(not(feature = "tui"))]
+fn run_dashboard(
+    _solvers: &[solution::Solver],
+    _options: &cli::Options,
+) -> Option<(Vec<runner::Record>, std::time::Duration)> {
+    eprintln!("--tui needs the `tui` feature, ie. `cargo run --features tui -- --tui`");
+    process::exit(2);
+}
 
-    table.printstd();
+#[cfg(feature = "serve")]
+fn run_serve(args: impl Iterator<Item = String>) {
+    let options = cli::parse_serve_args(args).unwrap_or_else(|err| {
+        eprintln!("{}\n\n{}", err, cli::SERVE_USAGE);
+        process::exit(2);
+    });
+    if options.help {
+        println!("{}", cli::SERVE_USAGE);
+        return;
+    }
+    let server = serve::Server::bind(&options.addr, options.config).unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        process::exit(1);
+    });
+    eprintln!("Listening on http://{}", server.addr());
+    server.run();
+}
+
+/// Built without the `serve` feature, there's no server to run.
+#[cfg(not(feature = "serve"))]
+fn run_serve(_args: impl Iterator<Item = String>) {
+    eprintln!("serve needs the `serve` feature, ie. `cargo run --features serve -- serve`");
+    process::exit(2);
+}
+
+fn run_trace(args: impl Iterator<Item = String>) {
+    let options = cli::parse_trace_args(args).unwrap_or_else(|err| {
+        eprintln!("{}\n\n{}", err, cli::TRACE_USAGE);
+        process::exit(2);
+    });
+    if options.help {
+        println!("{}", cli::TRACE_USAGE);
+        return;
+    }
+    let (ip, program) = load_program(&options.program);
+    let mut registers = [0; elfcode::REGISTERS];
+    registers[0] = options.r0;
+    let mut cpu = elfcode::Cpu::new(ip, &program[..]).registers_set_to(registers);
+    let mut tracer = elfcode::trace::Tracer::new().sample_every(options.every);
+    if let Some(last) = options.last {
+        tracer = tracer.keep_last(last);
+    }
+    let result = match &options.output {
+        Some(path) => fs::File::create(path)
+            .and_then(|file| {
+                let mut trace = io::BufWriter::new(file);
+                let halted = tracer.run(&mut cpu, options.limit, &mut trace)?;
+                io::Write::flush(&mut trace)?;
+                Ok(halted)
+            })
+            .map_err(|err| format!("Unable to write {}: {}", path.display(), err)),
+        None => tracer
+            .run(&mut cpu, options.limit, io::sink())
+            .map_err(|err| err.to_string()),
+    };
+    let halted = result.unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        process::exit(1);
+    });
+    if halted {
+        println!("The program halted after {} instructions\n", cpu.executed());
+    } else {
+        println!(
+            "Stopped after {} instructions, see --limit\n",
+            cpu.executed()
+        );
+    }
+    println!("{}", tracer.profile().report(ip, &program));
+}
+
+fn run_benchmarks(
+    solvers: &[solution::Solver],
+    inputs: &mut input::Inputs,
+    options: &cli::Bench,
+    timeout: Option<Duration>,
+) {
+    let baseline = options.baseline.as_ref().map(|path| {
+        bench::load_baseline(path).unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            process::exit(1);
+        })
+    });
+
+    let results = bench::bench_all(solvers, inputs, options.warmup, options.runs, timeout);
+    let stdout = io::stdout();
+    let regressions = bench::write_table(
+        &results,
+        baseline.as_deref(),
+        options.threshold,
+        &mut stdout.lock(),
+    )
+    .unwrap_or_else(|err| {
+        eprintln!("Unable to write the results: {}", err);
+        process::exit(1);
+    });
+
+    if let Some(path) = &options.save_baseline {
+        let benchmarks = results
+            .into_iter()
+            .filter_map(Result::ok)
+            .collect::<Vec<_>>();
+        if let Err(err) = bench::save_baseline(path, &benchmarks) {
+            eprintln!("{}", err);
+            process::exit(1);
+        }
+    }
+    if regressions > 0 {
+        eprintln!(
+            "{} part(s) regressed by more than {}%",
+            regressions, options.threshold
+        );
+        process::exit(1);
+    }
 }
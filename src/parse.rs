@@ -0,0 +1,139 @@
+//! Helpers for parsing the puzzle inputs, keeping track of where things went wrong.
+
+use crate::solution::ParseError;
+use regex::{Captures, Regex};
+use std::convert::TryInto;
+use std::str::FromStr;
+
+/// A line of a puzzle input.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Line<'a> {
+    /// The day of the puzzle.
+    pub day: u8,
+    /// The line number, starting at 1.
+    pub number: usize,
+    /// The contents of the line.
+    pub text: &'a str,
+}
+
+/// The lines of the input, numbered from 1.
+pub fn lines(day: u8, input: &str) -> impl Iterator<Item = Line<'_>> {
+    input.lines().enumerate().map(move |(i, text)| Line {
+        day,
+        number: i + 1,
+        text,
+    })
+}
+
+/// The first line of the input, or an error when the input is empty.
+pub fn first_line<'a>(day: u8, input: &'a str, expected: &str) -> Result<Line<'a>, ParseError> {
+    lines(day, input)
+        .next()
+        .ok_or_else(|| ParseError::new(day, 1, 1, expected))
+}
+
+/// Parses the value given to a command line option or a debugger command as a number.
+pub fn parse_number<T: FromStr>(option: &str, input: &str) -> Result<T, String> {
+    input
+        .trim()
+        .parse()
+        .map_err(|_| format!("Invalid value for {}: {}, expected a number", option, input))
+}
+
+/// An error at the end of the input, for when there should have been more of it.
+pub fn unexpected_end(day: u8, input: &str, expected: &str) -> ParseError {
+    let line = input.lines().count().max(1);
+    let column = input.lines().last().map_or(0, |l| l.chars().count()) + 1;
+    ParseError::new(day, line, column, expected)
+}
+
+impl<'a> Line<'a> {
+    /// An error at the given column, starting at 1.
+    pub fn error_at(&self, column: usize, expected: impl Into<String>) -> ParseError {
+        ParseError {
+            text: Some(self.text.to_owned()),
+            ..ParseError::new(self.day, self.number, column, expected)
+        }
+    }
+
+    /// An error at the position of `part`, which should be a slice of the line.
+    pub fn error(&self, part: &str, expected: impl Into<String>) -> ParseError {
+        self.error_at(self.column(part), expected)
+    }
+
+    /// The column `part` starts at, or the start of the line when it isn't a slice of it.
+    fn column(&self, part: &str) -> usize {
+        let start = self.text.as_ptr() as usize;
+        match (part.as_ptr() as usize).checked_sub(start) {
+            Some(offset) if offset <= self.text.len() => self.text[..offset].chars().count() + 1,
+            _ => 1,
+        }
+    }
+
+    /// Parses `part` (a slice of the line), ignoring surrounding whitespace.
+    pub fn parse<T: FromStr>(&self, part: &str, expected: &str) -> Result<T, ParseError> {
+        part.trim().parse().map_err(|_| self.error(part, expected))
+    }
+
+    /// Matches the line against the regex.
+    pub fn captures(&self, regex: &Regex, expected: &str) -> Result<Captures<'a>, ParseError> {
+        regex
+            .captures(self.text)
+            .ok_or_else(|| self.error_at(1, expected))
+    }
+
+    /// Splits the line on the separator, expecting exactly `N` fields.
+    pub fn fields<const N: usize>(
+        &self,
+        separator: &str,
+        expected: &str,
+    ) -> Result<[&'a str; N], ParseError> {
+        let fields = self.text.split(separator).collect::<Vec<_>>();
+        fields.try_into().map_err(|_| self.error_at(1, expected))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_positions() {
+        let input = "1, 2\n3, x\n";
+        let line = lines(6, input).nth(1).unwrap();
+        assert_eq!(line.number, 2);
+        let [a, b] = line.fields(", ", "two numbers").unwrap();
+        assert_eq!(line.parse::<i32>(a, "a number"), Ok(3));
+
+        let err = line.parse::<i32>(b, "a number").unwrap_err();
+        assert_eq!((err.day, err.line, err.column), (6, 2, 4));
+        assert_eq!(
+            err.to_string(),
+            "day 6, line 2, column 4: expected a number\n    3, x\n       ^"
+        );
+        assert_eq!(line.error("elsewhere", "nothing").column, 1);
+        assert!(line.fields::<3>(", ", "three numbers").is_err());
+    }
+
+    #[test]
+    fn missing_input() {
+        let err = first_line(9, "", "the rules").unwrap_err();
+        assert_eq!((err.line, err.column, err.text), (1, 1, None));
+        let err = unexpected_end(22, "depth: 1\n", "the target");
+        assert_eq!((err.line, err.column), (1, 9));
+        assert_eq!(
+            err.to_string(),
+            "day 22, line 1, column 9: expected the target"
+        );
+    }
+
+    #[test]
+    fn captures() {
+        let regex = Regex::new(r"^#(\d+) @ (\d+)$").unwrap();
+        let line = lines(3, "#12 @ 3").next().unwrap();
+        let caps = line.captures(&regex, "a claim").unwrap();
+        assert_eq!(line.error(caps.get(2).unwrap().as_str(), "x").column, 7);
+        let line = lines(3, "#12 @").next().unwrap();
+        assert_eq!(line.captures(&regex, "a claim").unwrap_err().column, 1);
+    }
+}
@@ -0,0 +1,392 @@
+//! Writing the results of a run in the various output formats.
+
+use crate::runner::{nanos, Record, Status};
+use prettytable::{format, row, Cell, Table};
+use serde::Serialize;
+use std::io::{self, Write};
+use std::str::FromStr;
+use std::time::Duration;
+
+/// The supported output formats.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub enum Format {
+    /// A pretty table for humans.
+    #[default]
+    Table,
+    /// The records, with the totals.
+    Json,
+    /// A header and a line per record, followed by the totals.
+    Csv,
+    /// A markdown table.
+    Markdown,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Format, String> {
+        match input {
+            "table" => Ok(Format::Table),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            "markdown" | "md" => Ok(Format::Markdown),
+            _ => Err(format!(
+                "Invalid format: {}, expected table, json, csv or markdown",
+                input
+            )),
+        }
+    }
+}
+
+/// The time spent on all the parts together, which exceeds the wall-clock time when running in
+/// parallel.
+fn cpu_time(records: &[Record]) -> Duration {
+    records.iter().map(Record::total_time).sum()
+}
+
+/// The text for the result column, the answer or what went wrong.
+pub(crate) fn result_text(record: &Record) -> String {
+    match (record.status, &record.answer, &record.error) {
+        (Status::Ok, Some(answer), _) => answer.to_owned(),
+        (Status::ParseError, _, Some(error)) => format!("Parse error: {}", error),
+        (Status::Timeout, _, Some(error)) => format!("TIMEOUT: {}", error),
+        (Status::Panic, _, Some(error)) => format!("PANIC: {}", error),
+        (_, _, error) => error.clone().unwrap_or_default(),
+    }
+}
+
+/// Whether the records were verified against an answers file.
+fn is_verified(records: &[Record]) -> bool {
+    records.iter().any(|record| record.correct.is_some())
+}
+
+/// The verification mark, including the expected answer when it's short enough.
+fn verdict_text(record: &Record) -> String {
+    match (record.correct, &record.expected) {
+        (Some(true), _) => "✔".to_owned(),
+        (Some(false), Some(expected)) if !expected.trim().contains('\n') => {
+            format!("✘ expected {}", expected.trim())
+        }
+        (Some(false), _) => "✘".to_owned(),
+        (None, _) => "?".to_owned(),
+    }
+}
+
+fn write_table(records: &[Record], total: Duration, out: &mut dyn Write) -> io::Result<()> {
+    let verified = is_verified(records);
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
+    let mut titles = row!["Day", "Part", "Result", "Parse", "Solve"];
+    if verified {
+        titles.add_cell(Cell::new("Verified"));
+    }
+    table.set_titles(titles);
+    for record in records {
+        let result = result_text(record);
+        let mut row = row![
+            record.day,
+            record.part,
+            result,
+            format!("{:?}", record.parse_time),
+            format!("{:?}", record.solve_time)
+        ];
+        if verified {
+            row.add_cell(Cell::new(&verdict_text(record)));
+        }
+        // Multi-line results (ie. day 10) gets some room to breathe.
+        if result.contains('\n') {
+            table.add_empty_row();
+            table.add_row(row);
+            table.add_empty_row();
+        } else {
+            table.add_row(row);
+        }
+    }
+    table.add_row(row!["", "", "Wall-clock time:", "", format!("{:?}", total)]);
+    table.add_row(row![
+        "",
+        "",
+        "Sum of parts:",
+        "",
+        format!("{:?}", cpu_time(records))
+    ]);
+    table.print(out)?;
+    Ok(())
+}
+
+/// The JSON output, with the totals of the table in nanoseconds.
+#[derive(Serialize)]
+struct Report<'a> {
+    records: &'a [Record],
+    #[serde(rename = "wall_clock_ns", with = "nanos")]
+    wall_clock: Duration,
+    #[serde(rename = "sum_of_parts_ns", with = "nanos")]
+    sum_of_parts: Duration,
+}
+
+fn write_json(records: &[Record], total: Duration, out: &mut dyn Write) -> io::Result<()> {
+    let report = Report {
+        records,
+        wall_clock: total,
+        sum_of_parts: cpu_time(records),
+    };
+    serde_json::to_writer_pretty(&mut *out, &report)?;
+    writeln!(out)
+}
+
+/// Quotes the field when needed, as described in RFC 4180.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+/// The totals are last, as rows with only a status and a solve time like in the table.
+fn write_csv(records: &[Record], total: Duration, out: &mut dyn Write) -> io::Result<()> {
+    let verified = is_verified(records);
+    write!(out, "day,part,status,answer,error,parse_ns,solve_ns")?;
+    writeln!(out, "{}", if verified { ",expected,correct" } else { "" })?;
+    for record in records {
+        write!(
+            out,
+            "{},{},{},{},{},{},{}",
+            record.day,
+            record.part,
+            serde_json::to_value(record.status)?
+                .as_str()
+                .unwrap_or_default(),
+            csv_field(record.answer.as_deref().unwrap_or_default()),
+            csv_field(record.error.as_deref().unwrap_or_default()),
+            record.parse_time.as_nanos(),
+            record.solve_time.as_nanos()
+        )?;
+        if verified {
+            write!(
+                out,
+                ",{},{}",
+                csv_field(record.expected.as_deref().unwrap_or_default()),
+                record.correct.map(|c| c.to_string()).unwrap_or_default()
+            )?;
+        }
+        writeln!(out)?;
+    }
+    let empty = if verified { ",," } else { "" };
+    writeln!(out, ",,wall_clock,,,,{}{}", total.as_nanos(), empty)?;
+    writeln!(
+        out,
+        ",,sum_of_parts,,,,{}{}",
+        cpu_time(records).as_nanos(),
+        empty
+    )
+}
+
+/// Escapes a table cell, markdown tables can't span multiple lines.
+fn markdown_cell(cell: &str) -> String {
+    cell.trim_end()
+        .replace('|', "\\|")
+        .replace("\r\n", "<br>")
+        .replace('\n', "<br>")
+}
+
+fn write_markdown(records: &[Record], total: Duration, out: &mut dyn Write) -> io::Result<()> {
+    let verified = is_verified(records);
+    if verified {
+        writeln!(out, "| Day | Part | Result | Parse | Solve | Verified |")?;
+        writeln!(out, "| --: | ---: | ------ | ----: | ----: | -------- |")?;
+    } else {
+        writeln!(out, "| Day | Part | Result | Parse | Solve |")?;
+        writeln!(out, "| --: | ---: | ------ | ----: | ----: |")?;
+    }
+    for record in records {
+        let result = result_text(record);
+        // Keep ASCII art aligned by using a monospace font.
+        let result = if result.contains('\n') {
+            format!("<code>{}</code>", markdown_cell(&result))
+        } else {
+            markdown_cell(&result)
+        };
+        write!(
+            out,
+            "| {} | {} | {} | {:?} | {:?} |",
+            record.day, record.part, result, record.parse_time, record.solve_time
+        )?;
+        if verified {
+            write!(out, " {} |", markdown_cell(&verdict_text(record)))?;
+        }
+        writeln!(out)?;
+    }
+    writeln!(out, "| | | **Wall-clock time** | | {:?} |", total)?;
+    writeln!(out, "| | | **Sum of parts** | | {:?} |", cpu_time(records))
+}
+
+/// Writes the records in the given format, `total` is the wall-clock duration of the run.
+///
+/// Every format ends with both `total` and the sum of the time spent on every part.
+pub fn write(
+    records: &[Record],
+    total: Duration,
+    format: Format,
+    out: &mut dyn Write,
+) -> io::Result<()> {
+    match format {
+        Format::Table => write_table(records, total, out),
+        Format::Json => write_json(records, total, out),
+        Format::Csv => write_csv(records, total, out),
+        Format::Markdown => write_markdown(records, total, out),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records() -> Vec<Record> {
+        vec![
+            Record {
+                day: 10,
+                part: 1,
+                status: Status::Ok,
+                answer: Some("#..#\n|\"#,".to_owned()),
+                error: None,
+                expected: None,
+                correct: None,
+                parse_time: Duration::from_nanos(1200),
+                solve_time: Duration::from_nanos(3400),
+            },
+            Record {
+                day: 10,
+                part: 2,
+                status: Status::InputError,
+                answer: None,
+                error: Some("No input".to_owned()),
+                expected: None,
+                correct: None,
+                parse_time: Duration::default(),
+                solve_time: Duration::default(),
+            },
+        ]
+    }
+
+    fn write_records(records: &[Record], format: Format) -> String {
+        let mut out = vec![];
+        write(records, Duration::from_micros(5), format, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    fn output(format: Format) -> String {
+        write_records(&records(), format)
+    }
+
+    fn verified() -> Vec<Record> {
+        let mut records = records();
+        records[0].expected = records[0].answer.clone();
+        records[0].correct = Some(true);
+        records[1].expected = Some("42".to_owned());
+        records[1].correct = Some(false);
+        records
+    }
+
+    #[test]
+    fn verification() {
+        let output = write_records(&verified(), Format::Table);
+        assert!(output
+            .lines()
+            .nth(1)
+            .unwrap()
+            .ends_with("| Verified      |"));
+        assert!(output.contains("| 0ns   | 0ns   | ✘ expected 42 |"));
+
+        let output = write_records(&verified(), Format::Csv);
+        let lines = output.lines().collect::<Vec<_>>();
+        assert!(lines[0].ends_with(",solve_ns,expected,correct"));
+        assert_eq!(lines[4], "10,2,input_error,,No input,0,0,42,false");
+        assert_eq!(lines[6], ",,sum_of_parts,,,,4600,,");
+
+        let output = write_records(&verified(), Format::Markdown);
+        assert!(output.contains("| 0ns | 0ns | ✘ expected 42 |"));
+
+        let output = write_records(&verified(), Format::Json);
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(json["records"][0]["correct"], true);
+        assert_eq!(json["records"][1]["expected"], "42");
+        let json: serde_json::Value =
+            serde_json::from_str(&write_records(&records(), Format::Json)).unwrap();
+        assert!(json["records"][0].get("correct").is_none());
+    }
+
+    #[test]
+    fn parse_format() {
+        assert_eq!("json".parse(), Ok(Format::Json));
+        assert_eq!("md".parse(), Ok(Format::Markdown));
+        assert!("xml".parse::<Format>().is_err());
+    }
+
+    #[test]
+    fn table() {
+        let output = output(Format::Table);
+        let lines = output.lines().collect::<Vec<_>>();
+        assert_eq!(
+            lines[1],
+            "| Day | Part | Result           | Parse | Solve |"
+        );
+        assert_eq!(
+            lines[4],
+            "| 10  | 1    | #..#             | 1.2µs | 3.4µs |"
+        );
+        assert_eq!(
+            lines[5],
+            "|     |      | |\"#,             |       |       |"
+        );
+        assert_eq!(
+            lines[8],
+            "|     |      | Wall-clock time: |       | 5µs   |"
+        );
+        assert_eq!(
+            lines[9],
+            "|     |      | Sum of parts:    |       | 4.6µs |"
+        );
+    }
+
+    #[test]
+    fn json() {
+        let json: serde_json::Value = serde_json::from_str(&output(Format::Json)).unwrap();
+        let records = &json["records"];
+        assert_eq!(records[0]["day"], 10);
+        assert_eq!(records[0]["answer"], "#..#\n|\"#,");
+        assert_eq!(records[0]["parse_ns"], 1200);
+        assert_eq!(records[0]["solve_ns"], 3400);
+        assert_eq!(records[1]["status"], "input_error");
+        assert_eq!(records[1]["answer"], serde_json::Value::Null);
+        assert_eq!(records[1]["error"], "No input");
+        assert_eq!(json["wall_clock_ns"], 5000);
+        assert_eq!(json["sum_of_parts_ns"], 4600);
+    }
+
+    #[test]
+    fn csv() {
+        assert_eq!(
+            output(Format::Csv),
+            "day,part,status,answer,error,parse_ns,solve_ns
+10,1,ok,\"#..#\n|\"\"#,\",,1200,3400
+10,2,input_error,,No input,0,0
+,,wall_clock,,,,5000
+,,sum_of_parts,,,,4600
+"
+        );
+    }
+
+    #[test]
+    fn markdown() {
+        let output = output(Format::Markdown);
+        let lines = output.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 6);
+        assert_eq!(
+            lines[2],
+            "| 10 | 1 | <code>#..#<br>\\|\"#,</code> | 1.2µs | 3.4µs |"
+        );
+        assert_eq!(lines[3], "| 10 | 2 | No input | 0ns | 0ns |");
+        assert_eq!(lines[5], "| | | **Sum of parts** | | 4.6µs |");
+    }
+}
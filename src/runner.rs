@@ -0,0 +1,342 @@
+//! Running the solvers and collecting the results.
+//!
+//! Every solver runs on a thread of its own, so a panic or a solver that never finishes only
+//! affects its own record instead of the whole run.
+
+use crate::input::Inputs;
+use crate::solution::{Outcome, ParseError, Solver};
+use serde::Serialize;
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+/// How long a solver may run before giving up on it, unless told otherwise.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// The stack size of the solver threads, some of the solutions recurse quite deeply.
+pub(crate) const STACK_SIZE: usize = 64 * 1024 * 1024;
+
+/// How running a solver went.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    /// The part was solved.
+    Ok,
+    /// The input could not be loaded.
+    InputError,
+    /// The input could not be parsed.
+    ParseError,
+    /// The solver didn't finish in time.
+    Timeout,
+    /// The solver panicked.
+    Panic,
+}
+
+/// The result of running a single day/part.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct Record {
+    /// The day of the puzzle.
+    pub day: u8,
+    /// The part of the puzzle.
+    pub part: u8,
+    /// How it went.
+    pub status: Status,
+    /// The answer, if the part was solved.
+    pub answer: Option<String>,
+    /// What went wrong, if the part wasn't solved.
+    pub error: Option<String>,
+    /// The expected answer, when verifying against an answers file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected: Option<String>,
+    /// Whether the answer was the expected one, when known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub correct: Option<bool>,
+    /// The time spent parsing the input.
+    #[serde(rename = "parse_ns", with = "nanos")]
+    pub parse_time: Duration,
+    /// The time spent solving the part.
+    #[serde(rename = "solve_ns", with = "nanos")]
+    pub solve_time: Duration,
+}
+
+/// (De)serializes durations as a number of nanoseconds.
+pub(crate) mod nanos {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(duration.as_nanos() as u64)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        u64::deserialize(deserializer).map(Duration::from_nanos)
+    }
+}
+
+impl Record {
+    fn failed(solver: &Solver, status: Status, error: String) -> Record {
+        Record {
+            day: solver.day,
+            part: solver.part,
+            status,
+            answer: None,
+            error: Some(error),
+            expected: None,
+            correct: None,
+            parse_time: Duration::default(),
+            solve_time: Duration::default(),
+        }
+    }
+
+    /// The time spent parsing and solving.
+    pub fn total_time(&self) -> Duration {
+        self.parse_time + self.solve_time
+    }
+}
+
+/// Extracts the message of a panic payload.
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        (*message).to_owned()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_owned()
+    }
+}
+
+/// Runs the solver on a separate thread, catching panics and giving up after the timeout.
+///
+/// A thread that timed out can't be stopped, it's left running in the background. `held` is kept
+/// by the thread until the solver finishes.
+fn run_isolated<T: Send + 'static>(
+    solver: &Solver,
+    input: &str,
+    timeout: Option<Duration>,
+    held: T,
+) -> Result<Result<Outcome, ParseError>, (Status, String)> {
+    let (sender, receiver) = mpsc::channel();
+    let solver = *solver;
+    let input = input.to_owned();
+    thread::Builder::new()
+        .name(format!("day{:02}-part{}", solver.day, solver.part))
+        .stack_size(STACK_SIZE)
+        .spawn(move || {
+            let result = panic::catch_unwind(AssertUnwindSafe(|| solver.run(&input)));
+            drop(held);
+            // Nobody is listening anymore when the run timed out.
+            let _ = sender.send(result.map_err(|payload| panic_message(&*payload)));
+        })
+        .map_err(|err| (Status::Panic, format!("Unable to start a thread: {}", err)))?;
+
+    let result = match timeout {
+        Some(timeout) => receiver.recv_timeout(timeout),
+        None => receiver.recv().map_err(RecvTimeoutError::from),
+    };
+    match result {
+        Ok(Ok(result)) => Ok(result),
+        Ok(Err(message)) => Err((Status::Panic, message)),
+        Err(RecvTimeoutError::Timeout) => Err((
+            Status::Timeout,
+            format!("no answer after {:?}", timeout.unwrap_or_default()),
+        )),
+        Err(RecvTimeoutError::Disconnected) => {
+            Err((Status::Panic, "the solver thread died".to_owned()))
+        }
+    }
+}
+
+/// Runs the solver on the input, or records why the input is missing.
+///
+/// Panics are caught and the solver is given up on after `timeout`, when given.
+pub fn run(solver: &Solver, input: Result<&str, String>, timeout: Option<Duration>) -> Record {
+    run_holding(solver, input, timeout, ())
+}
+
+/// Runs the solver like [`run`], dropping `held` once the solver finished, even when it was given
+/// up on. Used to keep track of the solvers still running in the background.
+pub fn run_holding<T: Send + 'static>(
+    solver: &Solver,
+    input: Result<&str, String>,
+    timeout: Option<Duration>,
+    held: T,
+) -> Record {
+    let input = match input {
+        Ok(input) => input,
+        Err(err) => return Record::failed(solver, Status::InputError, err),
+    };
+    match run_isolated(solver, input, timeout, held) {
+        Ok(Ok(outcome)) => Record {
+            day: solver.day,
+            part: solver.part,
+            status: Status::Ok,
+            answer: Some(outcome.answer),
+            error: None,
+            expected: None,
+            correct: None,
+            parse_time: outcome.parse_time,
+            solve_time: outcome.solve_time,
+        },
+        Ok(Err(err)) => Record::failed(solver, Status::ParseError, err.to_string()),
+        Err((status, err)) => Record::failed(solver, status, err),
+    }
+}
+
+/// The progress of [`run_all_observed`], by the index of the solver.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Progress<'a> {
+    Started(usize),
+    Finished(usize, &'a Record),
+}
+
+/// Runs all the solvers, loading the inputs as needed.
+///
+/// With more than a single job the solvers run on a pool of `jobs` threads, the records are
+/// returned in the order of the solvers either way.
+pub fn run_all(
+    solvers: &[Solver],
+    inputs: &mut Inputs,
+    timeout: Option<Duration>,
+    jobs: usize,
+) -> Vec<Record> {
+    run_all_observed(solvers, inputs, timeout, jobs, &|_| {})
+}
+
+/// Runs all the solvers like [`run_all`], telling `observer` when each of them starts and
+/// finishes.
+pub fn run_all_observed(
+    solvers: &[Solver],
+    inputs: &mut Inputs,
+    timeout: Option<Duration>,
+    jobs: usize,
+    observer: &(dyn Fn(Progress) + Sync),
+) -> Vec<Record> {
+    let run_observed = |i: usize, input: Result<&str, String>| {
+        observer(Progress::Started(i));
+        let record = run(&solvers[i], input, timeout);
+        observer(Progress::Finished(i, &record));
+        record
+    };
+    if jobs <= 1 {
+        return (0..solvers.len())
+            .map(|i| run_observed(i, inputs.get(solvers[i].day)))
+            .collect();
+    }
+
+    let inputs = solvers
+        .iter()
+        .map(|solver| inputs.get(solver.day).map(str::to_owned))
+        .collect::<Vec<_>>();
+    let next = AtomicUsize::new(0);
+    let records = Mutex::new(vec![None; solvers.len()]);
+    thread::scope(|scope| {
+        for _ in 0..jobs.min(solvers.len()) {
+            scope.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                if i >= solvers.len() {
+                    break;
+                }
+                let record = run_observed(i, inputs[i].as_deref().map_err(Clone::clone));
+                records.lock().unwrap()[i] = Some(record);
+            });
+        }
+    });
+    records
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .map(|record| record.expect("every solver has been run"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::Source;
+    use crate::solution::solvers;
+    use std::path::PathBuf;
+
+    #[test]
+    fn run_solver() {
+        let record = run(&solvers()[0], Ok("+1, +1, -2"), None);
+        assert_eq!(record.status, Status::Ok);
+        assert_eq!(record.answer, Some("0".to_owned()));
+        assert_eq!(record.error, None);
+    }
+
+    #[test]
+    fn run_without_input() {
+        let record = run(&solvers()[0], Err("Nope".to_owned()), None);
+        assert_eq!(record.status, Status::InputError);
+        assert_eq!(record.answer, None);
+        assert_eq!(record.error, Some("Nope".to_owned()));
+        assert_eq!(record.total_time(), Duration::default());
+    }
+
+    #[test]
+    fn run_with_timeout() {
+        // The frequency never repeats, so part 2 of day 1 never finishes.
+        let timeout = Duration::from_millis(50);
+        let record = run(&solvers()[1], Ok("+1"), Some(timeout));
+        assert_eq!((record.day, record.part), (1, 2));
+        assert_eq!(record.status, Status::Timeout);
+        assert_eq!(record.error, Some("no answer after 50ms".to_owned()));
+    }
+
+    #[test]
+    fn run_with_panic() {
+        // There are no rooms besides the starting point, so there is no furthest one.
+        let solver = solvers().into_iter().find(|s| s.day == 20).unwrap();
+        let record = run(&solver, Ok("^$"), None);
+        assert_eq!(record.status, Status::Panic);
+        assert!(record.error.unwrap().contains("None"));
+        assert_eq!(record.answer, None);
+    }
+
+    #[test]
+    fn run_in_parallel() {
+        let solvers = solvers()
+            .into_iter()
+            .filter(|s| s.day <= 2)
+            .collect::<Vec<_>>();
+        let mut inputs = Inputs::new(Source::Dir(PathBuf::from("does-not-exist")));
+        let sequential = run_all(&solvers, &mut inputs, None, 1);
+        let parallel = run_all(&solvers, &mut inputs, None, 3);
+        let order = |records: &[Record]| {
+            records
+                .iter()
+                .map(|r| (r.day, r.part, r.status, r.error.clone()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(parallel.len(), 4);
+        assert_eq!(order(&sequential), order(&parallel));
+    }
+
+    #[test]
+    fn observe_progress() {
+        let solvers = solvers().into_iter().take(2).collect::<Vec<_>>();
+        let mut inputs = Inputs::new(Source::Dir(PathBuf::from("does-not-exist")));
+        for &jobs in &[1, 2] {
+            let events = Mutex::new(Vec::new());
+            let records = run_all_observed(&solvers, &mut inputs, None, jobs, &|progress| {
+                let event = match progress {
+                    Progress::Started(i) => (i, None),
+                    Progress::Finished(i, record) => (i, Some(record.status)),
+                };
+                events.lock().unwrap().push(event);
+            });
+            let events = events.into_inner().unwrap();
+            // The parts finish in any order with several jobs, but never before they start.
+            for (i, record) in records.iter().enumerate() {
+                let started = events.iter().position(|&e| e == (i, None)).unwrap();
+                let finished = events.iter().position(|&e| e == (i, Some(record.status)));
+                assert!(started < finished.unwrap());
+            }
+            assert_eq!(events.len(), 4);
+        }
+    }
+}
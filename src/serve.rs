@@ -0,0 +1,367 @@
+//! An HTTP service exposing the solvers, built with the `serve` feature.
+//!
+//! `POST /day/{n}/part/{p}` with the puzzle input as the body runs the part through
+//! [`runner::run`], responding with the [`Record`] as JSON, like the records of `--format json`:
+//!
+//! ```json
+//! {"day":1,"part":1,"status":"ok","answer":"3","error":null,"parse_ns":2143,"solve_ns":118}
+//! ```
+//!
+//! Requests that don't get as far as the solver are answered with `{"error": "..."}`.
+//!
+//! A part that timed out can't be stopped and keeps running in the background, so the number of
+//! solves running at once (including those given up on) is capped, further requests being
+//! answered with 503 until some of them finish.
+
+use crate::runner::{self, Record, Status};
+use crate::solution::{self, Solver};
+use serde_json::json;
+use std::io::Read;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use tiny_http::{Header, Request, Response};
+
+/// The address the service listens on, unless told otherwise.
+pub const DEFAULT_ADDR: &str = "127.0.0.1:8018";
+
+/// The largest accepted input, unless told otherwise. The real inputs are all well below it.
+pub const DEFAULT_MAX_INPUT_SIZE: usize = 1024 * 1024;
+
+/// The number of requests handled at the same time, unless told otherwise.
+pub const DEFAULT_JOBS: usize = 4;
+
+/// The number of solves running at once, unless told otherwise.
+pub const DEFAULT_MAX_SOLVES: usize = 8;
+
+/// The limits of the service.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Config {
+    /// How long a part may run, `None` waits forever.
+    pub timeout: Option<Duration>,
+    /// The largest accepted input, in bytes.
+    pub max_input_size: usize,
+    /// The number of requests handled at the same time.
+    pub jobs: usize,
+    /// The number of solves running at once, counting the ones that timed out but didn't finish.
+    pub max_solves: usize,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            timeout: Some(runner::DEFAULT_TIMEOUT),
+            max_input_size: DEFAULT_MAX_INPUT_SIZE,
+            jobs: DEFAULT_JOBS,
+            max_solves: DEFAULT_MAX_SOLVES,
+        }
+    }
+}
+
+/// The solves running, shared by the workers.
+#[derive(Debug, Clone, Default)]
+pub struct Solves {
+    running: Arc<AtomicUsize>,
+}
+
+/// A running solve, freeing its place once dropped.
+struct Slot(Arc<AtomicUsize>);
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl Solves {
+    /// The number of solver threads still running, including the ones given up on.
+    pub fn running(&self) -> usize {
+        self.running.load(Ordering::SeqCst)
+    }
+
+    /// Takes a place for a solve, unless `max` of them are running already.
+    fn start(&self, max: usize) -> Option<Slot> {
+        self.running
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |running| {
+                (running < max).then_some(running + 1)
+            })
+            .ok()?;
+        Some(Slot(Arc::clone(&self.running)))
+    }
+}
+
+/// The status code and JSON body of a response.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Reply {
+    pub status: u16,
+    pub body: String,
+}
+
+impl Reply {
+    fn error(status: u16, message: &str) -> Reply {
+        Reply {
+            status,
+            body: json!({ "error": message }).to_string(),
+        }
+    }
+
+    fn too_large(config: &Config) -> Reply {
+        let message = format!("The input is larger than {} bytes", config.max_input_size);
+        Reply::error(413, &message)
+    }
+
+    fn record(record: &Record) -> Reply {
+        let status = match record.status {
+            Status::Ok => 200,
+            Status::InputError | Status::ParseError => 422,
+            Status::Timeout => 504,
+            Status::Panic => 500,
+        };
+        Reply {
+            status,
+            body: serde_json::to_string(record).expect("Records are always valid JSON"),
+        }
+    }
+}
+
+/// Finds the solver for a `/day/{n}/part/{p}` path.
+fn route<'a>(solvers: &'a [Solver], path: &str) -> Option<&'a Solver> {
+    let path = path.split('?').next().unwrap_or_default();
+    let segments = path.trim_matches('/').split('/').collect::<Vec<_>>();
+    let (day, part) = match segments.as_slice() {
+        ["day", day, "part", part] => (day, part),
+        _ => return None,
+    };
+    let (day, part) = (day.parse::<u8>().ok()?, part.parse::<u8>().ok()?);
+    solvers.iter().find(|s| s.day == day && s.part == part)
+}
+
+/// Answers a request, `body` holding up to a byte more than the size limit.
+pub fn respond(
+    solvers: &[Solver],
+    config: &Config,
+    solves: &Solves,
+    method: &str,
+    path: &str,
+    body: &[u8],
+) -> Reply {
+    let solver = match route(solvers, path) {
+        Some(solver) => solver,
+        None => return Reply::error(404, "Not found, expected /day/{n}/part/{p}"),
+    };
+    if method != "POST" {
+        return Reply::error(405, "Method not allowed, the input must be POSTed");
+    }
+    if body.len() > config.max_input_size {
+        return Reply::too_large(config);
+    }
+    let input = match std::str::from_utf8(body) {
+        Ok(input) => input,
+        Err(_) => return Reply::error(400, "The input isn't valid UTF-8"),
+    };
+    match solves.start(config.max_solves) {
+        Some(slot) => Reply::record(&runner::run_holding(
+            solver,
+            Ok(input),
+            config.timeout,
+            slot,
+        )),
+        None => Reply::error(503, "Too many parts are being solved, try again later"),
+    }
+}
+
+fn handle(solvers: &[Solver], config: &Config, solves: &Solves, mut request: Request) {
+    // Don't bother reading what's going to be rejected anyway, and never read more than a byte
+    // over the limit when the length isn't known upfront.
+    let reply = if request.body_length().unwrap_or(0) > config.max_input_size {
+        Reply::too_large(config)
+    } else {
+        let mut body = Vec::new();
+        let limit = config.max_input_size as u64 + 1;
+        let method = request.method().to_string();
+        let url = request.url().to_owned();
+        match request.as_reader().take(limit).read_to_end(&mut body) {
+            Ok(_) => respond(solvers, config, solves, &method, &url, &body),
+            Err(err) => Reply::error(400, &format!("Unable to read the input: {}", err)),
+        }
+    };
+    let content_type = Header::from_bytes("Content-Type", "application/json").unwrap();
+    let response = Response::from_string(reply.body)
+        .with_status_code(reply.status)
+        .with_header(content_type);
+    // The client might have given up already, there's nobody to tell about it.
+    let _ = request.respond(response);
+}
+
+/// A bound HTTP server, not yet handling requests.
+pub struct Server {
+    http: Arc<tiny_http::Server>,
+    config: Config,
+    solves: Solves,
+}
+
+impl Server {
+    /// Starts listening on the given address, ie. `127.0.0.1:8018`.
+    pub fn bind(addr: &str, config: Config) -> Result<Server, String> {
+        let http = tiny_http::Server::http(addr)
+            .map_err(|err| format!("Unable to listen on {}: {}", addr, err))?;
+        Ok(Server {
+            http: Arc::new(http),
+            config,
+            solves: Solves::default(),
+        })
+    }
+
+    /// The address the server listens on, useful when binding to port 0.
+    pub fn addr(&self) -> SocketAddr {
+        self.http
+            .server_addr()
+            .to_ip()
+            .expect("Only listening on IP addresses")
+    }
+
+    /// Handles requests on `config.jobs` threads, forever.
+    pub fn run(self) {
+        let workers = (0..self.config.jobs.max(1))
+            .map(|_| {
+                let http = Arc::clone(&self.http);
+                let config = self.config;
+                let solves = self.solves.clone();
+                thread::spawn(move || {
+                    let solvers = solution::solvers();
+                    for request in http.incoming_requests() {
+                        handle(&solvers, &config, &solves, request);
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        for worker in workers {
+            let _ = worker.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    fn post(path: &str, body: &str) -> (u16, Value) {
+        let config = Config {
+            max_input_size: 32,
+            ..Config::default()
+        };
+        let solves = Solves::default();
+        let reply = respond(
+            &solution::solvers(),
+            &config,
+            &solves,
+            "POST",
+            path,
+            body.as_bytes(),
+        );
+        (reply.status, serde_json::from_str(&reply.body).unwrap())
+    }
+
+    #[test]
+    fn solves_parts() {
+        let (status, json) = post("/day/1/part/2", "+1, -2, +3, +1");
+        assert_eq!(status, 200);
+        assert_eq!(json["status"], "ok");
+        assert_eq!(json["answer"], "2");
+        assert!(json["parse_ns"].is_u64() && json["solve_ns"].is_u64());
+
+        let (status, json) = post("/day/01/part/1/?verbose", "+1, -2, +3, +1");
+        assert_eq!((status, json["answer"].as_str()), (200, Some("3")));
+    }
+
+    #[test]
+    fn rejected_requests() {
+        assert_eq!(post("/day/1/part/3", "+1").0, 404);
+        assert_eq!(post("/day/25/part/2", "0,0,0,0").0, 404);
+        assert_eq!(post("/day/1", "+1").0, 404);
+        assert_eq!(post("/day/1/part/1/extra", "+1").0, 404);
+        assert_eq!(post("/day/1/part/1", &"+1\n".repeat(11)).0, 413);
+
+        let (status, json) = post("/day/1/part/1", "+1, x");
+        assert_eq!(
+            (status, json["status"].as_str()),
+            (422, Some("parse_error"))
+        );
+
+        let config = Config::default();
+        let solves = Solves::default();
+        let reply = respond(
+            &solution::solvers(),
+            &config,
+            &solves,
+            "GET",
+            "/day/1/part/1",
+            b"",
+        );
+        assert_eq!(reply.status, 405);
+        let reply = respond(
+            &solution::solvers(),
+            &config,
+            &solves,
+            "POST",
+            "/day/1/part/1",
+            b"\xff",
+        );
+        assert_eq!(reply.status, 400);
+    }
+
+    #[test]
+    fn timed_out_solves_are_capped() {
+        let config = Config {
+            timeout: Some(Duration::from_millis(1)),
+            max_solves: 2,
+            ..Config::default()
+        };
+        let solves = Solves::default();
+        let solvers = solution::solvers();
+        let post = |body: &str| {
+            respond(
+                &solvers,
+                &config,
+                &solves,
+                "POST",
+                "/day/1/part/2",
+                body.as_bytes(),
+            )
+            .status
+        };
+        // Takes a while to repeat a frequency, well over the timeout.
+        let slow = "+2000000, -1999999";
+        let statuses = (0..6).map(|_| post(slow)).collect::<Vec<_>>();
+        assert_eq!(statuses[..2], [504, 504]);
+        assert!(statuses[2..].iter().all(|&status| status == 503));
+        assert!(solves.running() <= 2);
+
+        // Once the solves given up on finish, there's room again.
+        let start = std::time::Instant::now();
+        while solves.running() > 0 {
+            assert!(
+                start.elapsed() < Duration::from_secs(120),
+                "The solves never finish"
+            );
+            thread::sleep(Duration::from_millis(10));
+        }
+        let config = Config {
+            timeout: None,
+            ..config
+        };
+        let reply = respond(
+            &solvers,
+            &config,
+            &solves,
+            "POST",
+            "/day/1/part/2",
+            b"+1, -1",
+        );
+        assert_eq!(reply.status, 200);
+        assert_eq!(solves.running(), 0);
+    }
+}
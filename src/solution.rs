@@ -0,0 +1,274 @@
+//! The [`Solution`] trait implemented by all the days, and the registry of them.
+
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::time::{Duration, Instant};
+
+use crate::{
+    day01, day02, day03, day04, day05, day06, day07, day08, day09, day10, day11, day12, day13,
+    day14, day15, day16, day17, day18, day19, day20, day21, day22, day23, day24, day25,
+};
+
+/// An error raised when a puzzle input could not be parsed.
+///
+/// The [`parse`](crate::parse) module has helpers for creating these while parsing line by line.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ParseError {
+    /// The day of the puzzle.
+    pub day: u8,
+    /// The line of the input, starting at 1.
+    pub line: usize,
+    /// The column of the line, starting at 1.
+    pub column: usize,
+    /// What was expected at that position.
+    pub expected: String,
+    /// The offending line, if known.
+    pub text: Option<String>,
+}
+
+impl ParseError {
+    /// Creates an error for the given position, without the text of the line.
+    pub fn new(day: u8, line: usize, column: usize, expected: impl Into<String>) -> ParseError {
+        ParseError {
+            day,
+            line,
+            column,
+            expected: expected.into(),
+            text: None,
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "day {}, line {}, column {}: expected {}",
+            self.day, self.line, self.column, self.expected
+        )?;
+        // Point out the position in the line below the message.
+        if let Some(text) = &self.text {
+            write!(
+                f,
+                "\n    {}\n    {}^",
+                text,
+                " ".repeat(self.column.saturating_sub(1))
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for ParseError {}
+
+/// The common interface for all the days.
+///
+/// The types are erased by [`solvers`], which wraps every part in a [`Solver`].
+/// Parsing and solving are timed separately, so all the work of reading the raw input belongs in
+/// [`Solution::parse`] to keep the timings comparable between days.
+pub trait Solution {
+    /// The day of December the puzzle belongs to.
+    const DAY: u8;
+    /// The number of parts in the puzzle, day 25 only has a single one.
+    const PARTS: u8 = 2;
+
+    /// The parsed puzzle input, shared by both parts.
+    type Input;
+    /// The answer to the first part.
+    type Answer1: Display;
+    /// The answer to the second part.
+    type Answer2: Display;
+
+    /// Parses the raw puzzle input.
+    fn parse(input: &str) -> Result<Self::Input, ParseError>;
+    /// Solves the first part of the puzzle.
+    fn part1(input: &Self::Input) -> Self::Answer1;
+    /// Solves the second part of the puzzle.
+    fn part2(input: &Self::Input) -> Self::Answer2;
+
+    /// Draws the puzzle after solving it, for the days working on a map. Shown by the dashboard.
+    fn render(_input: &Self::Input) -> Option<String> {
+        None
+    }
+}
+
+/// The answer to a part, and the time it took to get there.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Outcome {
+    /// The answer, as formatted by the solution.
+    pub answer: String,
+    /// The time spent parsing the input.
+    pub parse_time: Duration,
+    /// The time spent solving the part, once the input was parsed.
+    pub solve_time: Duration,
+}
+
+type RunFn = fn(&str) -> Result<Outcome, ParseError>;
+type ParseFn = fn(&str) -> Result<(), ParseError>;
+type RenderFn = fn(&str) -> Result<Option<String>, ParseError>;
+
+/// A single part of a day's puzzle, with the types erased so all the days can be treated the same.
+#[derive(Clone, Copy)]
+pub struct Solver {
+    /// The day of the puzzle.
+    pub day: u8,
+    /// The part of the puzzle, 1 or 2.
+    pub part: u8,
+    run: RunFn,
+    parse: ParseFn,
+    render: RenderFn,
+}
+
+impl Solver {
+    /// Parses the input and solves the part, timing both steps.
+    pub fn run(&self, input: &str) -> Result<Outcome, ParseError> {
+        (self.run)(input)
+    }
+
+    /// Only parses the input, checking whether it's valid.
+    pub fn parse(&self, input: &str) -> Result<(), ParseError> {
+        (self.parse)(input)
+    }
+
+    /// Parses the input and draws the puzzle, if the day has something to show.
+    pub fn render(&self, input: &str) -> Result<Option<String>, ParseError> {
+        (self.render)(input)
+    }
+
+    /// Parses the input and solves the part, returning the answer.
+    pub fn solve(&self, input: &str) -> Result<String, ParseError> {
+        self.run(input).map(|outcome| outcome.answer)
+    }
+}
+
+fn run_part<S: Solution, A: Display>(
+    input: &str,
+    part: fn(&S::Input) -> A,
+) -> Result<Outcome, ParseError> {
+    let before = Instant::now();
+    let input = S::parse(input)?;
+    let parse_time = before.elapsed();
+
+    let before = Instant::now();
+    let answer = part(&input);
+    let solve_time = before.elapsed();
+
+    Ok(Outcome {
+        answer: answer.to_string(),
+        parse_time,
+        solve_time,
+    })
+}
+
+fn run_part1<S: Solution>(input: &str) -> Result<Outcome, ParseError> {
+    run_part::<S, _>(input, S::part1)
+}
+
+fn run_part2<S: Solution>(input: &str) -> Result<Outcome, ParseError> {
+    run_part::<S, _>(input, S::part2)
+}
+
+fn parse_only<S: Solution>(input: &str) -> Result<(), ParseError> {
+    S::parse(input).map(|_| ())
+}
+
+fn render<S: Solution>(input: &str) -> Result<Option<String>, ParseError> {
+    S::parse(input).map(|input| S::render(&input))
+}
+
+/// Registers the parts of the given solution.
+fn register<S: Solution>(solvers: &mut Vec<Solver>) {
+    let parts: [RunFn; 2] = [run_part1::<S>, run_part2::<S>];
+    for (part, &run) in (1..=S::PARTS).zip(parts.iter()) {
+        solvers.push(Solver {
+            day: S::DAY,
+            part,
+            run,
+            parse: parse_only::<S>,
+            render: render::<S>,
+        });
+    }
+}
+
+/// All the known solvers, in day/part order.
+pub fn solvers() -> Vec<Solver> {
+    let mut solvers = vec![];
+    register::<day01::Day01>(&mut solvers);
+    register::<day02::Day02>(&mut solvers);
+    register::<day03::Day03>(&mut solvers);
+    register::<day04::Day04>(&mut solvers);
+    register::<day05::Day05>(&mut solvers);
+    register::<day06::Day06>(&mut solvers);
+    register::<day07::Day07>(&mut solvers);
+    register::<day08::Day08>(&mut solvers);
+    register::<day09::Day09>(&mut solvers);
+    register::<day10::Day10>(&mut solvers);
+    register::<day11::Day11>(&mut solvers);
+    register::<day12::Day12>(&mut solvers);
+    register::<day13::Day13>(&mut solvers);
+    register::<day14::Day14>(&mut solvers);
+    register::<day15::Day15>(&mut solvers);
+    register::<day16::Day16>(&mut solvers);
+    register::<day17::Day17>(&mut solvers);
+    register::<day18::Day18>(&mut solvers);
+    register::<day19::Day19>(&mut solvers);
+    register::<day20::Day20>(&mut solvers);
+    register::<day21::Day21>(&mut solvers);
+    register::<day22::Day22>(&mut solvers);
+    register::<day23::Day23>(&mut solvers);
+    register::<day24::Day24>(&mut solvers);
+    register::<day25::Day25>(&mut solvers);
+    solvers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn all_days_are_registered() {
+        let solvers = solvers();
+        assert_eq!(solvers.len(), 49);
+        for (i, solver) in solvers.iter().enumerate() {
+            assert_eq!(solver.day as usize, i / 2 + 1);
+            assert_eq!(solver.part as usize, i % 2 + 1);
+        }
+    }
+
+    #[test]
+    fn solve_through_the_registry() {
+        let solvers = solvers();
+        assert_eq!(solvers[0].solve("+1, -2, +3, +1").unwrap(), "3");
+        assert_eq!(solvers[1].solve("+3, +3, +4, -2, -4").unwrap(), "10");
+        assert_eq!(solvers[1].solve(&day01::get_input()).unwrap(), "71892");
+        assert!(solvers[0].parse("+1, -2").is_ok());
+        assert_eq!(solvers[0].parse("+1, x").unwrap_err().column, 4);
+        assert_eq!(solvers[0].render("+1, -2"), Ok(None));
+        let day18 = solvers.iter().find(|s| s.day == 18 && s.part == 1).unwrap();
+        assert_eq!(day18.render(".#\n|.\n").unwrap().unwrap(), "..\n|.\n");
+    }
+
+    #[test]
+    fn display_parse_errors() {
+        let error = ParseError {
+            text: Some("+1, x".to_owned()),
+            ..ParseError::new(1, 2, 5, "a number")
+        };
+        assert_eq!(
+            error.to_string(),
+            "day 1, line 2, column 5: expected a number\n    +1, x\n        ^"
+        );
+        // The columns start at 1, a 0 points at the start of the line as well.
+        let error = ParseError { column: 0, ..error };
+        assert!(error.to_string().ends_with("\n    +1, x\n    ^"));
+    }
+
+    proptest! {
+        /// Whatever the input, the parsers return an error instead of panicking.
+        #[test]
+        fn parsers_never_panic(day in 1..=25usize, input in "[ -~\n]{0,80}") {
+            let _ = solvers()[(day - 1) * 2].parse(&input);
+        }
+    }
+}
@@ -0,0 +1,185 @@
+//! Checking the answers against a file of expected answers.
+//!
+//! The answers file is either TOML or JSON (based on the extension), with a table per day and
+//! the answers as strings or numbers:
+//!
+//! ```toml
+//! [day01]
+//! part1 = 516
+//! part2 = 71892
+//!
+//! [day07]
+//! part1 = "OCPUEFIXHRGWDZABTQJYMNKVSL"
+//! ```
+
+use crate::runner::Record;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Answer {
+    Text(String),
+    Number(i64),
+}
+
+type Days = BTreeMap<String, BTreeMap<String, Answer>>;
+
+/// The expected answers, by day and part.
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct Answers {
+    answers: BTreeMap<(u8, u8), String>,
+}
+
+fn parse_key(key: &str, prefix: &str, range: (u8, u8)) -> Option<u8> {
+    let n = key.strip_prefix(prefix)?.parse().ok()?;
+    if (range.0..=range.1).contains(&n) {
+        Some(n)
+    } else {
+        None
+    }
+}
+
+impl Answers {
+    /// Reads the answers from a `.toml` or `.json` file.
+    pub fn load(path: &Path) -> Result<Answers, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|err| format!("Unable to read {}: {}", path.display(), err))?;
+        let toml = path.extension().is_some_and(|ext| ext == "toml");
+        Answers::parse(&content, toml).map_err(|err| format!("{}: {}", path.display(), err))
+    }
+
+    /// Parses the answers from TOML or JSON.
+    pub fn parse(content: &str, toml: bool) -> Result<Answers, String> {
+        let days: Days = if toml {
+            toml::from_str(content).map_err(|err| err.to_string())?
+        } else {
+            serde_json::from_str(content).map_err(|err| err.to_string())?
+        };
+        let mut answers = BTreeMap::new();
+        for (day_key, parts) in days {
+            let day = parse_key(&day_key, "day", (1, 25))
+                .ok_or_else(|| format!("Invalid day: {}, expected day01-day25", day_key))?;
+            for (part_key, answer) in parts {
+                let part = parse_key(&part_key, "part", (1, 2)).ok_or_else(|| {
+                    format!(
+                        "Invalid part: {}.{}, expected part1 or part2",
+                        day_key, part_key
+                    )
+                })?;
+                let answer = match answer {
+                    Answer::Text(text) => text,
+                    Answer::Number(n) => n.to_string(),
+                };
+                answers.insert((day, part), answer);
+            }
+        }
+        Ok(Answers { answers })
+    }
+
+    /// The expected answer for the day/part, if known.
+    pub fn get(&self, day: u8, part: u8) -> Option<&str> {
+        self.answers.get(&(day, part)).map(String::as_str)
+    }
+}
+
+/// Whether the answer matches the expected one, ignoring surrounding whitespace.
+pub fn matches(answer: &str, expected: &str) -> bool {
+    answer.trim() == expected.trim()
+}
+
+/// Fills in the expected answer and the verdict of every record with a known answer, returning
+/// the number of mismatches.
+pub fn check(records: &mut [Record], answers: &Answers) -> usize {
+    let mut mismatches = 0;
+    for record in records {
+        if let Some(expected) = answers.get(record.day, record.part) {
+            let correct = record
+                .answer
+                .as_ref()
+                .is_some_and(|answer| matches(answer, expected));
+            if !correct {
+                mismatches += 1;
+            }
+            record.expected = Some(expected.to_owned());
+            record.correct = Some(correct);
+        }
+    }
+    mismatches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::Status;
+    use std::time::Duration;
+
+    fn record(day: u8, answer: Option<&str>) -> Record {
+        Record {
+            day,
+            part: 1,
+            status: if answer.is_some() {
+                Status::Ok
+            } else {
+                Status::InputError
+            },
+            answer: answer.map(str::to_owned),
+            error: None,
+            expected: None,
+            correct: None,
+            parse_time: Duration::default(),
+            solve_time: Duration::default(),
+        }
+    }
+
+    #[test]
+    fn parse_answers() {
+        let toml = Answers::parse(
+            "[day01]\npart1 = 516\npart2 = \"71892\"\n\n[day10]\npart1 = '''\n#..#\n'''\n",
+            true,
+        )
+        .unwrap();
+        let json = Answers::parse(
+            r##"{"day01": {"part1": 516, "part2": "71892"}, "day10": {"part1": "#..#\n"}}"##,
+            false,
+        )
+        .unwrap();
+        assert_eq!(toml, json);
+        assert_eq!(toml.get(1, 1), Some("516"));
+        assert_eq!(toml.get(1, 2), Some("71892"));
+        assert_eq!(toml.get(10, 1), Some("#..#\n"));
+        assert_eq!(toml.get(10, 2), None);
+
+        assert!(Answers::parse("[day26]\npart1 = 1", true).is_err());
+        assert!(Answers::parse("[day01]\npart3 = 1", true).is_err());
+        assert!(Answers::parse("[day01]\npart1 = [1]", true).is_err());
+    }
+
+    #[test]
+    fn check_records() {
+        let answers = Answers::parse("[day01]\npart1 = 516\n[day02]\npart1 = 1", true).unwrap();
+        let mut records = vec![
+            record(1, Some("516")),
+            record(2, Some("2")),
+            record(3, Some("3")),
+        ];
+        assert_eq!(check(&mut records, &answers), 1);
+        assert_eq!(records[0].correct, Some(true));
+        assert_eq!(records[1].correct, Some(false));
+        assert_eq!(records[1].expected, Some("1".to_owned()));
+        assert_eq!(records[2].correct, None);
+
+        let mut records = vec![record(1, None)];
+        assert_eq!(check(&mut records, &answers), 1);
+    }
+
+    #[test]
+    fn bundled_answers() {
+        let answers = Answers::load(Path::new("answers.toml")).unwrap();
+        assert_eq!(answers.get(7, 1), Some("OCPUEFIXHRGWDZABTQJYMNKVSL"));
+        assert!(answers.get(10, 1).unwrap().contains(".#....#..######"));
+        assert_eq!(answers.get(25, 2), None);
+    }
+}
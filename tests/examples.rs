@@ -0,0 +1,21 @@
+//! Generated by `cargo run -- extract`, runs the examples in `fixtures/` through the solvers.
+
+use adventofcode2018::examples;
+
+#[test]
+fn day10() {
+    let fixture = include_str!("../fixtures/day10.toml");
+    examples::check(10, fixture).unwrap();
+}
+
+#[test]
+fn day13() {
+    let fixture = include_str!("../fixtures/day13.toml");
+    examples::check(13, fixture).unwrap();
+}
+
+#[test]
+fn day23() {
+    let fixture = include_str!("../fixtures/day23.toml");
+    examples::check(23, fixture).unwrap();
+}
@@ -0,0 +1,209 @@
+//! Runs the fetcher against a local stand-in for the Advent of Code server.
+
+use adventofcode2018::fetch::{Fetched, Fetcher};
+use adventofcode2018::input;
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// A request received by the server.
+#[derive(Debug, Clone)]
+struct Request {
+    path: String,
+    cookie: Option<String>,
+    at: Instant,
+}
+
+/// A tiny HTTP server, serving the inputs of days 1-5 to the `secret` session.
+struct Server {
+    url: String,
+    requests: Arc<Mutex<Vec<Request>>>,
+}
+
+impl Server {
+    fn start() -> Server {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&requests);
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let path = line.split(' ').nth(1).unwrap_or_default().to_owned();
+                let mut cookie = None;
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header.trim().is_empty() {
+                        break;
+                    }
+                    if let Some(value) = header.strip_prefix("Cookie: ") {
+                        cookie = Some(value.trim().to_owned());
+                    }
+                }
+
+                let (status, body) = Server::respond(&path, cookie.as_deref());
+                log.lock().unwrap().push(Request {
+                    path,
+                    cookie,
+                    at: Instant::now(),
+                });
+                let response = format!(
+                    "HTTP/1.1 {}\r\nContent-Length: {}\r\nRetry-After: 60\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        Server { url, requests }
+    }
+
+    fn respond(path: &str, cookie: Option<&str>) -> (&'static str, String) {
+        if cookie != Some("session=secret") {
+            return ("400 Bad Request", "Please log in.".to_owned());
+        }
+        let day = path
+            .strip_prefix("/2018/day/")
+            .and_then(|rest| rest.strip_suffix("/input"))
+            .and_then(|day| day.parse::<u8>().ok());
+        match day {
+            Some(day @ 1..=5) => ("200 OK", format!("input for day {}\n", day)),
+            Some(13) => ("429 Too Many Requests", String::new()),
+            _ => ("404 Not Found", "Not found.".to_owned()),
+        }
+    }
+
+    fn requests(&self) -> Vec<Request> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+/// Creates an empty directory for the test to download into.
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("aoc2018-fetch-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn downloads_into_the_cache() {
+    let server = Server::start();
+    let dir = temp_dir("cache");
+    let mut fetcher = Fetcher::new("secret", &dir)
+        .base_url(&server.url)
+        .delay(Duration::from_millis(0));
+
+    assert_eq!(fetcher.fetch(2), Ok(Fetched::Downloaded));
+    assert_eq!(
+        fs::read_to_string(input::day_path(&dir, 2)).unwrap(),
+        "input for day 2\n"
+    );
+    let requests = server.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].path, "/2018/day/2/input");
+    assert_eq!(requests[0].cookie.as_deref(), Some("session=secret"));
+
+    // Downloaded inputs are never fetched again, not even by another fetcher.
+    assert_eq!(fetcher.fetch(2), Ok(Fetched::Cached));
+    let mut fetcher = Fetcher::new("secret", &dir).base_url(&server.url);
+    assert_eq!(fetcher.fetch(2), Ok(Fetched::Cached));
+    assert_eq!(server.requests().len(), 1);
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn waits_between_requests() {
+    let server = Server::start();
+    let dir = temp_dir("delay");
+    let mut fetcher = Fetcher::new("secret", &dir)
+        .base_url(&server.url)
+        .delay(Duration::from_millis(300));
+    for day in 1..=3 {
+        assert_eq!(fetcher.fetch(day), Ok(Fetched::Downloaded));
+    }
+    let requests = server.requests();
+    for pair in requests.windows(2) {
+        assert!(pair[1].at - pair[0].at >= Duration::from_millis(300));
+    }
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn failed_downloads_are_not_cached() {
+    let server = Server::start();
+    let dir = temp_dir("errors");
+    let mut fetcher = Fetcher::new("secret", &dir)
+        .base_url(&server.url)
+        .delay(Duration::from_millis(0));
+    assert_eq!(fetcher.fetch(6).unwrap_err(), "Day 6 isn't available yet");
+    assert_eq!(
+        fetcher.fetch(13).unwrap_err(),
+        "Rate limited by the server, try again in 60 seconds"
+    );
+    assert!(!input::day_path(&dir, 6).exists());
+    assert!(!input::day_path(&dir, 13).exists());
+
+    let mut fetcher = Fetcher::new("wrong", &dir).base_url(&server.url);
+    assert!(fetcher
+        .fetch(1)
+        .unwrap_err()
+        .contains("session token was rejected"));
+    assert!(!input::day_path(&dir, 1).exists());
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn fetch_command() {
+    let server = Server::start();
+    let dir = temp_dir("command");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(input::day_path(&dir, 4), "edited by hand").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_adventofcode2018"))
+        .args(["fetch", "--days", "4-5", "--base-url", &server.url])
+        .arg("--output-dir")
+        .arg(&dir)
+        .env("AOC_SESSION", "secret")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "Day 04: already downloaded\nDay 05: downloaded\n"
+    );
+    assert_eq!(
+        fs::read_to_string(input::day_path(&dir, 4)).unwrap(),
+        "edited by hand"
+    );
+    assert_eq!(
+        fs::read_to_string(input::day_path(&dir, 5)).unwrap(),
+        "input for day 5\n"
+    );
+    assert_eq!(server.requests().len(), 1);
+
+    // Stops at the first day that can't be downloaded.
+    let output = Command::new(env!("CARGO_BIN_EXE_adventofcode2018"))
+        .args(["fetch", "--days", "6-7", "--session", "secret"])
+        .args(["--base-url", &server.url])
+        .arg("--output-dir")
+        .arg(&dir)
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "Day 6 isn't available yet\n"
+    );
+    assert_eq!(server.requests().len(), 2);
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,97 @@
+//! Talks to the HTTP service over a socket, run with `cargo test --features serve`.
+#![cfg(feature = "serve")]
+
+use adventofcode2018::serve::{Config, Server};
+use serde_json::Value;
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::thread;
+use std::time::Duration;
+
+fn start(config: Config) -> SocketAddr {
+    let server = Server::bind("127.0.0.1:0", config).unwrap();
+    let addr = server.addr();
+    thread::spawn(move || server.run());
+    addr
+}
+
+/// Sends a request, returning the status code and the JSON body of the response.
+fn request(addr: SocketAddr, method: &str, path: &str, body: &str) -> (u16, Value) {
+    let mut stream = TcpStream::connect(addr).unwrap();
+    write!(
+        stream,
+        "{} {} HTTP/1.1\r\nHost: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        method,
+        path,
+        addr,
+        body.len(),
+        body
+    )
+    .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+
+    let status = response.split(' ').nth(1).unwrap().parse().unwrap();
+    let (headers, body) = response.split_at(response.find("\r\n\r\n").unwrap() + 4);
+    assert!(headers.contains("Content-Type: application/json"));
+    (status, serde_json::from_str(body).unwrap())
+}
+
+#[test]
+fn solves_posted_inputs() {
+    let addr = start(Config::default());
+    let (status, json) = request(addr, "POST", "/day/1/part/2", "+1, -2, +3, +1");
+    assert_eq!(status, 200);
+    assert_eq!(json["day"], 1);
+    assert_eq!(json["part"], 2);
+    assert_eq!(json["answer"], "2");
+    assert!(json["parse_ns"].is_u64() && json["solve_ns"].is_u64());
+
+    let (status, json) = request(addr, "POST", "/day/1/part/1", "+1, +x");
+    assert_eq!(status, 422);
+    assert_eq!(json["status"], "parse_error");
+    assert!(json["error"].as_str().unwrap().contains("line 1"));
+
+    // Too many marbles to keep in memory, rejected instead of taking the server down.
+    let input = "1 players; last marble is worth 10000000000 points";
+    for path in ["/day/9/part/1", "/day/9/part/2"] {
+        let (status, json) = request(addr, "POST", path, input);
+        assert_eq!(status, 422);
+        assert_eq!(json["status"], "parse_error");
+    }
+    assert_eq!(
+        request(
+            addr,
+            "POST",
+            "/day/9/part/1",
+            "9 players; last marble is worth 25 points"
+        )
+        .1["answer"],
+        "32"
+    );
+
+    let (status, json) = request(addr, "GET", "/day/1/part/1", "");
+    assert_eq!(status, 405);
+    assert!(json["error"].is_string());
+    assert_eq!(request(addr, "POST", "/day/26/part/1", "").0, 404);
+}
+
+#[test]
+fn enforces_the_limits() {
+    let addr = start(Config {
+        timeout: Some(Duration::from_millis(50)),
+        max_input_size: 16,
+        jobs: 2,
+        ..Config::default()
+    });
+    let (status, json) = request(addr, "POST", "/day/1/part/1", "+1, +1, +1, +1, +1, +1");
+    assert_eq!(status, 413);
+    assert_eq!(json["error"], "The input is larger than 16 bytes");
+
+    let (status, json) = request(addr, "POST", "/day/14/part/2", "704321");
+    assert_eq!(status, 504);
+    assert_eq!(json["status"], "timeout");
+
+    // The timed out part doesn't keep the others from being served.
+    assert_eq!(request(addr, "POST", "/day/14/part/1", "9").0, 200);
+}